@@ -1,6 +1,7 @@
 from argparse import ArgumentParser, RawTextHelpFormatter
 from pathlib import Path
 from shutil import copyfile
+import json
 import os
 import logging
 from zipfile import ZipFile
@@ -11,6 +12,18 @@ from chardet.universaldetector import UniversalDetector
 from chunker import write_chunker  # type: ignore
 
 
+CONFIG_DIR = Path(__file__).absolute().parent.parent / "nlprule" / "configs"
+
+
+    # every supported language has a descriptor in nlprule/configs/<lang_code>/
+    # every supported language has a descriptor, see nlprule/configs/<lang_code>/language.json
+    path = CONFIG_DIR / lang_code / "language.json"
+    if not path.exists():
+        raise ValueError(f"{path} does not exist. Add the configs for '{lang_code}'.")
+
+    return json.load(open(path))
+
+
 def write_freqlist(f, lang_code, top_n=1000):
     # wordfreq lists are lowercase so we add title case manually
     for word in wordfreq.top_n_list(lang_code, top_n):
@@ -35,6 +48,10 @@ def copy_lt_files(out_dir, lt_dir, lang_code):
         (lt_resource_dir / "removed.txt", tag_dir / "removed.txt"),
         (lt_resource_dir / "disambiguation.xml", out_dir / "disambiguation.xml"),
         (lt_resource_dir / "multiwords.txt", tag_dir / "multiwords.txt",),
+        (
+            lt_resource_dir / "tokenize_exceptions.txt",
+            out_dir / "tokenize_exceptions.txt",
+        ),
         (lt_rule_dir / "grammar.xml", out_dir / "grammar.xml"),
     ]:
         if source.exists():
@@ -59,6 +76,19 @@ def copy_lt_files(out_dir, lt_dir, lang_code):
         canonicalize(out_dir / xmlfile)
 
 
+def get_lt_version(lt_dir):
+    manifest = ZipFile(lt_dir / "libs" / "languagetool-core.jar").read(
+        "META-INF/MANIFEST.MF"
+    )
+
+    for line in manifest.decode("utf-8").splitlines():
+        if line.startswith("Implementation-Version:"):
+            return line.split(":", 1)[1].strip()
+
+    logging.warning("LanguageTool version not found in the manifest.")
+    return None
+
+
 def dump_dictionary(out_path, lt_dir, tag_dict_path, tag_info_path):
     # dump dictionary, see https://dev.languagetool.org/developing-a-tagger-dictionary
     os.system(
@@ -112,12 +142,18 @@ Requirements:
     parser.add_argument(
         "--tag_dict_path",
         type=lambda p: Path(p).absolute(),
-        help="Path to a tagger dictionary .dict file.",
+        default=None,
+        help="""
+Path to a tagger dictionary .dict file.
+
+Defaults to the `tag_dict` of the language descriptor in the LanguageTool resource directory.
+""",
     )
     parser.add_argument(
         "--tag_info_path",
         type=lambda p: Path(p).absolute(),
-        help="Path to the accompanying tagger dictionary .info file.",
+        default=None,
+        help="Path to the accompanying tagger dictionary .info file. Defaults to the .dict path with an .info extension.",
     )
     parser.add_argument(
         "--chunker_token_model",
@@ -145,6 +181,28 @@ Only needed if the language requires a chunker (e. g. English).
     )
 
     args = parser.parse_args()
+    descriptor = load_language_descriptor(args.lang_code)
+
+    if args.tag_dict_path is None:
+        args.tag_dict_path = (
+            args.lt_dir
+            / "org"
+            / "languagetool"
+            / "resource"
+            / args.lang_code
+            / descriptor["tag_dict"]
+        )
+    if args.tag_info_path is None:
+        args.tag_info_path = args.tag_dict_path.with_suffix(".info")
+
+    has_chunker_models = (
+        args.chunker_token_model is not None
+        and args.chunker_pos_model is not None
+        and args.chunker_chunk_model is not None
+    )
+    if descriptor.get("chunker", False) and not has_chunker_models:
+        parser.error(f"{descriptor['name']} needs a chunker. Pass the chunker models.")
+
     args.out_dir.mkdir(parents=True)
 
     write_freqlist(open(args.out_dir / "common.txt", "w"), args.lang_code)
@@ -155,11 +213,7 @@ Only needed if the language requires a chunker (e. g. English).
         args.tag_dict_path,
         args.tag_info_path,
     )
-    if (
-        args.chunker_token_model is not None
-        and args.chunker_pos_model is not None
-        and args.chunker_chunk_model is not None
-    ):
+    if has_chunker_models:
         write_chunker(
             args.out_dir / "chunker.json",
             args.chunker_token_model,
@@ -169,4 +223,8 @@ Only needed if the language requires a chunker (e. g. English).
 
     open(args.out_dir / "lang_code.txt", "w").write(args.lang_code)
 
+    lt_version = get_lt_version(args.lt_dir)
+    if lt_version is not None:
+        open(args.out_dir / "lt_version.txt", "w").write(lt_version)
+
     print("Success!")
@@ -1,4 +1,4 @@
-//! Compiles the language build configurations in configs/ into two files (one for the tokenizer, one for the rules)
+//! Compiles the language build configurations in configs/ into one file per kind of config (e. g. one for the tokenizer, one for the rules)
 //! so they can be inlined. These configs are included at compile time because they define the neccessary parameters to
 //! run the rules for a language correctly. They are NOT user configuration.
 
@@ -20,6 +20,7 @@ fn main() {
         ("tokenizer.json", "tokenizer_configs.json"),
         ("rules.json", "rules_configs.json"),
         ("tagger.json", "tagger_configs.json"),
+        ("language.json", "language_configs.json"),
     ] {
         let mut config_map: HashMap<String, serde_json::Value> = HashMap::new();
 
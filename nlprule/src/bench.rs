@@ -0,0 +1,287 @@
+//! Measures the throughput and latency of the individual stages of the pipeline on a user-supplied corpus.
+//! Intended for sizing hardware and comparing binaries, see [run].
+
+use crate::{
+    rules::Rules,
+    tokenizer::{finalize, Tokenizer},
+    utils::parallelism,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    ops::AddAssign,
+    time::{Duration, Instant},
+};
+
+/// Configuration for a benchmark run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchConfig {
+    /// How often to process the whole corpus before starting measurements.
+    pub warmup_iterations: usize,
+    /// How often to process the whole corpus while measuring.
+    pub iterations: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        BenchConfig {
+            warmup_iterations: 1,
+            iterations: 3,
+        }
+    }
+}
+
+/// Throughput and latency of one stage of the pipeline.
+/// Latencies are measured per text of the corpus.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageReport {
+    /// Number of sentences processed per second.
+    pub sentences_per_sec: f64,
+    /// Median latency in milliseconds.
+    pub p50_ms: f64,
+    /// 99th percentile latency in milliseconds.
+    pub p99_ms: f64,
+    /// Total time spent in this stage in seconds.
+    pub total_secs: f64,
+}
+
+impl StageReport {
+    fn new(mut latencies: Vec<Duration>, n_sentences: usize) -> Self {
+        latencies.sort_unstable();
+
+        let percentile = |p: f64| -> f64 {
+            if latencies.is_empty() {
+                return 0.;
+            }
+
+            let index = ((latencies.len() - 1) as f64 * p).round() as usize;
+            latencies[index].as_secs_f64() * 1000.
+        };
+
+        let total_secs: f64 = latencies.iter().map(Duration::as_secs_f64).sum();
+
+        StageReport {
+            sentences_per_sec: if total_secs > 0. {
+                n_sentences as f64 / total_secs
+            } else {
+                0.
+            },
+            p50_ms: percentile(0.5),
+            p99_ms: percentile(0.99),
+            total_secs,
+        }
+    }
+}
+
+/// The time spent on one text (or sentence) in the individual stages of the pipeline in microseconds.
+/// Collected if [ApplyOptions::timing][crate::rules::ApplyOptions::timing] is set or by [Tokenizer::pipe_timed].
+/// Measured on the thread which does the work, so it is also correct if texts are processed in parallel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timing {
+    /// Sentence splitting, tagging and chunking.
+    pub tokenize: u64,
+    /// Rule-based disambiguation.
+    pub disambiguate: u64,
+    /// Application of the grammar rules.
+    pub rules: u64,
+    /// The total time including overhead not attributed to a stage.
+    pub total: u64,
+}
+
+impl Timing {
+    pub(crate) fn micros(duration: Duration) -> u64 {
+        duration.as_micros() as u64
+    }
+}
+
+impl AddAssign for Timing {
+    fn add_assign(&mut self, other: Timing) {
+        self.tokenize += other.tokenize;
+        self.disambiguate += other.disambiguate;
+        self.rules += other.rules;
+        self.total += other.total;
+    }
+}
+
+/// Information about the environment a benchmark was run in, so results are comparable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentInfo {
+    /// The version of nlprule.
+    pub version: String,
+    /// Whether parallelism is enabled (see the `NLPRULE_PARALLELISM` environment variable).
+    pub parallelism: bool,
+    /// The number of threads available for parallel processing.
+    pub threads: usize,
+    /// The SHA-256 checksum of the tokenizer binary. Not known to [run] since the tokenizer is already loaded,
+    /// set by the caller e. g. the `bench` binary.
+    pub tokenizer_hash: Option<String>,
+    /// The SHA-256 checksum of the rules binary. Set by the caller like `tokenizer_hash`.
+    pub rules_hash: Option<String>,
+}
+
+/// The result of a benchmark run. See [run].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// The configuration used for this run.
+    pub config: BenchConfig,
+    /// Information about the environment.
+    pub environment: EnvironmentInfo,
+    /// Number of texts in the corpus.
+    pub n_texts: usize,
+    /// Number of sentences in the corpus.
+    pub n_sentences: usize,
+    /// Sentence splitting, tagging and chunking.
+    pub tokenization: StageReport,
+    /// Rule-based disambiguation.
+    pub disambiguation: StageReport,
+    /// Application of the grammar rules.
+    pub rules: StageReport,
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "nlprule {} ({} threads, parallelism {})",
+            self.environment.version,
+            self.environment.threads,
+            if self.environment.parallelism {
+                "on"
+            } else {
+                "off"
+            }
+        )?;
+        if let (Some(tokenizer_hash), Some(rules_hash)) = (
+            &self.environment.tokenizer_hash,
+            &self.environment.rules_hash,
+        ) {
+            writeln!(f, "tokenizer {}, rules {}", tokenizer_hash, rules_hash)?;
+        }
+        writeln!(
+            f,
+            "{} texts, {} sentences, {} iterations ({} warmup)",
+            self.n_texts, self.n_sentences, self.config.iterations, self.config.warmup_iterations
+        )?;
+
+        for (name, stage) in &[
+            ("tokenization", &self.tokenization),
+            ("disambiguation", &self.disambiguation),
+            ("rules", &self.rules),
+        ] {
+            writeln!(
+                f,
+                "{:<16}{:>12.1} sentences/s  p50 {:>8.3}ms  p99 {:>8.3}ms",
+                name, stage.sentences_per_sec, stage.p50_ms, stage.p99_ms
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs the full pipeline on each text of the `corpus` and measures the time spent in
+/// tokenization, disambiguation and rule application separately.
+pub fn run<S: AsRef<str>>(
+    tokenizer: &Tokenizer,
+    rules: &Rules,
+    corpus: &[S],
+    config: &BenchConfig,
+) -> BenchReport {
+    let mut tokenization = Vec::new();
+    let mut disambiguation = Vec::new();
+    let mut application = Vec::new();
+    let mut n_sentences = 0;
+
+    for iteration in 0..(config.warmup_iterations + config.iterations) {
+        let is_warmup = iteration < config.warmup_iterations;
+
+        for text in corpus {
+            let text = text.as_ref();
+
+            let start = Instant::now();
+            let sentences = tokenizer.sentencize(text);
+            let tokenization_time = start.elapsed();
+
+            let start = Instant::now();
+            let sentences: Vec<_> = sentences
+                .into_iter()
+                .map(|tokens| finalize(tokenizer.disambiguate(tokens)))
+                .collect();
+            let disambiguation_time = start.elapsed();
+
+            let start = Instant::now();
+            for tokens in sentences.iter() {
+                rules.apply(tokens, tokenizer);
+            }
+            let application_time = start.elapsed();
+
+            if !is_warmup {
+                tokenization.push(tokenization_time);
+                disambiguation.push(disambiguation_time);
+                application.push(application_time);
+                n_sentences += sentences.len();
+            }
+        }
+    }
+
+    BenchReport {
+        config: config.clone(),
+        environment: EnvironmentInfo {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            parallelism: parallelism::get_parallelism(),
+            threads: rayon::current_num_threads(),
+            tokenizer_hash: None,
+            rules_hash: None,
+        },
+        n_texts: corpus.len(),
+        n_sentences: n_sentences / config.iterations.max(1),
+        tokenization: StageReport::new(tokenization, n_sentences),
+        disambiguation: StageReport::new(disambiguation, n_sentences),
+        rules: StageReport::new(application, n_sentences),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_report_percentiles() {
+        let latencies: Vec<_> = (1..=100).map(Duration::from_millis).collect();
+        let report = StageReport::new(latencies, 50);
+
+        assert!((report.p50_ms - 51.).abs() < 1e-6);
+        assert!((report.p99_ms - 99.).abs() < 1e-6);
+        assert!((report.total_secs - 5.05).abs() < 1e-6);
+        assert!((report.sentences_per_sec - 50. / 5.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn timings_add_up() {
+        let mut timing = Timing {
+            tokenize: 1,
+            disambiguate: 2,
+            rules: 3,
+            total: 7,
+        };
+        timing += timing;
+
+        assert_eq!(
+            timing,
+            Timing {
+                tokenize: 2,
+                disambiguate: 4,
+                rules: 6,
+                total: 14,
+            }
+        );
+    }
+
+    #[test]
+    fn stage_report_handles_empty_corpus() {
+        let report = StageReport::new(Vec::new(), 0);
+
+        assert_eq!(report.p50_ms, 0.);
+        assert_eq!(report.sentences_per_sec, 0.);
+    }
+}
@@ -0,0 +1,90 @@
+use clap::Clap;
+use nlprule::{
+    bench::{self, BenchConfig},
+    rules::Rules,
+    schema::Versioned,
+    tokenizer::Tokenizer,
+};
+
+#[derive(Clap)]
+#[clap(
+    version = "1.0",
+    author = "Benjamin Minixhofer <bminixhofer@gmail.com>"
+)]
+struct Opts {
+    /// Path to a corpus file with one text per line.
+    corpus: String,
+    /// The language code of the binaries to use e. g. "en". Loads `<storage>/<lang>_tokenizer.bin`
+    /// and `<storage>/<lang>_rules.bin` unless `--tokenizer` or `--rules` is given.
+    #[clap(long, short)]
+    lang: Option<String>,
+    /// The directory the binaries for `--lang` are stored in.
+    #[clap(long, default_value = "storage")]
+    storage: String,
+    #[clap(long, short)]
+    tokenizer: Option<String>,
+    #[clap(long, short)]
+    rules: Option<String>,
+    #[clap(long, default_value = "1")]
+    warmup: usize,
+    #[clap(long, default_value = "3")]
+    iterations: usize,
+    /// Print the report as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+impl Opts {
+    fn binary_path(&self, path: &Option<String>, kind: &str) -> String {
+        match (path, &self.lang) {
+            (Some(path), _) => path.clone(),
+            (None, Some(lang)) => format!("{}/{}_{}.bin", self.storage, lang, kind),
+            (None, None) => panic!("either --lang or --{} must be given", kind),
+        }
+    }
+}
+
+/// The SHA-256 checksum of the data as hex string.
+fn sha256_hex(data: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, data)
+        .as_ref()
+        .iter()
+        .map(|x| format!("{:02x}", x))
+        .collect()
+}
+
+fn main() {
+    env_logger::init();
+    let opts = Opts::parse();
+
+    // the binaries are hashed as they are stored, the loaded structures can not be hashed reproducibly
+    let tokenizer_data = std::fs::read(opts.binary_path(&opts.tokenizer, "tokenizer")).unwrap();
+    let rules_data = std::fs::read(opts.binary_path(&opts.rules, "rules")).unwrap();
+
+    let tokenizer = Tokenizer::from_reader(&tokenizer_data[..]).unwrap();
+    let rules = Rules::from_reader(&rules_data[..]).unwrap();
+
+    let corpus = std::fs::read_to_string(&opts.corpus).unwrap();
+    let corpus: Vec<_> = corpus.lines().filter(|x| !x.trim().is_empty()).collect();
+
+    let mut report = bench::run(
+        &tokenizer,
+        &rules,
+        &corpus,
+        &BenchConfig {
+            warmup_iterations: opts.warmup,
+            iterations: opts.iterations,
+        },
+    );
+    report.environment.tokenizer_hash = Some(sha256_hex(&tokenizer_data));
+    report.environment.rules_hash = Some(sha256_hex(&rules_data));
+
+    if opts.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&Versioned::new(&report)).unwrap()
+        );
+    } else {
+        print!("{}", report);
+    }
+}
@@ -0,0 +1,40 @@
+use clap::Clap;
+use nlprule::{rules::Rules, tokenizer::Tokenizer, types::ModelInfo};
+
+#[derive(Clap)]
+#[clap(
+    version = "1.0",
+    author = "Benjamin Minixhofer <bminixhofer@gmail.com>"
+)]
+struct Opts {
+    #[clap(long, short)]
+    tokenizer: Option<String>,
+    #[clap(long, short)]
+    rules: Option<String>,
+}
+
+fn print_info(name: &str, info: &ModelInfo) {
+    println!("{}:", name);
+    println!("  language: {}", info.language);
+    println!("  built with: nlprule {}", info.crate_version);
+    println!("  build date: {}", info.build_date);
+    println!(
+        "  LanguageTool version: {}",
+        info.lt_version.as_deref().unwrap_or("unknown")
+    );
+    println!("  checksum: {}", info.checksum);
+}
+
+fn main() {
+    env_logger::init();
+    let opts = Opts::parse();
+
+    println!("nlprule {}", env!("CARGO_PKG_VERSION"));
+
+    if let Some(path) = opts.tokenizer {
+        print_info("tokenizer", Tokenizer::new(path).unwrap().info());
+    }
+    if let Some(path) = opts.rules {
+        print_info("rules", Rules::new(path).unwrap().info());
+    }
+}
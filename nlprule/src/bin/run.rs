@@ -1,5 +1,9 @@
 use clap::Clap;
-use nlprule::{rules::Rules, tokenizer::Tokenizer};
+use nlprule::{
+    rules::{ApplyOptions, Rules},
+    tokenizer::Tokenizer,
+    types::InlineEdit,
+};
 
 #[derive(Clap)]
 #[clap(
@@ -12,17 +16,53 @@ struct Opts {
     tokenizer: String,
     #[clap(long, short)]
     rules: String,
+    /// Rejoin words which are hyphenated at a line break e. g. in text extracted from PDFs.
+    #[clap(long)]
+    rejoin_hyphenation: bool,
+    /// Only check the sentences which fit into this many chars, the rest of the text is not checked.
+    #[clap(long)]
+    max_chars: Option<usize>,
 }
 
 fn main() {
     env_logger::init();
     let opts = Opts::parse();
 
-    let tokenizer = Tokenizer::new(opts.tokenizer).unwrap();
+    let mut tokenizer = Tokenizer::new(opts.tokenizer).unwrap();
+    tokenizer.options_mut().rejoin_hyphenation = opts.rejoin_hyphenation;
     let rules = Rules::new(opts.rules).unwrap();
 
     let tokens = tokenizer.pipe(&opts.text);
 
+    let options = ApplyOptions {
+        max_chars: opts.max_chars,
+        report: true,
+        ..ApplyOptions::default()
+    };
+    let output = rules.suggest_with_options(&opts.text, &tokenizer, &options);
+    let suggestions = output.active;
+
     println!("Tokens: {:#?}", tokens);
-    println!("Suggestions: {:#?}", rules.suggest(&opts.text, &tokenizer));
+    println!("Suggestions: {:#?}", suggestions);
+
+    if let Some(truncated_at) = output.report.and_then(|report| report.truncated_at) {
+        println!(
+            "Stopped checking at char {} (sentence {}) due to --max-chars.",
+            truncated_at.char_offset, truncated_at.sentence_index
+        );
+    }
+
+    for suggestion in suggestions.iter().filter(|x| !x.replacements.is_empty()) {
+        let diff: String = suggestion
+            .inline_diff(&opts.text, 0)
+            .iter()
+            .map(|edit| match edit {
+                InlineEdit::Equal(text) => text.clone(),
+                InlineEdit::Insert(text) => format!("{{+{}+}}", text),
+                InlineEdit::Delete(text) => format!("[-{}-]", text),
+            })
+            .collect();
+
+        println!("{}: {}", suggestion.source, diff);
+    }
 }
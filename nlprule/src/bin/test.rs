@@ -1,5 +1,10 @@
 use clap::Clap;
-use nlprule::{rules::Rules, tokenizer::Tokenizer};
+use nlprule::{
+    rules::Rules,
+    schema::{TestReport, Versioned},
+    tokenizer::Tokenizer,
+};
+use std::{fs::File, io::BufWriter, path::PathBuf};
 
 #[derive(Clap)]
 #[clap(
@@ -13,6 +18,9 @@ struct Opts {
     rules: String,
     #[clap(long, short)]
     ids: Vec<String>,
+    /// Path to write a JSON report of the rules failing their examples to. Published next to the binaries.
+    #[clap(long, parse(from_os_str))]
+    report: Option<PathBuf>,
 }
 
 fn main() {
@@ -26,13 +34,29 @@ fn main() {
     println!("Runnable rules: {}", rules.len());
 
     let mut passes = 0;
+    let mut failures = Vec::new();
     for rule in rules {
         if opts.ids.is_empty() || opts.ids.contains(&rule.id().to_string()) {
-            passes += rule.test(&tokenizer) as usize;
+            if rule.test(&tokenizer) {
+                passes += 1;
+            } else {
+                failures.push(rule.id().to_string());
+            }
         }
     }
 
     println!("Rules passing tests: {}", passes);
+    if let Some(path) = opts.report {
+        let report = Versioned::new(TestReport {
+            language: rules_container.info().language.clone(),
+            runnable: rules.len(),
+            passing: passes,
+            failing: failures,
+        });
+
+        serde_json::to_writer_pretty(BufWriter::new(File::create(path).unwrap()), &report).unwrap();
+    }
+
     if passes == rules.len() {
         std::process::exit(0);
     } else {
@@ -14,18 +14,26 @@ use crate::{
     rule::{
         disambiguation::POSFilter,
         engine::{
-            composition::{GraphId, Matcher, PosMatcher, TextMatcher},
-            Engine,
+            composition::{
+                concrete::{QuoteAtom, RepeatAtom, TextAtom},
+                AndAtom, Atom, Composition, GraphId, Matcher, NotAtom, Part, PosMatcher,
+                Quantifier, TextMatcher,
+            },
+            Engine, TokenEngine,
         },
-        id::Category,
+        grammar::{Conversion, Match, Synthesizer, SynthesizerPart},
+        id::{Category, Index},
         DisambiguationRule, MatchGraph, Rule,
     },
-    rules::{Rules, RulesLangOptions, RulesOptions},
+    rules::{
+        Rules, RulesLangOptions, RulesOptions, Toggles, TypographicQuotesLangOptions,
+        WordRepeatLangOptions,
+    },
     tokenizer::{
         chunk,
         multiword::{MultiwordTagger, MultiwordTaggerFields},
-        tag::{Tagger, TaggerLangOptions},
-        Tokenizer, TokenizerLangOptions,
+        tag::{Tagger, TaggerLangOptions, TokenClasses},
+        TokenExceptions, Tokenizer, TokenizerLangOptions, TokenizerOptions,
     },
     types::*,
     utils::{parallelism::MaybeParallelIterator, regex::Regex},
@@ -166,7 +174,9 @@ impl Tagger {
             groups,
             word_store,
             tag_store,
+            token_classes: TokenClasses::new(&lang_options.token_classes),
             lang_options,
+            overrides: DefaultHashSet::new(),
         })
     }
 }
@@ -267,8 +277,12 @@ impl Rules {
     ) -> Self {
         let rules = super::parse_structure::read_rules(path);
         let mut errors: HashMap<String, usize> = HashMap::new();
+        let is_selected = |id: &Index| {
+            (options.ids.is_empty() || options.ids.iter().any(|x| x.is_match(id)))
+                && !options.ignore_ids.iter().any(|x| x.is_match(id))
+        };
 
-        let rules: Vec<_> = rules
+        let mut rules: Vec<_> = rules
             .into_iter()
             .filter_map(|x| match x {
                 Ok((rule_structure, group, category)) => {
@@ -315,14 +329,13 @@ impl Rules {
 
                     match Rule::from_rule_structure(rule_structure, build_info) {
                         Ok(mut rule) => {
-                            if (options.ids.is_empty()
-                                || options.ids.iter().any(|x| x.is_match(&id)))
-                                && !options.ignore_ids.iter().any(|x| x.is_match(&id))
-                            {
+                            if is_selected(&id) {
                                 rule.id = id;
                                 rule.name = name;
                                 rule.category_name = category.name;
                                 rule.category_type = category.kind;
+                                rule.severity =
+                                    Severity::from_category_type(rule.category_type.as_deref());
                                 rule.enabled = category_on && group_on && rule_on;
                                 Some(rule)
                             } else {
@@ -342,6 +355,51 @@ impl Rules {
             })
             .collect();
 
+        // rules which are implemented in Java in LanguageTool or need state the XML rules can not express
+        let category = Category::new("MISC");
+        let mut builtin = Vec::new();
+
+        if let Some(word_repeat) = &options.word_repeat {
+            let id = category.join(word_repeat.id.as_str()).join(0);
+
+            if is_selected(&id) {
+                builtin.push((id, Rule::word_repeat(word_repeat, build_info)));
+            }
+        }
+
+        if let Some(quotes) = &options.typographic_quotes {
+            for level in 0..quotes.quotes.len() {
+                for &opening in &[true, false] {
+                    let index = 2 * level + if opening { 0 } else { 1 };
+                    let id = category.join(quotes.id.as_str()).join(index);
+
+                    if is_selected(&id) {
+                        builtin.push((id, Rule::typographic_quote(quotes, level, opening)));
+                    }
+                }
+            }
+        }
+
+        for (id, rule) in builtin {
+            match rule {
+                Ok(mut rule) => {
+                    // reuse the (localized) category name from the XML rules
+                    if let Some(other) = rules.iter().find(|x| x.id.parent().parent() == &category)
+                    {
+                        rule.category_name = other.category_name.clone();
+                        rule.category_type = other.category_type.clone();
+                        rule.severity = other.severity;
+                    }
+
+                    rule.id = id;
+                    rules.push(rule);
+                }
+                Err(x) => {
+                    *errors.entry(format!("[Rule] {}", x)).or_insert(0) += 1;
+                }
+            }
+        }
+
         if !errors.is_empty() {
             let mut errors: Vec<(String, usize)> = errors.into_iter().collect();
             errors.sort_by_key(|x| -(x.1 as i32));
@@ -357,11 +415,175 @@ impl Rules {
 
         Rules {
             rules,
-            options: RulesOptions::default(),
+            options: RulesOptions {
+                punctuation_inside_quotes: options.punctuation_inside_quotes,
+                ..RulesOptions::default()
+            },
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
         }
     }
 }
 
+impl Rule {
+    /// Creates the word repetition rule. Matches a word followed by the same word (ignoring case),
+    /// optionally with punctuation from [WordRepeatLangOptions::skip] in between.
+    pub(in crate::compile) fn word_repeat(
+        options: &WordRepeatLangOptions,
+        info: &mut BuildInfo,
+    ) -> Result<Self, Error> {
+        let text_atom = |regex: Regex, info: &mut BuildInfo| -> Atom {
+            TextAtom {
+                matcher: TextMatcher::new(Matcher::new_regex(regex, false, true), info),
+            }
+            .into()
+        };
+        let any_of = |words: &[String]| {
+            words
+                .iter()
+                .map(|x| regex_syntax::escape(x))
+                .collect::<Vec<_>>()
+                .join("|")
+        };
+        let part = |atom: Atom, quantifier: Quantifier| Part {
+            atom,
+            quantifier,
+            greedy: true,
+            visible: true,
+            unify: None,
+        };
+
+        let mut word_atoms = vec![text_atom(
+            Regex::from_java_regex(r"\p{L}+", true, true)?,
+            info,
+        )];
+        if !options.exceptions.is_empty() {
+            word_atoms.push(NotAtom::not(text_atom(
+                Regex::from_java_regex(&any_of(&options.exceptions), true, false)?,
+                info,
+            )));
+        }
+
+        let mut parts = vec![part(AndAtom::and(word_atoms), Quantifier::new(1, 1))];
+        if !options.skip.is_empty() {
+            parts.push(part(
+                text_atom(
+                    Regex::from_java_regex(&any_of(&options.skip), true, true)?,
+                    info,
+                ),
+                Quantifier::new(0, 2),
+            ));
+        }
+        parts.push(part(
+            RepeatAtom {
+                skip: options.skip.clone(),
+                case_sensitive: false,
+            }
+            .into(),
+            Quantifier::new(1, 1),
+        ));
+
+        let end = GraphId(parts.len());
+        let engine = Engine::Token(TokenEngine {
+            composition: Composition::new(parts)?,
+            antipatterns: Vec::new(),
+        });
+
+        Ok(Rule {
+            id: Index::default(),
+            triggers: engine.triggers(),
+            engine,
+            examples: Vec::new(),
+            suggesters: vec![Synthesizer {
+                use_titlecase_adjust: true,
+                suppress_misspelled: false,
+                parts: vec![SynthesizerPart::Match(Box::new(Match {
+                    id: GraphId(1),
+                    conversion: Conversion::Nop,
+                    pos_replacer: None,
+                    regex_replacer: None,
+                }))],
+            }],
+            message: Synthesizer {
+                use_titlecase_adjust: true,
+                suppress_misspelled: false,
+                parts: vec![SynthesizerPart::Text(options.message.clone())],
+            },
+            start: GraphId(1),
+            end,
+            url: None,
+            short: None,
+            name: options.name.clone(),
+            category_name: "Miscellaneous".into(),
+            category_type: None,
+            severity: Severity::default(),
+            unification: None,
+            filter: None,
+            enabled: true,
+            message_overridden: false,
+            severity_overridden: false,
+        })
+    }
+
+    /// Creates one rule of the typographic quotes family. Matches a straight quote which opens (if `opening`)
+    /// or closes a pair of quotes at the given nesting `level` and replaces it with the typographic quote
+    /// from [TypographicQuotesLangOptions::quotes]. These rules are disabled by default.
+    pub(in crate::compile) fn typographic_quote(
+        options: &TypographicQuotesLangOptions,
+        level: usize,
+        opening: bool,
+    ) -> Result<Self, Error> {
+        let (open, close) = &options.quotes[level];
+        let replacement = if opening { open } else { close };
+
+        let engine = Engine::Token(TokenEngine {
+            composition: Composition::new(vec![Part {
+                atom: QuoteAtom {
+                    opening,
+                    level,
+                    n_levels: options.quotes.len(),
+                }
+                .into(),
+                quantifier: Quantifier::new(1, 1),
+                greedy: true,
+                visible: true,
+                unify: None,
+            }])?,
+            antipatterns: Vec::new(),
+        });
+
+        Ok(Rule {
+            id: Index::default(),
+            triggers: engine.triggers(),
+            engine,
+            examples: Vec::new(),
+            suggesters: vec![Synthesizer {
+                use_titlecase_adjust: false,
+                suppress_misspelled: false,
+                parts: vec![SynthesizerPart::Text(replacement.clone())],
+            }],
+            message: Synthesizer {
+                use_titlecase_adjust: false,
+                suppress_misspelled: false,
+                parts: vec![SynthesizerPart::Text(options.message.clone())],
+            },
+            start: GraphId(1),
+            end: GraphId(1),
+            url: None,
+            short: None,
+            name: options.name.clone(),
+            category_name: "Miscellaneous".into(),
+            category_type: None,
+            severity: Severity::default(),
+            unification: None,
+            filter: None,
+            enabled: false,
+            message_overridden: false,
+            severity_overridden: false,
+        })
+    }
+}
+
 impl Tokenizer {
     pub(in crate::compile) fn from_xml<P: AsRef<Path>>(
         path: P,
@@ -441,6 +663,9 @@ impl Tokenizer {
             multiword_tagger,
             rules,
             lang_options,
+            token_exceptions: TokenExceptions::default(),
+            options: TokenizerOptions::default(),
+            info: ModelInfo::default(),
         })
     }
 }
@@ -535,10 +760,7 @@ impl Engine {
     pub(in crate::compile) fn to_graph_id(&self, id: usize) -> Result<GraphId, Error> {
         let mut id = GraphId(id);
 
-        let map = match &self {
-            Engine::Token(engine) => &engine.composition.id_to_idx,
-            Engine::Text(_, id_to_idx) => &id_to_idx,
-        };
+        let map = self.id_to_idx();
 
         let max_id = *map
             .keys()
@@ -571,6 +793,9 @@ mod composition {
                 | Atom::SpaceBeforeAtom(_)
                 | Atom::TextAtom(_)
                 | Atom::WordDataAtom(_)
+                | Atom::RepeatAtom(_)
+                | Atom::QuoteAtom(_)
+                | Atom::ClassAtom(_)
                 | Atom::FalseAtom(_)
                 | Atom::TrueAtom(_) => Box::new(std::iter::once(self)),
                 Atom::AndAtom(x) => Box::new(x.atoms.iter_mut()),
@@ -758,7 +983,11 @@ pub(in crate::compile) mod filters {
     use super::Error;
     use std::collections::HashMap;
 
-    use crate::{filter::*, rule::engine::Engine, utils::regex::Regex};
+    use crate::{
+        filter::*,
+        rule::engine::{composition::GraphId, Engine},
+        utils::regex::Regex,
+    };
 
     trait FromArgs: Sized {
         fn from_args(args: HashMap<String, String>, engine: &Engine) -> Result<Self, Error>;
@@ -805,16 +1034,85 @@ pub(in crate::compile) mod filters {
         }
     }
 
+    /// Gets the argument referring to a token of the pattern e. g. `\3` for `day:\3`.
+    fn graph_id_arg(
+        args: &HashMap<String, String>,
+        key: &str,
+        engine: &Engine,
+    ) -> Result<Option<GraphId>, Error> {
+        args.get(key)
+            .map(|x| engine.to_graph_id(x.trim_start_matches('\\').parse::<usize>()?))
+            .transpose()
+    }
+
+    fn required_graph_id_arg(
+        args: &HashMap<String, String>,
+        key: &str,
+        engine: &Engine,
+    ) -> Result<GraphId, Error> {
+        graph_id_arg(args, key, engine)?
+            .ok_or_else(|| Error::Unexpected(format!("filter must have `{}` argument", key)))
+    }
+
+    impl InvalidDateFilter {
+        fn from_args(
+            args: HashMap<String, String>,
+            engine: &Engine,
+            locale: DateLocale,
+        ) -> Result<Self, Error> {
+            Ok(InvalidDateFilter {
+                day: required_graph_id_arg(&args, "day", engine)?,
+                month: required_graph_id_arg(&args, "month", engine)?,
+                year: graph_id_arg(&args, "year", engine)?,
+                locale,
+            })
+        }
+    }
+
+    impl DateCheckFilter {
+        fn from_args(
+            args: HashMap<String, String>,
+            engine: &Engine,
+            locale: DateLocale,
+        ) -> Result<Self, Error> {
+            Ok(DateCheckFilter {
+                weekday: required_graph_id_arg(&args, "weekDay", engine)?,
+                day: required_graph_id_arg(&args, "day", engine)?,
+                month: required_graph_id_arg(&args, "month", engine)?,
+                year: required_graph_id_arg(&args, "year", engine)?,
+                locale,
+            })
+        }
+    }
+
+    /// Gets the filter for a (fully qualified) LanguageTool filter class
+    /// e. g. `org.languagetool.rules.en.DateCheckFilter`.
     pub(in crate::compile) fn get_filter(
-        name: &str,
+        class: &str,
         args: HashMap<String, String>,
         engine: &Engine,
     ) -> Result<Filter, Error> {
+        let mut path = class.rsplit('.');
+        let name = path.next().unwrap_or_default();
+        // date filters are language-specific, the language is the package e. g. `de`
+        let lang_code = path.next();
+        let date_locale = || {
+            lang_code
+                .and_then(DateLocale::from_lang_code)
+                .ok_or_else(|| Error::Unimplemented(format!("date filter {}", class)))
+        };
+
         match name {
             "NoDisambiguationEnglishPartialPosTagFilter" => {
                 Ok(NoDisambiguationEnglishPartialPosTagFilter::from_args(args, engine)?.into())
             }
-            _ => Err(Error::Unexpected(format!("unsupported filter {}", name))),
+            "InvalidDateFilter" => {
+                Ok(InvalidDateFilter::from_args(args, engine, date_locale()?)?.into())
+            }
+            "DateCheckFilter" => {
+                Ok(DateCheckFilter::from_args(args, engine, date_locale()?)?.into())
+            }
+            _ => Err(Error::Unimplemented(format!("filter {}", name))),
         }
     }
 }
@@ -15,9 +15,10 @@ use std::{
 use crate::{
     rules::Rules,
     tokenizer::{chunk::Chunker, multiword::MultiwordTagger, tag::Tagger, Tokenizer},
-    types::DefaultHasher,
+    types::{DefaultHasher, ModelInfo},
 };
 use log::info;
+use serde::Serialize;
 
 use self::parse_structure::{BuildInfo, RegexCache};
 use thiserror::Error;
@@ -38,6 +39,8 @@ struct BuildFilePaths {
     common_words_path: PathBuf,
     regex_cache_path: PathBuf,
     srx_path: PathBuf,
+    lt_version_path: PathBuf,
+    token_exceptions_path: PathBuf,
 }
 
 impl BuildFilePaths {
@@ -54,6 +57,8 @@ impl BuildFilePaths {
             common_words_path: p.join("common.txt"),
             regex_cache_path: p.join("regex_cache.bin"),
             srx_path: p.join("segment.srx"),
+            lt_version_path: p.join("lt_version.txt"),
+            token_exceptions_path: p.join("tokenize_exceptions.txt"),
         }
     }
 }
@@ -71,6 +76,8 @@ pub enum Error {
     SRX(#[from] srx::Error),
     #[error("language options do not exist for '{lang_code}'")]
     LanguageOptionsDoNotExist { lang_code: String },
+    #[error("build file {0} is required for this language but does not exist")]
+    MissingBuildFile(PathBuf),
     #[error("regex syntax error: {0}")]
     RegexSyntax(#[from] regex_syntax::ast::Error),
     #[error("regex compilation error: {0}")]
@@ -81,10 +88,19 @@ pub enum Error {
     Unimplemented(String),
     #[error("error parsing to integer: {0}")]
     ParseError(#[from] ParseIntError),
+    #[error("error writing binary: {0}")]
+    Write(#[from] crate::Error),
     #[error("unknown error")]
     Other(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
 }
 
+/// Hashes the serialized representation of a value to identify a binary.
+fn hash_serialized<T: Serialize>(value: &T) -> String {
+    let mut hasher = DefaultHasher::default();
+    hasher.write(&bincode::serialize(value).expect("serialization to memory can not fail"));
+    format!("{:016x}", hasher.finish())
+}
+
 /// Compiles the binaries from a build directory.
 pub fn compile(
     build_dir: impl AsRef<Path>,
@@ -94,6 +110,17 @@ pub fn compile(
     let paths = BuildFilePaths::new(&build_dir);
 
     let lang_code = fs::read_to_string(paths.lang_code_path)?;
+    // older build directories do not contain the LanguageTool version
+    let lt_version = fs::read_to_string(&paths.lt_version_path)
+        .ok()
+        .map(|x| x.trim().to_owned());
+    let info = ModelInfo {
+        language: lang_code.clone(),
+        crate_version: env!("CARGO_PKG_VERSION").to_owned(),
+        build_date: utils::current_date(),
+        lt_version,
+        checksum: String::new(),
+    };
 
     info!(
         "Reading common words from {}.",
@@ -104,6 +131,12 @@ pub fn compile(
         .map(|x| x.to_string())
         .collect();
 
+    let descriptor =
+        utils::language_descriptor(&lang_code).ok_or_else(|| Error::LanguageOptionsDoNotExist {
+            lang_code: lang_code.clone(),
+        })?;
+    info!("Building binaries for {}.", descriptor.name);
+
     let tokenizer_lang_options = utils::tokenizer_lang_options(&lang_code).ok_or_else(|| {
         Error::LanguageOptionsDoNotExist {
             lang_code: lang_code.clone(),
@@ -155,6 +188,10 @@ pub fn compile(
     };
 
     let mut build_info = BuildInfo::new(Arc::new(tagger), regex_cache);
+    if descriptor.chunker && !paths.chunker_path.exists() {
+        return Err(Error::MissingBuildFile(paths.chunker_path));
+    }
+
     let chunker = if paths.chunker_path.exists() {
         info!("{} exists. Building chunker.", paths.chunker_path.display());
         let reader = BufReader::new(File::open(paths.chunker_path)?);
@@ -177,20 +214,59 @@ pub fn compile(
     };
 
     info!("Creating tokenizer.");
-    let tokenizer = Tokenizer::from_xml(
+    let mut tokenizer = Tokenizer::from_xml(
         &paths.disambiguation_path,
         &mut build_info,
         chunker,
         multiword_tagger,
-        srx::SRX::from_str(&fs::read_to_string(&paths.srx_path)?)?.language_rules(lang_code),
+        srx::SRX::from_str(&fs::read_to_string(&paths.srx_path)?)?
+            .language_rules(descriptor.srx_code.unwrap_or(lang_code)),
         tokenizer_lang_options,
     )?;
 
-    bincode::serialize_into(&mut tokenizer_dest, &tokenizer)?;
+    let mut token_exceptions = tokenizer.lang_options.token_exceptions.clone();
+    // not every language has an exceptions file
+    if let Ok(content) = fs::read_to_string(&paths.token_exceptions_path) {
+        info!(
+            "Reading token exceptions from {}.",
+            paths.token_exceptions_path.display()
+        );
+        token_exceptions.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|x| !x.is_empty() && !x.starts_with('#'))
+                .map(ToOwned::to_owned),
+        );
+    }
+    tokenizer.add_token_exceptions(&token_exceptions);
+
+    tokenizer.info = ModelInfo {
+        checksum: hash_serialized(&tokenizer),
+        ..info.clone()
+    };
+    tokenizer.to_writer(&mut tokenizer_dest)?;
 
     info!("Creating grammar rules.");
-    let rules = Rules::from_xml(&paths.grammar_path, &mut build_info, rules_lang_options);
-    bincode::serialize_into(&mut rules_dest, &rules)?;
+    let mut rules = Rules::from_xml(&paths.grammar_path, &mut build_info, rules_lang_options);
+
+    let n_suppress_misspelled = rules
+        .rules()
+        .iter()
+        .filter(|rule| rule.suggesters.iter().any(|x| x.suppress_misspelled))
+        .count();
+    if n_suppress_misspelled > 0 {
+        // LanguageTool uses its speller here, we only know the words in the tag dictionary
+        info!(
+            "{} rules suppress misspelled suggestions. Spelling is approximated with the tagger dictionary and the known words of the tokenizer, words only known to the LanguageTool speller count as misspelled.",
+            n_suppress_misspelled
+        );
+    }
+    rules.info = ModelInfo {
+        checksum: hash_serialized(&rules),
+        ..info
+    };
+    rules.to_writer(&mut rules_dest)?;
 
     // we need to write the regex cache after building the rules, otherwise it isn't fully populated
     let f = BufWriter::new(File::create(&paths.regex_cache_path)?);
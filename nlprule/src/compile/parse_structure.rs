@@ -1,13 +1,17 @@
 use std::sync::Arc;
 
 use super::{structure, Error};
-use crate::{tokenizer::tag::Tagger, types::*};
+use crate::{
+    tokenizer::tag::{Tagger, TokenClasses},
+    types::*,
+};
 use crate::{utils, utils::regex::Regex};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 
 pub use structure::{read_disambiguation_rules, read_rules};
 
+use crate::filter::Filter;
 use crate::rule::disambiguation::*;
 use crate::rule::engine::composition::concrete::*;
 use crate::rule::engine::composition::*;
@@ -121,7 +125,17 @@ fn parse_match_attribs(
     let mut inflect_matcher = None;
     let mut pos_matcher = None;
 
-    if text.is_some() || text_match_idx.is_some() {
+    let alternation_class = match text {
+        Some(text) if is_regex && !inflected && !case_sensitive => {
+            class_of_alternation(text.trim(), info.tagger().token_classes())
+        }
+        _ => None,
+    };
+
+    if let Some(class) = alternation_class {
+        // the regex is a case-insensitive alternation of exactly the words in a class, a class lookup is equivalent but faster
+        atoms.push((ClassAtom { class, negate }).into());
+    } else if text.is_some() || text_match_idx.is_some() {
         let matcher = if is_regex {
             if let Some(text) = text {
                 let regex = Regex::from_java_regex(text.trim(), true, case_sensitive);
@@ -189,29 +203,6 @@ fn parse_match_attribs(
         );
     }
 
-    match (attribs.chunk(), attribs.chunk_re()) {
-        (Some(chunk), None) => {
-            let chunk_atom = ChunkAtom {
-                matcher: Matcher::new_string(
-                    either::Left(chunk.trim().to_string()),
-                    false,
-                    true,
-                    true,
-                ),
-            };
-            atoms.push(chunk_atom.into());
-        }
-        (None, Some(chunk_re)) => {
-            let regex = Regex::from_java_regex(chunk_re.trim(), true, true)?;
-            let chunk_atom = ChunkAtom {
-                matcher: Matcher::new_regex(regex, false, true),
-            };
-            atoms.push(chunk_atom.into());
-        }
-        (None, None) => {}
-        _ => panic!("unexpected combination of chunk / chunk_re values."),
-    }
-
     if let Some(chunk) = attribs.chunk() {
         let chunk_atom = ChunkAtom {
             matcher: Matcher::new_string(either::Left(chunk.trim().to_string()), false, true, true),
@@ -220,6 +211,31 @@ fn parse_match_attribs(
         atoms.push(chunk_atom.into());
     }
 
+    if let Some(chunk_re) = attribs.chunk_re() {
+        let regex = Regex::from_java_regex(chunk_re.trim(), true, true)?;
+        let chunk_atom = ChunkAtom {
+            matcher: Matcher::new_regex(regex, false, true),
+        };
+
+        atoms.push(chunk_atom.into());
+    }
+
+    if let Some(class) = attribs.class() {
+        let class = info
+            .tagger()
+            .token_classes()
+            .id(class.trim())
+            .ok_or_else(|| Error::Unexpected(format!("unknown token class {:?}", class)))?;
+
+        atoms.push(
+            (ClassAtom {
+                class,
+                negate: false,
+            })
+            .into(),
+        );
+    }
+
     if let Some(space_before) = attribs.spacebefore() {
         let value = match space_before.as_str() {
             "yes" => true,
@@ -233,6 +249,42 @@ fn parse_match_attribs(
     Ok(AndAtom::and(atoms))
 }
 
+/// Gets the words of a regex which is only an alternation of literal words e. g. `(?:in|on|at)`.
+/// Only accepts words whose case-insensitive match is equivalent to comparing the lowercase forms.
+fn literal_alternatives(regex: &str) -> Option<Vec<String>> {
+    let inner = regex
+        .strip_prefix("(?:")
+        .or_else(|| regex.strip_prefix('('))
+        .and_then(|x| x.strip_suffix(')'))
+        .unwrap_or(regex);
+
+    let is_literal = |c: char| {
+        c == '\''
+            || (c.is_alphabetic() && c.to_lowercase().count() == 1 && c.to_uppercase().count() == 1)
+    };
+
+    inner
+        .split('|')
+        .map(|word| {
+            if !word.is_empty() && word.chars().all(is_literal) {
+                Some(word.to_lowercase())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Gets the token class whose words are exactly the words of the regex, if the regex is an alternation of literal words.
+fn class_of_alternation(regex: &str, classes: &TokenClasses) -> Option<usize> {
+    let words: DefaultHashSet<_> = literal_alternatives(regex)?.into_iter().collect();
+
+    (0..classes.names().len()).find(|id| {
+        let class_words: DefaultHashSet<_> = classes.words(*id).collect();
+        class_words.len() == words.len() && words.iter().all(|x| class_words.contains(x.as_str()))
+    })
+}
+
 fn get_exceptions(
     token: &structure::Token,
     case_sensitive: bool,
@@ -514,6 +566,7 @@ fn parse_suggestion(
         parts,
         // use titlecase adjustment (i. e. make replacement title case if match is title case) if token rule
         use_titlecase_adjust: matches!(engine, Engine::Token(_)),
+        suppress_misspelled: data.suppress_misspelled.as_deref() == Some("yes"),
     })
 }
 
@@ -689,12 +742,6 @@ impl Rule {
         data: structure::Rule,
         info: &mut BuildInfo,
     ) -> Result<Rule, Error> {
-        if data.filter.is_some() {
-            return Err(Error::Unimplemented(
-                "rules with filter are not implemented.".into(),
-            ));
-        }
-
         let (engine, start, end) = match (&data.pattern, data.regex) {
             (Some(_), Some(_)) => Err(Error::Unexpected(
                 "must not contain both `pattern` and `regexp`.".into(),
@@ -772,7 +819,12 @@ impl Rule {
 
         for part in data.message.parts {
             match part {
-                structure::MessagePart::Suggestion(suggestion) => {
+                structure::MessagePart::Suggestion(mut suggestion) => {
+                    // `suppress_misspelled` of the message applies to all suggestions in it
+                    if suggestion.suppress_misspelled.is_none() {
+                        suggestion.suppress_misspelled = data.message.suppress_misspelled.clone();
+                    }
+
                     let suggester = parse_suggestion(suggestion.clone(), &engine, info)?;
                     // simpler to just parse a second time than cloning the result
                     message_parts.extend(parse_suggestion(suggestion, &engine, info)?.parts);
@@ -828,6 +880,7 @@ impl Rule {
                             ));
                         }
 
+                        let byte_start: usize = texts.iter().map(|x| x.len()).sum();
                         texts.push(marker.text.as_str());
                         let length = marker.text.chars().count();
 
@@ -850,9 +903,20 @@ impl Rule {
                             suggestion = Some(Suggestion {
                                 source: "_Test".to_string(),
                                 message: "_Test".to_string(),
-                                start: char_length,
-                                end: char_length + length,
+                                short_message: String::new(),
+                                url: None,
+                                severity: Severity::default(),
+                                span: Span::new(
+                                    char_length..char_length + length,
+                                    byte_start..byte_start + marker.text.len(),
+                                ),
+                                replacement_distance: replacements
+                                    .iter()
+                                    .map(|x| utils::levenshtein(&marker.text, x))
+                                    .min()
+                                    .unwrap_or(0),
                                 replacements,
+                                segment_type: SegmentType::default(),
                             });
                         }
 
@@ -880,16 +944,24 @@ impl Rule {
             None
         };
 
+        let filter = data
+            .filter
+            .map(|filter| parse_filter(filter, &engine))
+            .transpose()?;
+
         Ok(Rule {
             start: engine.to_graph_id(start)?,
             end: engine.to_graph_id(end)?,
+            triggers: engine.triggers(),
             engine,
             unification,
+            filter,
             examples,
             suggesters,
             message: Synthesizer {
                 parts: message_parts,
                 use_titlecase_adjust: true,
+                suppress_misspelled: false,
             },
             url: data.url.map(|x| x.to_string()),
             short: data.short.map(|x| x.to_string()),
@@ -898,7 +970,10 @@ impl Rule {
             name: String::new(),
             category_name: String::new(),
             category_type: None,
+            severity: Severity::default(),
             enabled: true,
+            message_overridden: false,
+            severity_overridden: false,
         })
     }
 }
@@ -953,6 +1028,25 @@ impl owned::WordData {
     }
 }
 
+fn parse_filter(filter: structure::Filter, engine: &Engine) -> Result<Filter, Error> {
+    let args = filter
+        .args
+        .split_whitespace()
+        .map(|x| {
+            let idx = x
+                .find(':')
+                .ok_or_else(|| Error::Unexpected(format!("invalid filter argument {}", x)))?;
+
+            Ok((
+                x[..idx].to_string(),
+                x[(idx + ':'.len_utf8())..].to_string(),
+            ))
+        })
+        .collect::<Result<_, Error>>()?;
+
+    super::impls::filters::get_filter(&filter.class, args, engine)
+}
+
 fn parse_pos_filter(postag: &str, postag_regexp: Option<&str>, info: &mut BuildInfo) -> POSFilter {
     match postag_regexp.as_deref() {
         Some("yes") => POSFilter::new(PosMatcher::new(
@@ -1194,27 +1288,10 @@ impl DisambiguationRule {
             Some(x) => Err(Error::Unimplemented(format!("action {}", x))),
         }?;
 
-        let filter = if let Some(filter_data) = data.filter {
-            let args = filter_data
-                .args
-                .split(' ')
-                .map(|x| {
-                    let idx = x.find(':').unwrap();
-                    (
-                        x[..idx].to_string(),
-                        x[(idx + ':'.len_utf8())..].to_string(),
-                    )
-                })
-                .collect();
-
-            Some(super::impls::filters::get_filter(
-                filter_data.class.split('.').next_back().unwrap(),
-                args,
-                &engine,
-            )?)
-        } else {
-            None
-        };
+        let filter = data
+            .filter
+            .map(|filter| parse_filter(filter, &engine))
+            .transpose()?;
 
         let mut examples = Vec::new();
 
@@ -1295,3 +1372,41 @@ impl DisambiguationRule {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn alternations_of_class_words_are_recognized() {
+        assert_eq!(
+            literal_alternatives("(?:In|on|at)"),
+            Some(vec!["in".to_owned(), "on".to_owned(), "at".to_owned()])
+        );
+        assert_eq!(
+            literal_alternatives("don't|can"),
+            Some(vec!["don't".to_owned(), "can".to_owned()])
+        );
+        assert_eq!(literal_alternatives("in|on.*"), None);
+        assert_eq!(literal_alternatives("in||on"), None);
+        assert_eq!(literal_alternatives("(in|on)|(at)"), None);
+
+        let mut classes = BTreeMap::new();
+        classes.insert(
+            "preposition".to_owned(),
+            vec!["in".to_owned(), "on".to_owned()],
+        );
+        classes.insert(
+            "stopword".to_owned(),
+            vec!["the".to_owned(), "a".to_owned()],
+        );
+        let classes = TokenClasses::new(&classes);
+
+        assert_eq!(class_of_alternation("(?:on|in)", &classes), Some(0));
+        assert_eq!(class_of_alternation("A|the|THE", &classes), Some(1));
+        // a subset or superset of a class is not equivalent
+        assert_eq!(class_of_alternation("in", &classes), None);
+        assert_eq!(class_of_alternation("in|on|at", &classes), None);
+    }
+}
@@ -317,6 +317,7 @@ pub struct Exception {
     pub spacebefore: Option<String>,
     pub negate: Option<String>,
     pub negate_pos: Option<String>,
+    pub class: Option<String>,
     pub scope: Option<String>,
     pub text: Option<XMLString>,
 }
@@ -355,6 +356,7 @@ pub struct Token {
     pub spacebefore: Option<String>,
     pub negate: Option<String>,
     pub negate_pos: Option<String>,
+    pub class: Option<String>,
     #[serde(rename = "$value")]
     pub parts: Option<Vec<TokenPart>>,
 }
@@ -371,6 +373,7 @@ pub trait MatchAttributes {
     fn spacebefore(&self) -> &Option<String>;
     fn negate(&self) -> &Option<String>;
     fn negate_pos(&self) -> &Option<String>;
+    fn class(&self) -> &Option<String>;
 }
 
 macro_rules! impl_match_attributes {
@@ -415,6 +418,10 @@ macro_rules! impl_match_attributes {
             fn negate_pos(&self) -> &Option<String> {
                 &self.negate_pos
             }
+
+            fn class(&self) -> &Option<String> {
+                &self.class
+            }
         }
     };
 }
@@ -702,10 +709,20 @@ pub fn read_rules<P: AsRef<std::path::Path>>(
                     RuleContainer::Rule(rule) => {
                         vec![Ok((rule, None, category))]
                     }
-                    RuleContainer::RuleGroup(rule_group) => flatten_group!(rule_group, category)
-                        .into_iter()
-                        .map(Ok)
-                        .collect(),
+                    RuleContainer::RuleGroup(rule_group) => {
+                        // the short message and url of a group apply to all rules without their own
+                        let short = rule_group.short.clone();
+                        let url = rule_group.url.clone();
+
+                        flatten_group!(rule_group, category)
+                            .into_iter()
+                            .map(|(mut rule, group, category)| {
+                                rule.short = rule.short.or_else(|| short.clone());
+                                rule.url = rule.url.or_else(|| url.clone());
+                                Ok((rule, group, category))
+                            })
+                            .collect()
+                    }
                     RuleContainer::Unification(unification) => {
                         unifications.push(unification);
 
@@ -1,6 +1,8 @@
 use crate::{rules::RulesLangOptions, tokenizer::TokenizerLangOptions};
 use crate::{tokenizer::tag::TaggerLangOptions, types::*};
 use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 lazy_static! {
     static ref TOKENIZER_LANG_OPTIONS: DefaultHashMap<String, TokenizerLangOptions> = {
@@ -35,6 +37,41 @@ lazy_static! {
     };
 }
 
+lazy_static! {
+    static ref LANGUAGE_DESCRIPTORS: DefaultHashMap<String, LanguageDescriptor> = {
+        serde_json::from_slice(include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/",
+            "language_configs.json"
+        )))
+        .expect("language configs must be valid JSON")
+    };
+}
+
+/// Describes how the build files of a language are obtained and which optional components it needs.
+/// Together with the tokenizer, tagger and rules configs this is everything needed to add a language.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct LanguageDescriptor {
+    /// The English name of the language e. g. "Portuguese".
+    pub name: String,
+    /// The name of the tagger dictionary in the LanguageTool resource directory e. g. "english.dict".
+    /// Only used by `make_build_dir.py`.
+    #[allow(dead_code)]
+    pub tag_dict: String,
+    /// The language code used to select the segmentation rules from the SRX file.
+    /// Defaults to the language code of the build directory.
+    #[serde(default)]
+    pub srx_code: Option<String>,
+    /// Whether the language needs a chunker. If set, building fails without a chunker in the build directory.
+    #[serde(default)]
+    pub chunker: bool,
+}
+
+/// Gets the language descriptor for the language code
+pub(crate) fn language_descriptor(lang_code: &str) -> Option<LanguageDescriptor> {
+    LANGUAGE_DESCRIPTORS.get(lang_code).cloned()
+}
+
 /// Gets the tokenizer language options for the language code
 pub(crate) fn tokenizer_lang_options(lang_code: &str) -> Option<TokenizerLangOptions> {
     TOKENIZER_LANG_OPTIONS.get(lang_code).cloned()
@@ -50,6 +87,32 @@ pub(crate) fn tagger_lang_options(lang_code: &str) -> Option<TaggerLangOptions>
     TAGGER_LANG_OPTIONS.get(lang_code).cloned()
 }
 
+/// Converts days since the unix epoch to a (year, month, day) date in the proleptic Gregorian calendar,
+/// see http://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// Gets the current date in `YYYY-MM-DD` format (UTC).
+pub(crate) fn current_date() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs());
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
 pub(crate) use regex::from_java_regex;
 
 mod regex {
@@ -416,3 +479,31 @@ mod regex {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(18_751), (2021, 5, 4));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn languages_have_complete_configs() {
+        for lang_code in LANGUAGE_DESCRIPTORS.keys() {
+            assert!(tokenizer_lang_options(lang_code).is_some());
+            assert!(tagger_lang_options(lang_code).is_some());
+            assert!(rules_lang_options(lang_code).is_some());
+        }
+
+        assert!(language_descriptor("en").unwrap().chunker);
+        assert_eq!(
+            language_descriptor("pt").unwrap().srx_code.as_deref(),
+            Some("pt-PT")
+        );
+    }
+}
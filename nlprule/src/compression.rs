@@ -0,0 +1,131 @@
+//! Transparent decompression of binaries. Binaries can be stored plain, gzip compressed (with the `gzip` feature)
+//! or zstd compressed (with the `zstd` feature). The compression is detected from the magic bytes at the start
+//! of the data, so e. g. [Tokenizer::from_reader][crate::Tokenizer::from_reader] accepts all of them.
+
+use crate::Error;
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{BufRead, Read},
+};
+
+/// The magic bytes gzip compressed data starts with, including the compression method (deflate).
+const GZIP_MAGIC: [u8; 3] = [0x1f, 0x8b, 0x08];
+/// The magic bytes of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// The compression of a binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Not compressed.
+    None,
+    /// Compressed with gzip, usually with a `.gz` file extension.
+    Gzip,
+    /// Compressed with zstd, usually with a `.zst` file extension.
+    Zstd,
+}
+
+impl Compression {
+    /// Detects the compression from the start of the data. Plain binaries never start with the magic bytes
+    /// of gzip or zstd because they start with the (comparatively small) length of the rules.
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else if data.starts_with(&ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Wraps the reader in a decoder for the detected compression of its data.
+///
+/// # Errors
+/// - If the data can not be read.
+/// - If the data is compressed and the feature for the compression is not enabled.
+pub fn decompress_reader<'r, R: BufRead + 'r>(mut reader: R) -> Result<Box<dyn Read + 'r>, Error> {
+    let compression = Compression::detect(reader.fill_buf()?);
+
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(flate2::bufread::GzDecoder::new(reader))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::with_buffer(reader)?)),
+        #[allow(unreachable_patterns)]
+        compression => Err(Error::UnsupportedCompression(compression)),
+    }
+}
+
+/// Decompresses the data with the detected compression. Plain data is returned as is.
+///
+/// # Errors
+/// - If the data is compressed and can not be decompressed.
+/// - If the data is compressed and the feature for the compression is not enabled.
+pub fn decompress(data: &[u8]) -> Result<Cow<'_, [u8]>, Error> {
+    if Compression::detect(data) == Compression::None {
+        return Ok(Cow::Borrowed(data));
+    }
+
+    let mut buffer = Vec::new();
+    decompress_reader(data)?.read_to_end(&mut buffer)?;
+    Ok(Cow::Owned(buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rules;
+    use std::io::Write;
+
+    fn compressed(data: &[u8]) -> Vec<(Compression, Vec<u8>)> {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(data).unwrap();
+
+        vec![
+            (Compression::None, data.to_vec()),
+            (Compression::Gzip, gzip.finish().unwrap()),
+            (Compression::Zstd, zstd::encode_all(data, 0).unwrap()),
+        ]
+    }
+
+    #[test]
+    fn compressed_binaries_load_identically() {
+        let mut rules = Rules::default();
+        rules.options_mut().punctuation_inside_quotes = true;
+        let mut binary = Vec::new();
+        rules.to_writer(&mut binary).unwrap();
+
+        for (compression, data) in compressed(&binary) {
+            assert_eq!(Compression::detect(&data), compression);
+            assert_eq!(decompress(&data).unwrap(), &binary[..]);
+
+            let loaded = Rules::from_reader(&data[..]).unwrap();
+            let mut reserialized = Vec::new();
+            loaded.to_writer(&mut reserialized).unwrap();
+            assert_eq!(reserialized, binary, "{}", compression);
+        }
+    }
+
+    #[test]
+    fn plain_data_is_borrowed() {
+        assert!(matches!(
+            decompress(b"plain").unwrap(),
+            Cow::Borrowed(b"plain")
+        ));
+        assert!(decompress(&GZIP_MAGIC).is_err());
+    }
+}
@@ -0,0 +1,380 @@
+//! Filters which check the validity of dates, ported from the date filters of LanguageTool.
+//! The captures of a match are parsed with locale-dependent month and weekday names.
+
+use super::Filterable;
+use crate::rule::{engine::composition::GraphId, MatchGraph};
+use crate::tokenizer::Tokenizer;
+use serde::{Deserialize, Serialize};
+
+/// The language of month and weekday names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateLocale {
+    English,
+    German,
+}
+
+impl DateLocale {
+    /// Gets the locale from the language code of a filter class e. g. "de" in `org.languagetool.rules.de.DateCheckFilter`.
+    #[cfg(feature = "compile")]
+    pub fn from_lang_code(lang_code: &str) -> Option<Self> {
+        match lang_code {
+            "en" => Some(DateLocale::English),
+            "de" => Some(DateLocale::German),
+            _ => None,
+        }
+    }
+
+    fn month_names(&self) -> &'static [&'static [&'static str]] {
+        match self {
+            DateLocale::English => &[
+                &["january", "jan"],
+                &["february", "feb"],
+                &["march", "mar"],
+                &["april", "apr"],
+                &["may"],
+                &["june", "jun"],
+                &["july", "jul"],
+                &["august", "aug"],
+                &["september", "sep", "sept"],
+                &["october", "oct"],
+                &["november", "nov"],
+                &["december", "dec"],
+            ],
+            DateLocale::German => &[
+                &["januar", "jänner", "jan", "jän"],
+                &["februar", "feber", "feb"],
+                &["märz", "mär", "mrz"],
+                &["april", "apr"],
+                &["mai"],
+                &["juni", "jun"],
+                &["juli", "jul"],
+                &["august", "aug"],
+                &["september", "sep", "sept"],
+                &["oktober", "okt"],
+                &["november", "nov"],
+                &["dezember", "dez"],
+            ],
+        }
+    }
+
+    fn weekday_names(&self) -> &'static [&'static [&'static str]] {
+        match self {
+            DateLocale::English => &[
+                &["monday", "mon"],
+                &["tuesday", "tue", "tues"],
+                &["wednesday", "wed"],
+                &["thursday", "thu", "thur", "thurs"],
+                &["friday", "fri"],
+                &["saturday", "sat"],
+                &["sunday", "sun"],
+            ],
+            DateLocale::German => &[
+                &["montag", "mo"],
+                &["dienstag", "di"],
+                &["mittwoch", "mi"],
+                &["donnerstag", "do"],
+                &["freitag", "fr"],
+                &["samstag", "sonnabend", "sa"],
+                &["sonntag", "so"],
+            ],
+        }
+    }
+
+    fn find(names: &[&[&str]], text: &str) -> Option<u32> {
+        let text = text.trim_end_matches('.').to_lowercase();
+
+        names
+            .iter()
+            .position(|names| names.contains(&text.as_str()))
+            .map(|i| i as u32 + 1)
+    }
+
+    /// Parses a month name, abbreviation or number to the month number (1 - 12).
+    pub fn month(&self, text: &str) -> Option<u32> {
+        parse_number(text).or_else(|| Self::find(self.month_names(), text))
+    }
+
+    /// Parses a weekday name or abbreviation to the ISO weekday number (1 = Monday, 7 = Sunday).
+    pub fn weekday(&self, text: &str) -> Option<u32> {
+        Self::find(self.weekday_names(), text)
+    }
+
+    /// Gets the full name of an ISO weekday number.
+    pub fn weekday_name(&self, weekday: u32) -> &'static str {
+        let names = match self {
+            DateLocale::English => [
+                "Monday",
+                "Tuesday",
+                "Wednesday",
+                "Thursday",
+                "Friday",
+                "Saturday",
+                "Sunday",
+            ],
+            DateLocale::German => [
+                "Montag",
+                "Dienstag",
+                "Mittwoch",
+                "Donnerstag",
+                "Freitag",
+                "Samstag",
+                "Sonntag",
+            ],
+        };
+
+        names[(weekday - 1) as usize]
+    }
+}
+
+/// Parses the leading digits of e. g. "3", "03", "3." or "3rd".
+fn parse_number(text: &str) -> Option<u32> {
+    let digits: String = text.chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+pub(crate) fn is_leap_year(year: u32) -> bool {
+    match (year % 400, year % 100, year % 4) {
+        (0, _, _) => true,
+        (_, 0, _) => false,
+        (_, _, 0) => true,
+        _ => false,
+    }
+}
+
+/// The number of days in a month. If the year is unknown, February has 29 days.
+pub(crate) fn days_in_month(month: u32, year: Option<u32>) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 => {
+            // without a year, February 29 could be valid
+            match year {
+                Some(year) if !is_leap_year(year) => 28,
+                _ => 29,
+            }
+        }
+        _ => 0,
+    }
+}
+
+/// Whether the date exists in the (proleptic) Gregorian calendar.
+pub(crate) fn is_valid_date(day: u32, month: u32, year: Option<u32>) -> bool {
+    day >= 1 && day <= days_in_month(month, year)
+}
+
+/// The ISO weekday (1 = Monday, 7 = Sunday) of a valid date, see Sakamoto's method.
+pub(crate) fn weekday(day: u32, month: u32, year: u32) -> u32 {
+    const OFFSETS: [u32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+
+    let year = if month < 3 { year - 1 } else { year };
+    // 0 = Sunday
+    let weekday =
+        (year + year / 4 - year / 100 + year / 400 + OFFSETS[(month - 1) as usize] + day) % 7;
+
+    if weekday == 0 {
+        7
+    } else {
+        weekday
+    }
+}
+
+fn group_text<'t>(graph: &'t MatchGraph, id: GraphId) -> &'t str {
+    graph.by_id(id).text(graph.tokens()[0].sentence)
+}
+
+/// Keeps a match if the captured date does not exist e. g. "February 30".
+/// Corresponds to LanguageTool's `InvalidDateFilter`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvalidDateFilter {
+    pub(crate) day: GraphId,
+    pub(crate) month: GraphId,
+    pub(crate) year: Option<GraphId>,
+    pub(crate) locale: DateLocale,
+}
+
+impl InvalidDateFilter {
+    pub(crate) fn is_invalid(&self, day: &str, month: &str, year: Option<&str>) -> bool {
+        let day = parse_number(day);
+        let month = self.locale.month(month);
+        let year = year.and_then(parse_number);
+
+        match (day, month) {
+            (Some(day), Some(month)) => !is_valid_date(day, month, year),
+            // can not parse the date, so do not flag it
+            _ => false,
+        }
+    }
+}
+
+impl Filterable for InvalidDateFilter {
+    fn keep(&self, graph: &MatchGraph, _tokenizer: &Tokenizer) -> bool {
+        self.is_invalid(
+            group_text(graph, self.day),
+            group_text(graph, self.month),
+            self.year.map(|id| group_text(graph, id)),
+        )
+    }
+}
+
+/// Keeps a match if the captured weekday does not match the weekday of the captured date
+/// e. g. "Monday, 4 May 2021". Replaces `{realDay}` in the message with the correct weekday.
+/// Corresponds to LanguageTool's `DateCheckFilter`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DateCheckFilter {
+    pub(crate) weekday: GraphId,
+    pub(crate) day: GraphId,
+    pub(crate) month: GraphId,
+    pub(crate) year: GraphId,
+    pub(crate) locale: DateLocale,
+}
+
+impl DateCheckFilter {
+    /// Gets the weekday of the date if it does not match the given weekday.
+    pub(crate) fn real_weekday(
+        &self,
+        weekday: &str,
+        day: &str,
+        month: &str,
+        year: &str,
+    ) -> Option<u32> {
+        let weekday = self.locale.weekday(weekday)?;
+        let day = parse_number(day)?;
+        let month = self.locale.month(month)?;
+        let year = parse_number(year)?;
+
+        // invalid dates are handled by the `InvalidDateFilter`
+        if year == 0 || !is_valid_date(day, month, Some(year)) {
+            return None;
+        }
+
+        let real = self::weekday(day, month, year);
+        if real != weekday {
+            Some(real)
+        } else {
+            None
+        }
+    }
+
+    fn real_weekday_in(&self, graph: &MatchGraph) -> Option<u32> {
+        self.real_weekday(
+            group_text(graph, self.weekday),
+            group_text(graph, self.day),
+            group_text(graph, self.month),
+            group_text(graph, self.year),
+        )
+    }
+}
+
+impl Filterable for DateCheckFilter {
+    fn keep(&self, graph: &MatchGraph, _tokenizer: &Tokenizer) -> bool {
+        self.real_weekday_in(graph).is_some()
+    }
+
+    fn adjust_message(&self, message: String, graph: &MatchGraph) -> String {
+        match self.real_weekday_in(graph) {
+            Some(real) => message.replace("{realDay}", self.locale.weekday_name(real)),
+            None => message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leap_years() {
+        assert!(is_leap_year(2000));
+        assert!(is_leap_year(2024));
+        assert!(!is_leap_year(1900));
+        assert!(!is_leap_year(2023));
+
+        assert!(is_valid_date(29, 2, Some(2024)));
+        assert!(is_valid_date(29, 2, Some(2000)));
+        assert!(!is_valid_date(29, 2, Some(1900)));
+        assert!(!is_valid_date(29, 2, Some(2021)));
+        // without a year, February 29 could be valid
+        assert!(is_valid_date(29, 2, None));
+        assert!(!is_valid_date(30, 2, None));
+    }
+
+    #[test]
+    fn weekdays() {
+        assert_eq!(weekday(3, 5, 2021), 1);
+        assert_eq!(weekday(29, 2, 2024), 4);
+        assert_eq!(weekday(1, 1, 2000), 6);
+        assert_eq!(weekday(31, 12, 1999), 5);
+    }
+
+    fn invalid_date_filter(locale: DateLocale) -> InvalidDateFilter {
+        InvalidDateFilter {
+            day: GraphId(0),
+            month: GraphId(0),
+            year: None,
+            locale,
+        }
+    }
+
+    #[test]
+    fn invalid_dates_mdy() {
+        let filter = invalid_date_filter(DateLocale::English);
+
+        // "February 30, 2021"
+        assert!(filter.is_invalid("30", "February", Some("2021")));
+        // "Feb. 29, 2021"
+        assert!(filter.is_invalid("29", "Feb.", Some("2021")));
+        // "February 29th, 2024"
+        assert!(!filter.is_invalid("29th", "February", Some("2024")));
+        // "13/32/2020"
+        assert!(filter.is_invalid("32", "12", Some("2020")));
+        assert!(filter.is_invalid("1", "13", Some("2020")));
+        assert!(!filter.is_invalid("31", "December", None));
+        assert!(filter.is_invalid("31", "April", None));
+    }
+
+    #[test]
+    fn invalid_dates_dmy() {
+        let filter = invalid_date_filter(DateLocale::German);
+
+        // "30. Februar 2021"
+        assert!(filter.is_invalid("30.", "Februar", Some("2021")));
+        // "3.13.2021"
+        assert!(filter.is_invalid("3.", "13.", Some("2021")));
+        // "29. Feb. 2000"
+        assert!(!filter.is_invalid("29.", "Feb.", Some("2000")));
+        assert!(filter.is_invalid("29.", "Feb.", Some("1900")));
+        assert!(!filter.is_invalid("31.", "März", None));
+        // unparseable dates are not flagged
+        assert!(!filter.is_invalid("x", "Februar", None));
+    }
+
+    #[test]
+    fn weekday_mismatches() {
+        let filter = DateCheckFilter {
+            weekday: GraphId(0),
+            day: GraphId(0),
+            month: GraphId(0),
+            year: GraphId(0),
+            locale: DateLocale::English,
+        };
+
+        // "Monday, May 3, 2021"
+        assert_eq!(filter.real_weekday("Monday", "3", "May", "2021"), None);
+        // "Tuesday, May 3, 2021"
+        assert_eq!(filter.real_weekday("Tuesday", "3", "May", "2021"), Some(1));
+        assert_eq!(filter.real_weekday("Thu", "29", "Feb", "2024"), None);
+        // invalid dates are left to the `InvalidDateFilter`
+        assert_eq!(filter.real_weekday("Monday", "29", "Feb", "2021"), None);
+
+        let filter = DateCheckFilter {
+            locale: DateLocale::German,
+            ..filter
+        };
+
+        // "Montag, 3.5.2021"
+        assert_eq!(filter.real_weekday("Montag", "3.", "5.", "2021"), None);
+        // "Freitag, 3. Mai 2021"
+        assert_eq!(filter.real_weekday("Freitag", "3.", "Mai", "2021"), Some(1));
+        assert_eq!(DateLocale::German.weekday_name(1), "Montag");
+    }
+}
@@ -4,18 +4,37 @@ use crate::utils::regex::Regex;
 use enum_dispatch::enum_dispatch;
 use serde::{Deserialize, Serialize};
 
+mod date;
+#[cfg(feature = "compile")]
+pub use date::DateLocale;
+pub use date::{DateCheckFilter, InvalidDateFilter};
+
+// the variants are named after the filter classes of LanguageTool
+#[allow(clippy::enum_variant_names, clippy::large_enum_variant)]
 #[enum_dispatch]
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum Filter {
     NoDisambiguationEnglishPartialPosTagFilter,
+    InvalidDateFilter,
+    DateCheckFilter,
 }
 
 #[enum_dispatch(Filter)]
 pub trait Filterable {
     fn keep(&self, graph: &MatchGraph, tokenizer: &Tokenizer) -> bool;
+
+    /// Adjusts the message of a suggestion from a match which was kept e. g. to fill in placeholders.
+    fn adjust_message(&self, message: String, _graph: &MatchGraph) -> String {
+        message
+    }
+
+    /// The regexes this filter matches with.
+    fn regexes(&self) -> Vec<&Regex> {
+        Vec::new()
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct NoDisambiguationEnglishPartialPosTagFilter {
     pub(crate) id: GraphId,
     pub(crate) regexp: Regex,
@@ -39,4 +58,8 @@ impl Filterable for NoDisambiguationEnglishPartialPosTagFilter {
             }
         })
     }
+
+    fn regexes(&self) -> Vec<&Regex> {
+        vec![&self.regexp, &self.postag_regexp]
+    }
 }
@@ -23,7 +23,7 @@
 //! # Example: get suggestions and correct a text
 //!
 //! ```no_run
-//! use nlprule::{Tokenizer, Rules, types::Suggestion, rules::apply_suggestions};
+//! use nlprule::{Tokenizer, Rules, types::{Suggestion, SegmentType, Severity, Span}, rules::apply_suggestions};
 //!
 //! let tokenizer = Tokenizer::new("path/to/en_tokenizer.bin")?;
 //! let rules = Rules::new("path/to/en_rules.bin")?;
@@ -34,11 +34,16 @@
 //! assert_eq!(
 //!     suggestions,
 //!     vec![Suggestion {
-//!         start: 4, // these are character indices!
-//!         end: 16,
+//!         // character and byte offsets coincide for ASCII text
+//!         span: Span::new(4..16, 4..16),
 //!         replacements: vec!["was not".into(), "has not been".into()],
 //!         source: "WAS_BEEN.1".into(),
-//!         message: "Did you mean was not or has not been?".into()
+//!         message: "Did you mean was not or has not been?".into(),
+//!         short_message: String::new(),
+//!         url: None,
+//!         severity: Severity::Error,
+//!         segment_type: SegmentType::Sentence,
+//!         replacement_distance: 1,
 //!     }]
 //! );
 //!
@@ -58,11 +63,17 @@ use std::io;
 
 use thiserror::Error;
 
+pub mod bench;
 #[cfg(feature = "compile")]
 pub mod compile;
+pub mod compression;
 mod filter;
 pub mod rule;
 pub mod rules;
+pub mod schema;
+pub mod strictness;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 pub mod tokenizer;
 pub mod types;
 pub(crate) mod utils;
@@ -77,6 +88,44 @@ pub enum Error {
     Io(#[from] io::Error),
     #[error("deserialization error: {0}")]
     Deserialization(#[from] bincode::Error),
+    #[error("unsupported config version: {0}")]
+    UnsupportedConfigVersion(u32),
+    #[error("unknown rule IDs in config: {}", .0.join(", "))]
+    UnknownRuleIds(Vec<String>),
+    #[error("no rule matches the ID {id}, close matches: {close_matches:?}")]
+    NoMatchingRules {
+        id: String,
+        close_matches: Vec<String>,
+    },
+    #[error("estimated memory footprint of {required} bytes exceeds the budget of {budget} bytes")]
+    MemoryBudgetExceeded { budget: usize, required: usize },
+    #[error("invalid options: {0}")]
+    InvalidOptions(String),
+    #[error("invalid message for rule {id}: group \\{group} does not exist")]
+    InvalidMessage { id: String, group: usize },
+    #[error("invalid pattern at index {index}: {message}")]
+    InvalidPattern { index: usize, message: String },
+    #[error("degraded behavior in strict mode: {0}")]
+    Degraded(strictness::Degradation),
+    #[error("suggestion observer panicked: {0}")]
+    ObserverPanicked(String),
+    #[error(
+        "the binary was built with nlprule {found} which is not compatible with nlprule {expected}"
+    )]
+    IncompatibleBinary { found: String, expected: String },
+    #[error("the data does not start with an nlprule binary header, it is not a binary or was built with nlprule 0.4.6 or older")]
+    MissingBinaryHeader,
+    #[error("expected a {expected} binary, found a {found} binary")]
+    UnexpectedBinaryKind {
+        expected: types::BinaryKind,
+        found: types::BinaryKind,
+    },
+    #[error("unknown part-of-speech tag: {0}")]
+    UnknownPosTag(String),
+    #[error("the tagger is shared and can not be changed, drop the other references to it first")]
+    SharedTagger,
+    #[error("the binary is {0} compressed, which needs the `{0}` feature of nlprule")]
+    UnsupportedCompression(compression::Compression),
 }
 
 /// Gets the canonical filename for the tokenizer binary for a language code in ISO 639-1 (two-letter) format.
@@ -19,9 +19,17 @@ impl Matcher {
         graph: &MatchGraph,
         case_sensitive: Option<bool>,
     ) -> bool {
-        input
-            .iter()
-            .any(|x| self.is_match(x.as_ref(), graph, case_sensitive))
+        // a negated matcher matches if *no* element matches the inner matcher,
+        // so it has to hold for all elements instead of any element
+        if self.negate {
+            input
+                .iter()
+                .all(|x| self.is_match(x.as_ref(), graph, case_sensitive))
+        } else {
+            input
+                .iter()
+                .any(|x| self.is_match(x.as_ref(), graph, case_sensitive))
+        }
     }
 
     pub fn is_match(&self, input: &str, graph: &MatchGraph, case_sensitive: Option<bool>) -> bool {
@@ -159,6 +167,9 @@ pub enum Atom {
     SpaceBeforeAtom(concrete::SpaceBeforeAtom),
     TextAtom(concrete::TextAtom),
     WordDataAtom(concrete::WordDataAtom),
+    RepeatAtom(concrete::RepeatAtom),
+    QuoteAtom(concrete::QuoteAtom),
+    ClassAtom(concrete::ClassAtom),
     TrueAtom,
     FalseAtom,
     AndAtom,
@@ -167,9 +178,183 @@ pub enum Atom {
     OffsetAtom,
 }
 
+impl Atom {
+    /// Whether this atom matches on chunks i. e. can not match as intended without a chunker.
+    pub(crate) fn uses_chunks(&self) -> bool {
+        match self {
+            Atom::ChunkAtom(_) => true,
+            Atom::AndAtom(atom) => atom.atoms.iter().any(Atom::uses_chunks),
+            Atom::OrAtom(atom) => atom.atoms.iter().any(Atom::uses_chunks),
+            Atom::NotAtom(atom) => atom.atom.uses_chunks(),
+            Atom::OffsetAtom(atom) => atom.atom.uses_chunks(),
+            _ => false,
+        }
+    }
+
+    /// The regexes this atom matches with, including those of nested atoms.
+    pub(crate) fn regexes(&self) -> Vec<&Regex> {
+        match self {
+            Atom::TextAtom(atom) => atom.matcher.matcher.regexes(),
+            Atom::ChunkAtom(atom) => atom.matcher.regexes(),
+            Atom::WordDataAtom(atom) => atom
+                .matcher
+                .inflect_matcher
+                .as_ref()
+                .map_or_else(Vec::new, |x| x.matcher.regexes()),
+            Atom::AndAtom(atom) => atom.atoms.iter().flat_map(Atom::regexes).collect(),
+            Atom::OrAtom(atom) => atom.atoms.iter().flat_map(Atom::regexes).collect(),
+            Atom::NotAtom(atom) => atom.atom.regexes(),
+            Atom::OffsetAtom(atom) => atom.atom.regexes(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Words of which at least one has to occur in a sentence for this atom to match anywhere in it.
+    /// `None` if no such words are known e. g. for atoms matching on POS tags.
+    #[cfg(feature = "compile")]
+    pub(crate) fn triggers(&self) -> Option<Vec<Trigger>> {
+        match self {
+            Atom::TextAtom(atom) => atom.matcher.matcher.triggers(),
+            // any child has to match, so the most selective one suffices
+            Atom::AndAtom(atom) => atom
+                .atoms
+                .iter()
+                .filter_map(Atom::triggers)
+                .min_by_key(Vec::len),
+            Atom::OrAtom(atom) => atom
+                .atoms
+                .iter()
+                .map(Atom::triggers)
+                .collect::<Option<Vec<_>>>()
+                .map(|triggers| triggers.into_iter().flatten().collect()),
+            Atom::OffsetAtom(atom) => atom.atom.triggers(),
+            _ => None,
+        }
+    }
+}
+
+/// A word which has to occur in a sentence for a pattern to match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Trigger {
+    /// Occurs if a token is equal to the word, ignoring case.
+    Word(String),
+    /// Occurs if the lowercased (char by char) text of a token is equal to the word.
+    Lower(String),
+}
+
+/// The token texts of a sentence to look up [Trigger]s in.
+pub(crate) struct SentenceWords<'t> {
+    words: DefaultHashSet<UniCase<&'t str>>,
+    lower: DefaultHashSet<String>,
+}
+
+impl<'t> SentenceWords<'t> {
+    pub fn new(tokens: &'t [Token]) -> Self {
+        SentenceWords {
+            words: tokens
+                .iter()
+                .map(|token| UniCase::new(token.word.text.as_ref()))
+                .collect(),
+            lower: tokens
+                .iter()
+                .map(|token| lowercase_chars(token.word.text.as_ref()))
+                .collect(),
+        }
+    }
+
+    pub fn contains(&self, trigger: &Trigger) -> bool {
+        match trigger {
+            Trigger::Word(word) => self.words.contains(&UniCase::new(word.as_str())),
+            Trigger::Lower(word) => self.lower.contains(word),
+        }
+    }
+}
+
+/// Lowercases each char on its own. Unlike [str::to_lowercase] this does not depend on the context of a char
+/// (e. g. the final sigma), so it is consistent with regexes matching char by char.
+fn lowercase_chars(text: &str) -> String {
+    text.chars().flat_map(char::to_lowercase).collect()
+}
+
+/// The lowercase variant of a char which is allowed in triggers, if it has exactly one.
+#[cfg(feature = "compile")]
+fn trigger_char(c: char) -> Option<char> {
+    if !(c.is_alphanumeric() || c == '\'') {
+        return None;
+    }
+
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(lower), None) => Some(lower),
+        _ => None,
+    }
+}
+
+/// Extracts the words from a compiled regex of the form `^(?:word|word)$` where each word consists of
+/// literal chars or classes of case variants of one char (e. g. `[cC][aA][nN]`).
+/// Returns `None` if the regex can match anything else.
+#[cfg(feature = "compile")]
+fn regex_triggers(regex: &str) -> Option<Vec<String>> {
+    let inner = regex.strip_prefix("^(?:")?.strip_suffix(")$")?;
+
+    inner
+        .split('|')
+        .map(|alternative| {
+            let mut word = String::new();
+            let mut chars = alternative.chars();
+
+            while let Some(c) = chars.next() {
+                let variants: Vec<char> = if c == '[' {
+                    chars.by_ref().take_while(|c| *c != ']').collect()
+                } else {
+                    vec![c]
+                };
+
+                let lower = trigger_char(*variants.first()?)?;
+                if !variants.iter().all(|c| trigger_char(*c) == Some(lower)) {
+                    return None;
+                }
+                word.push(lower);
+            }
+
+            if word.is_empty() {
+                None
+            } else {
+                Some(word)
+            }
+        })
+        .collect()
+}
+
+impl Matcher {
+    pub(crate) fn regexes(&self) -> Vec<&Regex> {
+        match &self.matcher {
+            either::Right(regex) => vec![regex],
+            either::Left(_) => Vec::new(),
+        }
+    }
+
+    #[cfg(feature = "compile")]
+    fn triggers(&self) -> Option<Vec<Trigger>> {
+        if self.negate {
+            return None;
+        }
+
+        match &self.matcher {
+            either::Left(either::Left(string)) if !string.is_empty() => {
+                Some(vec![Trigger::Word(string.clone())])
+            }
+            either::Right(regex) => regex_triggers(regex.as_str())
+                .map(|words| words.into_iter().map(Trigger::Lower).collect()),
+            _ => None,
+        }
+    }
+}
+
 pub mod concrete {
     use super::{Atomable, MatchGraph, Matcher, TextMatcher, Token, WordDataMatcher};
     use serde::{Deserialize, Serialize};
+    use unicase::UniCase;
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct TextAtom {
@@ -178,6 +363,11 @@ pub mod concrete {
 
     impl Atomable for TextAtom {
         fn is_match(&self, input: &[Token], graph: &MatchGraph, position: usize) -> bool {
+            // running (regex) matchers on very long tokens is slow, and they are not meaningful words anyway
+            if input[position].oversized {
+                return false;
+            }
+
             self.matcher
                 .is_match(&input[position].word.text, graph, None)
         }
@@ -220,6 +410,86 @@ pub mod concrete {
                 .is_match(&tags, graph, Some(self.case_sensitive))
         }
     }
+
+    /// Matches if the token has the same text as the closest previous token whose text is not in `skip`
+    /// e. g. "the" in "the, the" if "," is skipped.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct RepeatAtom {
+        pub(crate) skip: Vec<String>,
+        pub(crate) case_sensitive: bool,
+    }
+
+    impl Atomable for RepeatAtom {
+        fn is_match(&self, input: &[Token], _graph: &MatchGraph, position: usize) -> bool {
+            let text = input[position].word.text.as_ref();
+            let previous = input[..position]
+                .iter()
+                .rev()
+                .map(|token| token.word.text.as_ref())
+                .find(|previous| !self.skip.iter().any(|x| x == previous));
+
+            match previous {
+                // the special SENT_START token has empty text
+                Some(previous) if !previous.is_empty() => {
+                    if self.case_sensitive {
+                        previous == text
+                    } else {
+                        UniCase::new(previous) == UniCase::new(text)
+                    }
+                }
+                _ => false,
+            }
+        }
+    }
+
+    /// Matches a straight quote which opens (or closes) a pair of quotes whose nesting depth modulo `n_levels` is `level`.
+    /// See [pair_straight_quotes](crate::utils::pair_straight_quotes) for how quotes are paired.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct QuoteAtom {
+        pub(crate) opening: bool,
+        pub(crate) level: usize,
+        pub(crate) n_levels: usize,
+    }
+
+    impl Atomable for QuoteAtom {
+        fn is_match(&self, input: &[Token], _graph: &MatchGraph, position: usize) -> bool {
+            let token = &input[position];
+            if token.original_text != "\"" && token.original_text != "'" {
+                return false;
+            }
+
+            crate::utils::pair_straight_quotes(token.sentence)
+                .into_iter()
+                .any(|pair| {
+                    let index = if self.opening { pair.open } else { pair.close };
+                    index == token.span.char().start && pair.depth % self.n_levels == self.level
+                })
+        }
+    }
+
+    /// Matches if the token is in the token class with the given ID,
+    /// see [TokenClasses](crate::tokenizer::tag::TokenClasses). Behaves like a negated [TextAtom] if `negate` is set.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct ClassAtom {
+        pub(crate) class: usize,
+        pub(crate) negate: bool,
+    }
+
+    impl Atomable for ClassAtom {
+        fn is_match(&self, input: &[Token], _graph: &MatchGraph, position: usize) -> bool {
+            let token = &input[position];
+            // consistent with the `TextAtom`, oversized tokens never match
+            if token.oversized {
+                return false;
+            }
+
+            token
+                .tagger
+                .token_classes()
+                .contains(token.word.text.as_ref(), self.class)
+                != self.negate
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -313,9 +583,9 @@ impl Group {
         let end = self.char_span.1;
 
         tokens.iter().filter(move |x| {
-            x.char_span.1 > x.char_span.0 // special tokens with zero range (e. g. SENT_START) can not be part of groups
-                    && x.char_span.0 >= start
-                    && x.char_span.1 <= end
+            x.span.char().end > x.span.char().start // special tokens with zero range (e. g. SENT_START) can not be part of groups
+                    && x.span.char().start >= start
+                    && x.span.char().end <= end
         })
     }
 
@@ -409,7 +679,11 @@ impl<'t> MatchGraph<'t> {
         let mut start = self
             .groups
             .iter()
-            .find_map(|x| x.tokens(&self.tokens).next().map(|token| token.char_span.0))
+            .find_map(|x| {
+                x.tokens(&self.tokens)
+                    .next()
+                    .map(|token| token.span.char().start)
+            })
             .expect("graph must contain at least one token");
 
         let mut end = self
@@ -419,7 +693,7 @@ impl<'t> MatchGraph<'t> {
             .find_map(|x| {
                 x.tokens(&self.tokens)
                     .next_back()
-                    .map(|token| token.char_span.1)
+                    .map(|token| token.span.char().end)
             })
             .expect("graph must contain at least one token");
 
@@ -431,9 +705,9 @@ impl<'t> MatchGraph<'t> {
 
         for (group, tokens) in self.groups.iter_mut().zip(group_tokens.iter()) {
             if !tokens.is_empty() {
-                group.char_span.0 = tokens[0].char_span.0;
-                group.char_span.1 = tokens[tokens.len() - 1].char_span.1;
-                start = tokens[tokens.len() - 1].char_span.1;
+                group.char_span.0 = tokens[0].span.char().start;
+                group.char_span.1 = tokens[tokens.len() - 1].span.char().end;
+                start = tokens[tokens.len() - 1].span.char().end;
             } else {
                 group.char_span.1 = start;
             }
@@ -441,7 +715,7 @@ impl<'t> MatchGraph<'t> {
 
         for (group, tokens) in self.groups.iter_mut().zip(group_tokens.iter()).rev() {
             if !tokens.is_empty() {
-                end = tokens[0].char_span.0;
+                end = tokens[0].span.char().start;
             } else {
                 group.char_span.0 = end;
             }
@@ -534,9 +808,9 @@ impl Composition {
 
                 // set the group beginning if the char end was zero (i. e. the group was empty)
                 if group.char_span.1 == 0 {
-                    group.char_span.0 = tokens[position].char_span.0;
+                    group.char_span.0 = tokens[position].span.char().start;
                 }
-                group.char_span.1 = tokens[position].char_span.1;
+                group.char_span.1 = tokens[position].span.char().end;
 
                 position += 1;
                 cur_count += 1;
@@ -559,6 +833,21 @@ impl Composition {
         }
     }
 
+    pub(crate) fn regexes(&self) -> Vec<&Regex> {
+        self.parts.iter().flat_map(|x| x.atom.regexes()).collect()
+    }
+
+    /// Words of which at least one has to occur in a sentence for this composition to match.
+    /// Uses the most selective required part. `None` if no required part has known trigger words.
+    #[cfg(feature = "compile")]
+    pub(crate) fn triggers(&self) -> Option<Vec<Trigger>> {
+        self.parts
+            .iter()
+            .filter(|part| part.quantifier.min > 0)
+            .filter_map(|part| part.atom.triggers())
+            .min_by_key(Vec::len)
+    }
+
     pub fn apply<'t>(&'t self, tokens: &'t [Token<'t>], start: usize) -> Option<MatchGraph<'t>> {
         // this path is extremely hot so more optimizations are done
 
@@ -585,3 +874,369 @@ impl Composition {
         self.apply_recursive(tokens, position, 0, graph)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "compile")]
+    use super::concrete::TextAtom;
+    use super::{
+        concrete::{ChunkAtom, ClassAtom, QuoteAtom, RepeatAtom},
+        *,
+    };
+    use crate::tokenizer::tag::{Tagger, TokenClasses};
+
+    fn token_with_chunks<'t>(chunks: &[&str], tagger: &'t Tagger) -> Token<'t> {
+        Token {
+            word: Word::new_with_tags(tagger.id_word("house".into()), Vec::new()),
+            original_text: "house",
+            span: Span::new(0..5, 0..5),
+            has_space_before: false,
+            chunks: chunks.iter().map(|x| x.to_string()).collect(),
+            oversized: false,
+            is_known: true,
+            sentence: "house",
+            tagger,
+        }
+    }
+
+    fn chunk_atom(matcher: either::Either<String, Regex>, negate: bool) -> ChunkAtom {
+        ChunkAtom {
+            matcher: Matcher {
+                matcher: matcher.map_left(either::Left),
+                negate,
+                case_sensitive: true,
+                empty_always_false: true,
+            },
+        }
+    }
+
+    #[test]
+    fn chunk_regex_matches_any_of_multiple_chunks() {
+        let tagger = Tagger::default();
+        let tokens = vec![token_with_chunks(&["E-NP-singular", "B-VP"], &tagger)];
+        let graph = MatchGraph::default();
+
+        let atom = chunk_atom(either::Right(Regex::new("^(?:B-VP.*)$".into())), false);
+        assert!(atom.is_match(&tokens, &graph, 0));
+
+        let atom = chunk_atom(either::Right(Regex::new("^(?:B-NP.*)$".into())), false);
+        assert!(!atom.is_match(&tokens, &graph, 0));
+
+        // regexes must match the whole chunk, not a substring of it
+        let atom = chunk_atom(either::Right(Regex::new("^(?:NP.*)$".into())), false);
+        assert!(!atom.is_match(&tokens, &graph, 0));
+    }
+
+    #[test]
+    fn chunk_string_matches_exactly() {
+        let tagger = Tagger::default();
+        let tokens = vec![token_with_chunks(&["E-NP-singular", "B-VP"], &tagger)];
+        let graph = MatchGraph::default();
+
+        let atom = chunk_atom(either::Left("E-NP-singular".into()), false);
+        assert!(atom.is_match(&tokens, &graph, 0));
+
+        let atom = chunk_atom(either::Left("E-NP".into()), false);
+        assert!(!atom.is_match(&tokens, &graph, 0));
+    }
+
+    #[test]
+    fn negated_chunk_matcher_requires_no_chunk_to_match() {
+        let tagger = Tagger::default();
+        let tokens = vec![
+            token_with_chunks(&["E-NP-singular", "B-VP"], &tagger),
+            token_with_chunks(&["I-NP-plural"], &tagger),
+            token_with_chunks(&[], &tagger),
+        ];
+        let graph = MatchGraph::default();
+
+        let atom = chunk_atom(either::Right(Regex::new("^(?:B-VP.*)$".into())), true);
+        assert!(!atom.is_match(&tokens, &graph, 0));
+        assert!(atom.is_match(&tokens, &graph, 1));
+        assert!(atom.is_match(&tokens, &graph, 2));
+    }
+
+    #[test]
+    fn nested_chunk_atoms_use_chunks() {
+        let atom: Atom = chunk_atom(either::Left("B-NP".into()), false).into();
+        assert!(atom.uses_chunks());
+
+        let atom: Atom = OrAtom {
+            atoms: vec![TrueAtom {}.into(), NotAtom { atom: atom.into() }.into()],
+        }
+        .into();
+        assert!(atom.uses_chunks());
+
+        let atom: Atom = AndAtom {
+            atoms: vec![TrueAtom {}.into(), FalseAtom {}.into()],
+        }
+        .into();
+        assert!(!atom.uses_chunks());
+    }
+
+    fn tokens_from_words<'t>(words: &[&'t str], tagger: &'t Tagger) -> Vec<Token<'t>> {
+        words
+            .iter()
+            .map(|word| Token {
+                word: Word::new_with_tags(tagger.id_word((*word).into()), Vec::new()),
+                original_text: word,
+                span: Span::default(),
+                has_space_before: true,
+                chunks: Vec::new(),
+                oversized: false,
+                is_known: true,
+                sentence: "",
+                tagger,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repeat_atom_matches_previous_word() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+        let atom = RepeatAtom {
+            skip: vec![",".into()],
+            case_sensitive: false,
+        };
+
+        let tokens = tokens_from_words(&["", "The", "the", "house"], &tagger);
+        assert!(atom.is_match(&tokens, &graph, 2));
+        assert!(!atom.is_match(&tokens, &graph, 3));
+        // must not compare against the SENT_START token
+        assert!(!atom.is_match(&tokens, &graph, 1));
+
+        let tokens = tokens_from_words(&["", "the", ",", ",", "the"], &tagger);
+        assert!(atom.is_match(&tokens, &graph, 4));
+
+        let tokens = tokens_from_words(&["", "the", ";", "the"], &tagger);
+        assert!(!atom.is_match(&tokens, &graph, 3));
+
+        let atom = RepeatAtom {
+            skip: Vec::new(),
+            case_sensitive: true,
+        };
+        let tokens = tokens_from_words(&["", "The", "the"], &tagger);
+        assert!(!atom.is_match(&tokens, &graph, 2));
+    }
+
+    #[test]
+    fn quote_atom_matches_paired_quotes() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+        let sentence = r#""a 'b' c" it's"#;
+
+        // one token per char, which is enough for ASCII text
+        let tokens: Vec<_> = (0..sentence.len())
+            .map(|i| Token {
+                original_text: &sentence[i..i + 1],
+                span: Span::new(i..i + 1, i..i + 1),
+                sentence,
+                ..tokens_from_words(&[""], &tagger).remove(0)
+            })
+            .collect();
+        let matches = |opening: bool, level: usize| -> Vec<usize> {
+            let atom = QuoteAtom {
+                opening,
+                level,
+                n_levels: 2,
+            };
+            (0..tokens.len())
+                .filter(|i| atom.is_match(&tokens, &graph, *i))
+                .collect()
+        };
+
+        assert_eq!(matches(true, 0), vec![0]);
+        assert_eq!(matches(false, 0), vec![8]);
+        assert_eq!(matches(true, 1), vec![3]);
+        assert_eq!(matches(false, 1), vec![5]);
+    }
+
+    #[test]
+    fn class_atom_matches_class_members() {
+        let mut classes = std::collections::BTreeMap::new();
+        classes.insert(
+            "preposition".to_owned(),
+            vec!["in".to_owned(), "on".to_owned()],
+        );
+        classes.insert(
+            "stopword".to_owned(),
+            vec!["the".to_owned(), "in".to_owned()],
+        );
+
+        let tagger = Tagger {
+            token_classes: TokenClasses::new(&classes),
+            ..Tagger::default()
+        };
+        let graph = MatchGraph::default();
+        let mut tokens = tokens_from_words(&["In", "the", "house", "ON"], &tagger);
+        tokens[2].oversized = true;
+
+        let stopword = tagger.token_classes().id("stopword").unwrap();
+        let atom = ClassAtom {
+            class: stopword,
+            negate: false,
+        };
+        let matches: Vec<_> = (0..tokens.len())
+            .filter(|i| atom.is_match(&tokens, &graph, *i))
+            .collect();
+        assert_eq!(matches, vec![0, 1]);
+
+        // like negated text matchers, negated class atoms do not match oversized tokens
+        let atom = ClassAtom {
+            class: stopword,
+            negate: true,
+        };
+        let matches: Vec<_> = (0..tokens.len())
+            .filter(|i| atom.is_match(&tokens, &graph, *i))
+            .collect();
+        assert_eq!(matches, vec![3]);
+
+        assert_eq!(
+            tagger.token_classes().classes_of("IN"),
+            vec!["preposition", "stopword"]
+        );
+        assert!(tagger.token_classes().classes_of("house").is_empty());
+    }
+
+    #[test]
+    fn pos_matcher_respects_tagger_overrides() {
+        let mut tagger = Tagger::default();
+        tagger.tag_store.insert("NN".into(), PosIdInt(0));
+        tagger.tag_store.insert("NNP".into(), PosIdInt(1));
+        tagger.add_words(&[("Apple", "apple", "NN")]).unwrap();
+
+        let graph = MatchGraph::default();
+        let matcher = WordDataMatcher {
+            pos_matcher: Some(PosMatcher {
+                mask: vec![true, false],
+            }),
+            inflect_matcher: None,
+        };
+        assert!(matcher.is_match(&tagger.get_tags("Apple"), &graph, None));
+
+        tagger.override_tags("Apple", &[("Apple", "NNP")]).unwrap();
+        assert!(!matcher.is_match(&tagger.get_tags("Apple"), &graph, None));
+    }
+
+    #[cfg(feature = "compile")]
+    fn text_matcher(matcher: either::Either<String, Regex>, negate: bool) -> Atom {
+        TextAtom {
+            matcher: TextMatcher {
+                matcher: Matcher {
+                    matcher: matcher.map_left(either::Left),
+                    negate,
+                    case_sensitive: false,
+                    empty_always_false: true,
+                },
+                set: None,
+            },
+        }
+        .into()
+    }
+
+    #[test]
+    #[cfg(feature = "compile")]
+    fn triggers_are_extracted_from_literal_regexes() {
+        let words = |words: &[&str]| Some(words.iter().map(|x| x.to_string()).collect());
+
+        assert_eq!(
+            regex_triggers("^(?:[cC][aA][nN]|[cC][oO][uU][lL][dD])$"),
+            words(&["can", "could"])
+        );
+        assert_eq!(regex_triggers("^(?:can|could)$"), words(&["can", "could"]));
+        assert_eq!(
+            regex_triggers("^(?:[dD][oO][nN]['][tT])$"),
+            words(&["don't"])
+        );
+        assert_eq!(regex_triggers("^(?:[cCcC]an)$"), words(&["can"]));
+        assert_eq!(regex_triggers("^(?:Straße)$"), words(&["straße"]));
+
+        for regex in &[
+            "^(?:can.*)$",
+            "^(?:[cd]an)$",
+            "^(?:[a-z]an)$",
+            "^(?:(?i)can)$",
+            "^(?:ca(n|t))$",
+            "^(?:can|)$",
+            "^(?:c\\.an)$",
+            "can",
+            // 'İ' has no single-char lowercase variant
+            "^(?:İ)$",
+        ] {
+            assert_eq!(regex_triggers(regex), None, "{}", regex);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "compile")]
+    fn triggers_are_only_extracted_from_positive_text_atoms() {
+        let can = text_matcher(either::Left("can".into()), false);
+        assert_eq!(can.triggers(), Some(vec![Trigger::Word("can".into())]));
+        assert_eq!(
+            text_matcher(either::Left("can".into()), true).triggers(),
+            None
+        );
+
+        let regex = || text_matcher(either::Right(Regex::new("^(?:a|b)$".into())), false);
+        assert_eq!(
+            regex().triggers(),
+            Some(vec![Trigger::Lower("a".into()), Trigger::Lower("b".into())])
+        );
+
+        let atom: Atom = AndAtom {
+            atoms: vec![TrueAtom {}.into(), regex(), can],
+        }
+        .into();
+        assert_eq!(atom.triggers(), Some(vec![Trigger::Word("can".into())]));
+
+        // one alternative could match any word
+        let atom: Atom = OrAtom {
+            atoms: vec![regex(), TrueAtom {}.into()],
+        }
+        .into();
+        assert_eq!(atom.triggers(), None);
+
+        let atom: Atom = NotAtom {
+            atom: Box::new(regex()),
+        }
+        .into();
+        assert_eq!(atom.triggers(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "compile")]
+    fn optional_parts_are_not_used_as_triggers() {
+        let part = |text: &str, min: usize| Part {
+            atom: text_matcher(either::Left(text.into()), false),
+            quantifier: Quantifier { min, max: 1 },
+            greedy: true,
+            visible: true,
+            unify: None,
+        };
+        let composition = |parts: Vec<Part>| Composition {
+            id_to_idx: DefaultHashMap::new(),
+            can_stop_mask: vec![false; parts.len()],
+            parts,
+        };
+
+        assert_eq!(
+            composition(vec![part("could", 0), part("of", 1)]).triggers(),
+            Some(vec![Trigger::Word("of".into())])
+        );
+        assert_eq!(composition(vec![part("could", 0)]).triggers(), None);
+    }
+
+    #[test]
+    fn sentence_words_contain_triggers() {
+        let tagger = Tagger::default();
+        let tokens = tokens_from_words(&["", "He", "COULD", "ΟΔΟΣ"], &tagger);
+        let words = SentenceWords::new(&tokens);
+
+        assert!(words.contains(&Trigger::Word("could".into())));
+        assert!(words.contains(&Trigger::Lower("could".into())));
+        assert!(!words.contains(&Trigger::Lower("can".into())));
+        // chars are lowercased on their own, like a regex matching `[σΣ]` would
+        assert!(words.contains(&Trigger::Lower("οδοσ".into())));
+    }
+}
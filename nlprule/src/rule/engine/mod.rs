@@ -75,6 +75,7 @@ struct TextMatches<'a, 't> {
 enum InnerMatches<'a: 't, 't> {
     Token(TokenMatches<'a>),
     Text(TextMatches<'a, 't>),
+    Empty,
 }
 
 pub struct EngineMatches<'a, 't> {
@@ -132,11 +133,53 @@ impl<'a, 't> Iterator for EngineMatches<'a, 't> {
 
                 MatchGraph::new(groups, inner.id_to_idx, tokens)
             }),
+            InnerMatches::Empty => None,
         }
     }
 }
 
 impl Engine {
+    /// Maps the IDs of the groups in this engine to their index in the match graph.
+    pub(crate) fn id_to_idx(&self) -> &DefaultHashMap<GraphId, usize> {
+        match self {
+            Engine::Token(engine) => &engine.composition.id_to_idx,
+            Engine::Text(_, id_to_idx) => id_to_idx,
+        }
+    }
+
+    /// Whether the pattern of this engine matches on chunks.
+    pub(crate) fn uses_chunks(&self) -> bool {
+        match self {
+            Engine::Token(engine) => engine
+                .composition
+                .parts
+                .iter()
+                .any(|part| part.atom.uses_chunks()),
+            Engine::Text(..) => false,
+        }
+    }
+
+    /// The regexes of the pattern and antipatterns of this engine.
+    pub(crate) fn regexes(&self) -> Vec<&Regex> {
+        match self {
+            Engine::Token(engine) => std::iter::once(&engine.composition)
+                .chain(engine.antipatterns.iter())
+                .flat_map(Composition::regexes)
+                .collect(),
+            Engine::Text(regex, _) => vec![regex.as_ref()],
+        }
+    }
+
+    /// Words of which at least one has to occur in a sentence for this engine to find a match.
+    /// `None` if the engine has to run on every sentence.
+    #[cfg(feature = "compile")]
+    pub(crate) fn triggers(&self) -> Option<Vec<composition::Trigger>> {
+        match self {
+            Engine::Token(engine) => engine.composition.triggers(),
+            Engine::Text(..) => None,
+        }
+    }
+
     pub fn get_matches<'a, 't>(
         &'a self,
         tokens: &'t [Token],
@@ -155,6 +198,10 @@ impl Engine {
                     index: 0,
                     mask: vec![false; tokens[0].sentence.chars().count()],
                 }),
+                // regexes over the whole sentence would have to scan oversized tokens
+                Engine::Text(..) if tokens.iter().any(|token| token.oversized) => {
+                    InnerMatches::Empty
+                }
                 Engine::Text(regex, id_to_idx) => {
                     let sentence = tokens[0].sentence;
 
@@ -1,7 +1,11 @@
-use super::engine::composition::{GraphId, MatchGraph, PosMatcher};
+use super::engine::{
+    composition::{GraphId, MatchGraph, PosMatcher},
+    Engine,
+};
 use crate::types::*;
 use crate::{
-    tokenizer::Tokenizer,
+    rules::ApplyOptions,
+    tokenizer::{detokenize, Tokenizer},
     utils::{self, regex::Regex},
 };
 use serde::{Deserialize, Serialize};
@@ -12,7 +16,7 @@ impl std::cmp::PartialEq for Suggestion {
         let a: HashSet<&String> = self.replacements.iter().collect();
         let b: HashSet<&String> = other.replacements.iter().collect();
 
-        a.intersection(&b).count() > 0 && other.start == self.start && other.end == self.end
+        a.intersection(&b).count() > 0 && other.span.char() == self.span.char()
     }
 }
 
@@ -70,9 +74,7 @@ impl PosReplacer {
             .get_tags(text)
             .iter()
             .map(|x| {
-                let group_words = tokenizer
-                    .tagger()
-                    .get_group_members(&x.lemma.as_ref().to_string());
+                let group_words = tokenizer.tagger().get_group_members(x.lemma.as_ref());
                 let mut data = Vec::new();
                 for word in group_words {
                     if let Some(i) = tokenizer
@@ -141,16 +143,91 @@ pub enum SynthesizerPart {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Synthesizer {
     pub(crate) use_titlecase_adjust: bool,
+    /// Whether to produce nothing if the output contains unknown words. The output is split and normalized like
+    /// a sentence, so this agrees with [IncompleteToken::is_known][crate::types::IncompleteToken::is_known]
+    /// of the tokens in the corrected text.
+    /// Corresponds to `suppress_misspelled` in LanguageTool.
+    pub(crate) suppress_misspelled: bool,
     pub(crate) parts: Vec<SynthesizerPart>,
 }
 
 impl Synthesizer {
+    /// The regexes used to transform the matched text in this synthesizer.
+    pub(crate) fn regexes(&self) -> Vec<&Regex> {
+        self.parts
+            .iter()
+            .filter_map(|part| match part {
+                SynthesizerPart::Match(m) => m.regex_replacer.as_ref().map(|(regex, _)| regex),
+                SynthesizerPart::Text(_) => None,
+            })
+            .collect()
+    }
+
+    /// Parses a message template in which `\1`, `\2` etc. refer to the groups of the `engine`.
+    /// Returns the offending reference as error if a group does not exist.
+    pub(crate) fn from_template(template: &str, engine: &Engine) -> Result<Self, usize> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let id = match (c, chars.peek().and_then(|x| x.to_digit(10))) {
+                ('\\', Some(id)) => id as usize,
+                _ => {
+                    text.push(c);
+                    continue;
+                }
+            };
+            chars.next();
+
+            if !engine.id_to_idx().contains_key(&GraphId(id)) {
+                return Err(id);
+            }
+
+            if !text.is_empty() {
+                parts.push(SynthesizerPart::Text(std::mem::take(&mut text)));
+            }
+            parts.push(SynthesizerPart::Match(
+                Match {
+                    id: GraphId(id),
+                    conversion: Conversion::Nop,
+                    pos_replacer: None,
+                    regex_replacer: None,
+                }
+                .into(),
+            ));
+        }
+
+        if !text.is_empty() {
+            parts.push(SynthesizerPart::Text(text));
+        }
+
+        Ok(Synthesizer {
+            use_titlecase_adjust: true,
+            suppress_misspelled: false,
+            parts,
+        })
+    }
+
+    /// Renders this synthesizer as template. Case conversions and replacements of matches are not
+    /// representable in templates, so they are lost.
+    pub(crate) fn to_template(&self) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                SynthesizerPart::Text(text) => text.clone(),
+                SynthesizerPart::Match(m) => format!("\\{}", m.id.0),
+            })
+            .collect()
+    }
+
     pub fn apply(
         &self,
         graph: &MatchGraph,
         tokenizer: &Tokenizer,
+        options: &ApplyOptions,
         start: GraphId,
-        _end: GraphId,
+        end: GraphId,
     ) -> Option<String> {
         let mut output = Vec::new();
 
@@ -170,31 +247,56 @@ impl Synthesizer {
 
         let suggestion = utils::normalize_whitespace(&output.join(""));
 
+        if self.suppress_misspelled && !tokenizer.all_known(&suggestion) {
+            return None;
+        }
+
         // if the suggestion does not start with a case conversion match, make it title case if:
         // * at sentence start
         // * the replaced text is title case
+        // unless the replaced text or the suggestion is case-locked (e. g. an acronym)
         let make_uppercase = !starts_with_conversion
             && graph.groups()[graph.get_index(start)..]
                 .iter()
                 .find_map(|x| x.tokens(graph.tokens()).next())
                 .map(|first_token| {
-                    (self.use_titlecase_adjust
-                        && first_token
-                            .word
-                            .text
-                            .as_ref()
-                            .chars()
-                            .next()
-                            .expect("token must have at least one char")
-                            .is_uppercase())
-                        || first_token.byte_span.0 == 0
+                    !options.is_case_locked(first_token.word.text.as_ref(), &suggestion)
+                        && ((self.use_titlecase_adjust
+                            && first_token
+                                .word
+                                .text
+                                .as_ref()
+                                .chars()
+                                .next()
+                                .expect("token must have at least one char")
+                                .is_uppercase())
+                            || first_token.span.byte().start == 0)
                 })
                 .unwrap_or(false);
 
-        if make_uppercase {
-            Some(utils::apply_to_first(&suggestion, |x| {
-                x.to_uppercase().collect()
-            }))
+        // all caps are transferred to the whole suggestion e. g. "THERE" -> "THEIR". Short words in all caps
+        // are likely acronyms (e. g. "TV" -> "television"), so they only count if the whole sentence is in all caps
+        let replaced = detokenize(
+            graph
+                .groups()
+                .get(graph.get_index(start)..=graph.get_index(end))
+                .unwrap_or_default()
+                .iter()
+                .flat_map(|x| x.tokens(graph.tokens())),
+        );
+        let make_all_caps = make_uppercase
+            && self.use_titlecase_adjust
+            && utils::is_all_caps(&replaced)
+            && (replaced.chars().count() > 4
+                || graph
+                    .tokens()
+                    .first()
+                    .is_some_and(|x| utils::is_all_caps(x.sentence)));
+
+        if make_all_caps {
+            Some(suggestion.to_uppercase())
+        } else if make_uppercase {
+            Some(utils::uppercase_first_letter(&suggestion))
         } else {
             Some(suggestion)
         }
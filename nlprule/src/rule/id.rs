@@ -122,6 +122,11 @@ impl Group {
     pub fn parent(&self) -> &Category {
         &self.parent
     }
+
+    /// Gets the name of this group without the category e. g. `WAS_BEEN`.
+    pub fn name(&self) -> &str {
+        &self.inner
+    }
 }
 
 impl Index {
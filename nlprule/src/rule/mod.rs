@@ -3,8 +3,10 @@
 use crate::types::*;
 use crate::{
     filter::{Filter, Filterable},
+    rules::{ApplyOptions, RulesOptions},
     tokenizer::{finalize, Tokenizer},
-    utils,
+    utils::{self, regex::Regex},
+    Error,
 };
 use itertools::Itertools;
 use log::{error, info, warn};
@@ -19,12 +21,15 @@ pub mod id;
 
 use engine::Engine;
 
-pub(crate) use engine::composition::MatchGraph;
+pub(crate) use engine::composition::{MatchGraph, SentenceWords};
 pub use grammar::Example;
 
 use self::{
     disambiguation::POSFilter,
-    engine::{composition::GraphId, EngineMatches},
+    engine::{
+        composition::{GraphId, Trigger},
+        EngineMatches,
+    },
     id::Index,
 };
 
@@ -94,7 +99,7 @@ pub struct DisambiguationRule {
 }
 
 #[derive(Default)]
-pub(crate) struct Changes(Vec<Vec<HashSet<(usize, usize)>>>);
+pub(crate) struct Changes(Vec<Vec<HashSet<Span>>>);
 
 impl Changes {
     pub fn is_empty(&self) -> bool {
@@ -113,7 +118,7 @@ impl DisambiguationRule {
             return Changes::default();
         }
 
-        let mut all_byte_spans = Vec::new();
+        let mut all_spans = Vec::new();
 
         for graph in self.engine.get_matches(tokens, self.start, self.end) {
             if let Some(unification) = &self.unification {
@@ -128,21 +133,21 @@ impl DisambiguationRule {
                 }
             }
 
-            let mut byte_spans = Vec::new();
+            let mut spans = Vec::new();
 
             for group_idx in GraphId::range(&self.start, &self.end) {
                 let group = graph.by_id(group_idx);
 
-                let group_byte_spans: HashSet<_> =
-                    group.tokens(graph.tokens()).map(|x| x.byte_span).collect();
+                let group_spans: HashSet<_> =
+                    group.tokens(graph.tokens()).map(|x| x.span).collect();
 
-                byte_spans.push(group_byte_spans);
+                spans.push(group_spans);
             }
 
-            all_byte_spans.push(byte_spans);
+            all_spans.push(spans);
         }
 
-        Changes(all_byte_spans)
+        Changes(all_spans)
     }
 
     pub(crate) fn change<'t>(
@@ -153,17 +158,14 @@ impl DisambiguationRule {
     ) {
         log::info!("applying {}", self.id);
 
-        for byte_spans in changes.0 {
+        for spans in changes.0 {
             let mut groups = Vec::new();
             let mut refs = tokens.iter_mut().collect::<Vec<_>>();
 
-            for group_byte_spans in byte_spans {
+            for group_spans in spans {
                 let mut group = Vec::new();
 
-                while let Some(i) = refs
-                    .iter()
-                    .position(|x| group_byte_spans.contains(&x.byte_span))
-                {
+                while let Some(i) = refs.iter().position(|x| group_spans.contains(&x.span)) {
                     group.push(refs.remove(i));
                 }
 
@@ -208,12 +210,12 @@ impl DisambiguationRule {
                 disambiguation::DisambiguationExample::Changed(change) => {
                     let _before = tokens_before
                         .iter()
-                        .find(|x| x.char_span == change.char_span)
+                        .find(|x| x.span.char() == (change.char_span.0..change.char_span.1))
                         .unwrap();
 
                     let after = tokens_after
                         .iter()
-                        .find(|x| x.char_span == change.char_span)
+                        .find(|x| x.span.char() == (change.char_span.0..change.char_span.1))
                         .unwrap();
 
                     let unordered_tags = after
@@ -266,6 +268,8 @@ impl DisambiguationRule {
 pub struct Suggestions<'a, 't> {
     rule: &'a Rule,
     tokenizer: &'a Tokenizer,
+    options: &'a RulesOptions,
+    apply_options: &'a ApplyOptions,
     matches: EngineMatches<'a, 't>,
     tokens: &'t [Token<'t>],
 }
@@ -280,6 +284,8 @@ impl<'a, 't> Iterator for Suggestions<'a, 't> {
 
         let rule = self.rule;
         let tokenizer = self.tokenizer;
+        let options = self.options;
+        let apply_options = self.apply_options;
         let tokens = self.tokens;
         let (start, end) = (self.rule.start, self.rule.end);
 
@@ -290,13 +296,19 @@ impl<'a, 't> Iterator for Suggestions<'a, 't> {
                 }
             }
 
+            if let Some(filter) = &rule.filter {
+                if !filter.keep(&graph, tokenizer) {
+                    return None;
+                }
+            }
+
             let start_group = graph.by_id(start);
             let end_group = graph.by_id(end);
 
             let replacements: Vec<String> = rule
                 .suggesters
                 .iter()
-                .filter_map(|x| x.apply(&graph, tokenizer, start, end))
+                .filter_map(|x| x.apply(&graph, tokenizer, apply_options, start, end))
                 .collect();
 
             let start = if replacements
@@ -314,7 +326,7 @@ impl<'a, 't> Iterator for Suggestions<'a, 't> {
                     .unwrap_or(0);
 
                 if idx > 0 {
-                    tokens[idx - 1].char_span.1
+                    tokens[idx - 1].span.char().end
                 } else {
                     start_group.char_span.0
                 }
@@ -323,17 +335,21 @@ impl<'a, 't> Iterator for Suggestions<'a, 't> {
             };
             let end = end_group.char_span.1;
 
+            if tokens.iter().any(|token| {
+                token.oversized && token.span.char().start < end && start < token.span.char().end
+            }) {
+                return None;
+            }
+
             // this should never happen, but just return None instead of raising an Error
             // `end` COULD be equal to `start` if the suggestion is to insert text at this position
             if end < start {
                 return None;
             }
-            let text_before: String = tokens[0]
-                .sentence
-                .chars()
-                .skip(start)
-                .take(end - start)
-                .collect();
+            let sentence = tokens[0].sentence;
+            let text_in =
+                |end: usize| -> String { sentence.chars().skip(start).take(end - start).collect() };
+            let text_before = text_in(end);
 
             // fix e. g. "Super , dass"
             let replacements: Vec<String> = replacements
@@ -342,16 +358,36 @@ impl<'a, 't> Iterator for Suggestions<'a, 't> {
                 .map(|x| utils::fix_nospace_chars(&x))
                 .collect();
 
+            // e. g. move an inserted period into closing quotes
+            let (end, replacements) =
+                options.place_inserted_punctuation(sentence, end, &text_before, replacements);
+            let text_before = text_in(end);
+
             if !replacements.is_empty() {
+                let replacement_distance = replacements
+                    .iter()
+                    .map(|x| utils::levenshtein(&text_before, x))
+                    .min()
+                    .unwrap_or(0);
+
+                let message = rule
+                    .message
+                    .apply(&graph, tokenizer, apply_options, rule.start, rule.end)
+                    .expect("Rules must have a message.");
+
                 Some(Suggestion {
-                    message: rule
-                        .message
-                        .apply(&graph, tokenizer, rule.start, rule.end)
-                        .expect("Rules must have a message."),
+                    message: match &rule.filter {
+                        Some(filter) => filter.adjust_message(message, &graph),
+                        None => message,
+                    },
+                    short_message: rule.short.clone().unwrap_or_default(),
+                    url: rule.url.clone(),
+                    severity: rule.severity,
                     source: rule.id.to_string(),
-                    start,
-                    end,
+                    span: Span::from_chars(sentence, start..end),
                     replacements,
+                    segment_type: SegmentType::default(),
+                    replacement_distance,
                 })
             } else {
                 None
@@ -389,8 +425,17 @@ pub struct Rule {
     pub(crate) name: String,
     pub(crate) category_name: String,
     pub(crate) category_type: Option<String>,
+    pub(crate) severity: Severity,
     pub(crate) unification: Option<Unification>,
+    pub(crate) filter: Option<Filter>,
     pub(crate) enabled: bool,
+    /// Whether the message was overridden at runtime, see [Rules::export_config][crate::rules::Rules::export_config].
+    pub(crate) message_overridden: bool,
+    /// Whether the severity was overridden at runtime, see [Rules::export_config][crate::rules::Rules::export_config].
+    pub(crate) severity_overridden: bool,
+    /// Words of which at least one has to occur in a sentence for this rule to match, computed at build time.
+    /// `None` if the rule has to run on every sentence.
+    pub(crate) triggers: Option<Vec<Trigger>>,
 }
 
 impl fmt::Display for Rule {
@@ -430,6 +475,32 @@ impl Rule {
         self.url.as_deref()
     }
 
+    /// Gets the template of the message of this rule. Groups of the match are referred to
+    /// as `\\1`, `\\2` etc.
+    pub fn message(&self) -> String {
+        self.message.to_template()
+    }
+
+    /// Overrides the message of this rule. The template uses the same syntax as the built-in messages
+    /// i. e. `\\1`, `\\2` etc. are replaced with the text of the corresponding group of the match.
+    ///
+    /// # Errors
+    /// - If the template refers to a group which does not exist in this rule.
+    pub fn set_message(&mut self, template: &str) -> Result<(), Error> {
+        self.message = self.parse_message(template)?;
+        self.message_overridden = true;
+        Ok(())
+    }
+
+    pub(crate) fn parse_message(&self, template: &str) -> Result<grammar::Synthesizer, Error> {
+        grammar::Synthesizer::from_template(template, &self.engine).map_err(|group| {
+            Error::InvalidMessage {
+                id: self.id.to_string(),
+                group,
+            }
+        })
+    }
+
     /// Gets the examples associated with this rule.
     pub fn examples(&self) -> &[Example] {
         &self.examples
@@ -450,15 +521,54 @@ impl Rule {
         self.category_type.as_deref()
     }
 
+    /// Gets the severity of the suggestions of this rule. Defaults to the severity of the category type,
+    /// see [Severity::from_category_type].
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Overrides the severity of the suggestions of this rule.
+    pub fn set_severity(&mut self, severity: Severity) {
+        self.severity = severity;
+        self.severity_overridden = true;
+    }
+
+    /// Whether this rule needs chunks i. e. can not match without a chunker in the tokenizer.
+    pub(crate) fn uses_chunks(&self) -> bool {
+        self.engine.uses_chunks()
+    }
+
+    /// All regexes used by this rule.
+    pub(crate) fn regexes(&self) -> Vec<&Regex> {
+        let mut regexes = self.engine.regexes();
+        regexes.extend(self.suggesters.iter().flat_map(|x| x.regexes()));
+        regexes.extend(self.message.regexes());
+        if let Some(filter) = &self.filter {
+            regexes.extend(filter.regexes());
+        }
+        regexes
+    }
+
+    /// Whether this rule can match a sentence with the given words. If not, it does not have to be applied.
+    pub(crate) fn may_match(&self, words: &SentenceWords) -> bool {
+        self.triggers
+            .as_ref()
+            .map_or(true, |triggers| triggers.iter().any(|x| words.contains(x)))
+    }
+
     pub(crate) fn apply<'a, 't>(
         &'a self,
         tokens: &'t [Token<'t>],
         tokenizer: &'a Tokenizer,
+        options: &'a RulesOptions,
+        apply_options: &'a ApplyOptions,
     ) -> Suggestions<'a, 't> {
         Suggestions {
             matches: self.engine.get_matches(tokens, self.start, self.end),
             rule: &self,
             tokenizer,
+            options,
+            apply_options,
             tokens,
         }
     }
@@ -472,7 +582,14 @@ impl Rule {
             // by convention examples are always considered as one sentence even if the sentencizer would split
             let tokens = finalize(tokenizer.disambiguate(tokenizer.tokenize(&test.text())));
             info!("Tokens: {:#?}", tokens);
-            let suggestions: Vec<_> = self.apply(&tokens, tokenizer).collect();
+            let suggestions: Vec<_> = self
+                .apply(
+                    &tokens,
+                    tokenizer,
+                    &RulesOptions::default(),
+                    &ApplyOptions::default(),
+                )
+                .collect();
 
             let pass = if suggestions.len() > 1 {
                 false
@@ -1,19 +1,651 @@
 //! Sets of grammatical error correction rules.
 
 use crate::types::*;
-use crate::utils::parallelism::MaybeParallelRefIterator;
-use crate::{rule::id::Selector, tokenizer::Tokenizer};
-use crate::{rule::Rule, Error};
+use crate::utils::{self, parallelism::MaybeParallelRefIterator, regex::Regex, Direction};
+use crate::{
+    bench::Timing,
+    compression::decompress_reader,
+    rule::id::{Category, Index, Selector},
+    strictness::{self, Degradation, Strictness},
+    tokenizer::Tokenizer,
+};
+use crate::{
+    rule::{grammar, Rule, SentenceWords},
+    Error,
+};
 use fs_err::File;
+use log::warn;
 use serde::{Deserialize, Serialize};
 use std::{
-    io::{BufReader, Read},
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::TryFrom,
+    fmt,
+    io::{BufRead, BufReader, Read, Write},
+    ops::Range,
+    panic::{self, AssertUnwindSafe},
     path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
 };
+use unicase::UniCase;
 
 /// Options for a rule set.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct RulesOptions {}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RulesOptions {
+    /// Rules to skip depending on the type of the segment the rules are applied to.
+    /// By default, rules which assume full sentences (casing and punctuation) are skipped for headings, list items, captions and fragments.
+    pub segment_ignore_ids: BTreeMap<SegmentType, Vec<Selector>>,
+    /// Whether sentence-final punctuation inserted by a suggestion goes inside closing quotes
+    /// (e. g. `said "yes."` in American English) instead of outside (e. g. `sagte „ja“.` in German).
+    /// Punctuation always goes after closing brackets and footnote markers.
+    #[serde(default)]
+    pub punctuation_inside_quotes: bool,
+}
+
+impl Default for RulesOptions {
+    fn default() -> Self {
+        let sentence_categories = || -> Vec<Selector> {
+            vec![
+                Category::new("CASING").into(),
+                Category::new("PUNCTUATION").into(),
+            ]
+        };
+
+        let mut segment_ignore_ids = BTreeMap::new();
+        segment_ignore_ids.insert(SegmentType::Heading, sentence_categories());
+        segment_ignore_ids.insert(SegmentType::ListItem, sentence_categories());
+        segment_ignore_ids.insert(SegmentType::Caption, sentence_categories());
+        segment_ignore_ids.insert(SegmentType::Fragment, sentence_categories());
+
+        RulesOptions {
+            segment_ignore_ids,
+            punctuation_inside_quotes: false,
+        }
+    }
+}
+
+impl RulesOptions {
+    /// Places sentence-final punctuation which a replacement appends to the replaced text (`text_before`, ending
+    /// at char `end` of the `sentence`) according to the typographic conventions of the language.
+    /// Closing quotes, brackets and footnote markers directly after the replaced text are included in the replacement,
+    /// see [RulesOptions::punctuation_inside_quotes]. Returns the new end and replacements.
+    pub(crate) fn place_inserted_punctuation(
+        &self,
+        sentence: &str,
+        end: usize,
+        text_before: &str,
+        replacements: Vec<String>,
+    ) -> (usize, Vec<String>) {
+        let is_closing_quote = |c: char| matches!(c, '"' | '”' | '“' | '‘' | '»' | '«' | '›' | '‹');
+        let is_closing_symbol = |c: char| {
+            is_closing_quote(c)
+                || matches!(c, ')' | ']')
+                // footnote markers
+                || matches!(c, '¹' | '²' | '³' | '⁰'..='⁹' | '†' | '‡')
+        };
+        let inserted_punctuation = |replacement: &str| {
+            let mut rest = replacement.strip_prefix(text_before)?.chars();
+            match (rest.next(), rest.next()) {
+                (Some(c), None) if matches!(c, '.' | '!' | '?') => Some(c),
+                _ => None,
+            }
+        };
+
+        if !replacements
+            .iter()
+            .any(|x| inserted_punctuation(x).is_some())
+        {
+            return (end, replacements);
+        }
+
+        let after: String = sentence
+            .chars()
+            .skip(end)
+            .take_while(|c| is_closing_symbol(*c))
+            .collect();
+        let region: Vec<char> = text_before.chars().chain(after.chars()).collect();
+        let n_trailing = region
+            .iter()
+            .rev()
+            .take_while(|c| is_closing_symbol(**c))
+            .count();
+        let (core, trailing) = region.split_at(region.len() - n_trailing);
+
+        let position = if self.punctuation_inside_quotes {
+            trailing
+                .iter()
+                .position(|c| is_closing_quote(*c))
+                .unwrap_or(trailing.len())
+        } else {
+            trailing.len()
+        };
+
+        let replacements = replacements
+            .into_iter()
+            .map(|replacement| match inserted_punctuation(&replacement) {
+                Some(punctuation) => core
+                    .iter()
+                    .chain(&trailing[..position])
+                    .chain(std::iter::once(&punctuation))
+                    .chain(&trailing[position..])
+                    .collect(),
+                None => replacement + &after,
+            })
+            .collect();
+
+        (end + after.chars().count(), replacements)
+    }
+}
+
+/// Options for one application of a rule set. See [Rules::apply_with_options].
+#[derive(Debug, Clone, Default)]
+pub struct ApplyOptions {
+    /// Whether to also run disabled rules and collect suggestions which are not returned as active suggestions
+    /// together with the reason why they were suppressed.
+    /// Disables some short-circuits, so applying the rules is slower if this is set.
+    pub collect_suppressed: bool,
+    /// The type of the segment the rules are applied to. See [RulesOptions::segment_ignore_ids].
+    pub segment_type: SegmentType,
+    /// If set, suggestions whose [replacement distance][Suggestion::replacement_distance] is larger than this
+    /// are suppressed. Guards against replacements which mangle the text e. g. due to bugs in suggestion templates.
+    pub max_replacement_distance: Option<usize>,
+    /// Rules which are not checked against [ApplyOptions::max_replacement_distance]
+    /// e. g. word order rules which legitimately rewrite large parts of the text.
+    pub replacement_distance_exceptions: Vec<Selector>,
+    /// Whether to produce a [CheckReport] describing what was actually checked. See [ApplyOutput::report].
+    pub report: bool,
+    /// Suggestions whose flagged text matches any of these patterns are suppressed e. g. ticket IDs like "ABC-1234".
+    /// Checked before overlaps are resolved, so an ignored suggestion does not hide other suggestions.
+    pub ignore_text_patterns: Vec<TextPattern>,
+    /// How to handle degraded behavior. If not set, the process-wide [strictness::strictness] is used.
+    /// Only the `try_` methods e. g. [Rules::try_apply_with_options] fail in strict mode, the other methods warn instead.
+    pub strictness: Option<Strictness>,
+    /// Called with each returned suggestion as soon as the suggestions of a sentence are final
+    /// i. e. after overlaps are resolved. Suppressed suggestions are not observed.
+    /// Useful to e. g. collect metrics in long batch runs without keeping all suggestions around.
+    pub on_suggestion: Option<SuggestionObserver>,
+    /// Whether to measure the time spent in the stages of the pipeline. See [ApplyOutput::timing].
+    pub timing: bool,
+    /// The maximum number of sentences to check per document. Sentences are checked in order until the budget
+    /// is exhausted, the rest of the document is not checked. See [CheckReport::truncated_at].
+    /// Only applies to methods which check a text e. g. [Rules::suggest_with_options] or [Rules::try_apply_stream_with_options].
+    pub max_sentences: Option<usize>,
+    /// The maximum number of chars to check per document, like [ApplyOptions::max_sentences].
+    /// Checking never stops within a sentence: a sentence is only checked if it fits into the remaining budget completely.
+    pub max_chars: Option<usize>,
+    /// Whether to run every rule on every sentence. By default, rules are skipped if none of the words they
+    /// require (e. g. "could" for a rule matching "could of") occur in the sentence.
+    /// Does not change the suggestions, only useful for debugging.
+    pub disable_prefilter: bool,
+    /// If set, suggestions of rules with a lower [severity][Rule::severity] are suppressed e. g. to only show
+    /// [errors][Severity::Error] and not stylistic [hints][Severity::Hint].
+    pub min_severity: Option<Severity>,
+    /// Which of several overlapping suggestions to return. The others are suppressed with [SuppressionReason::Overlap].
+    pub conflict: Conflict,
+    /// Case-locked terms e. g. "iPhone" or "iOS". If the replaced text or a replacement starts with one of
+    /// these terms (compared case-insensitively), the replacement keeps its own casing instead of being
+    /// adjusted to the casing of the replaced text.
+    pub case_exceptions: Vec<String>,
+    /// Whether to treat all-uppercase words with at most four characters (e. g. "IOS") as acronyms
+    /// and keep the casing of their replacements.
+    pub lock_acronym_case: bool,
+}
+
+impl ApplyOptions {
+    /// Calls the observer (if any) with each of the `suggestions`. A panic in the observer is caught
+    /// and returned as [Error::ObserverPanicked].
+    fn observe(&self, suggestions: &[Suggestion]) -> Result<(), Error> {
+        let observer = match &self.on_suggestion {
+            Some(observer) => observer,
+            None => return Ok(()),
+        };
+
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            suggestions.iter().for_each(|x| (observer.0)(x))
+        }))
+        .map_err(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|x| x.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+
+            Error::ObserverPanicked(message)
+        })
+    }
+
+    fn strictness(&self) -> Strictness {
+        self.strictness.unwrap_or_else(strictness::strictness)
+    }
+
+    /// Whether the casing of `replacement` must be kept as-is when it replaces the word `original`.
+    pub(crate) fn is_case_locked(&self, original: &str, replacement: &str) -> bool {
+        let is_acronym = |word: &str| {
+            let n_chars = word.chars().count();
+
+            n_chars > 1
+                && n_chars <= 4
+                && word.chars().any(char::is_alphabetic)
+                && crate::utils::is_uppercase(word)
+        };
+        let first_word = |text: &str| text.split_whitespace().next().unwrap_or("").to_lowercase();
+
+        if self.lock_acronym_case && is_acronym(original) {
+            return true;
+        }
+
+        let original = first_word(original);
+        let replacement = first_word(replacement);
+
+        self.case_exceptions.iter().any(|term| {
+            let term = term.to_lowercase();
+            term == original || term == replacement
+        })
+    }
+
+    fn is_below_min_severity(&self, rule: &Rule) -> bool {
+        self.min_severity.is_some_and(|min| rule.severity() < min)
+    }
+
+    fn ignores_text(&self, text: &str) -> bool {
+        self.ignore_text_patterns.iter().any(|x| x.is_match(text))
+    }
+
+    fn exceeds_replacement_distance(&self, rule: &Rule, suggestion: &Suggestion) -> bool {
+        match self.max_replacement_distance {
+            Some(max) => {
+                suggestion.replacement_distance > max
+                    && !self
+                        .replacement_distance_exceptions
+                        .iter()
+                        .any(|x| x.is_match(rule.id()))
+            }
+            None => false,
+        }
+    }
+}
+
+/// The remaining document budget of [ApplyOptions::max_sentences] and [ApplyOptions::max_chars].
+struct DocumentBudget {
+    sentences: Option<usize>,
+    chars: Option<usize>,
+}
+
+impl DocumentBudget {
+    fn new(options: &ApplyOptions) -> Self {
+        DocumentBudget {
+            sentences: options.max_sentences,
+            chars: options.max_chars,
+        }
+    }
+
+    /// Takes a sentence with `n_chars` chars from the budget.
+    /// Returns false and leaves the budget unchanged if the sentence does not fit.
+    fn take(&mut self, n_chars: usize) -> bool {
+        if self.sentences == Some(0) || matches!(self.chars, Some(chars) if chars < n_chars) {
+            return false;
+        }
+
+        if let Some(sentences) = self.sentences.as_mut() {
+            *sentences -= 1;
+        }
+        if let Some(chars) = self.chars.as_mut() {
+            *chars -= n_chars;
+        }
+        true
+    }
+}
+
+/// How to resolve overlapping suggestions, of which only one can be applied to a text.
+/// See [ApplyOptions::conflict] and [CorrectOptions::conflict].
+///
+/// The priority of a suggestion of [Rules] is the position of its rule in the rule set, later rules take precedence.
+/// [apply_suggestions_with_options] gives suggestions earlier in the slice precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Conflict {
+    /// Keep the suggestion which starts first. Ties are broken by priority.
+    #[default]
+    Earliest,
+    /// Keep the suggestion which spans the most chars. Ties are broken by position, then by priority.
+    Longest,
+    /// Keep the suggestion with the highest priority. Ties are broken by position.
+    Priority,
+}
+
+impl Conflict {
+    /// Splits the `items` into the ones to keep and the ones which overlap a kept item. Items with a higher `priority`
+    /// take precedence. Kept items are returned in the order they have to be applied to the text.
+    fn resolve<T>(
+        self,
+        mut items: Vec<T>,
+        span: impl Fn(&T) -> &Span,
+        priority: impl Fn(&T) -> usize,
+    ) -> (Vec<T>, Vec<T>) {
+        let position = |x: &T| (span(x).char().start, Reverse(priority(x)));
+
+        match self {
+            Conflict::Earliest => items.sort_by_key(|x| position(x)),
+            Conflict::Longest => {
+                items.sort_by_key(|x| (Reverse(span(x).char().len()), position(x)))
+            }
+            Conflict::Priority => {
+                items.sort_by_key(|x| (Reverse(priority(x)), span(x).char().start))
+            }
+        }
+
+        let mut kept: Vec<T> = Vec::new();
+        let mut overlapping = Vec::new();
+        for item in items {
+            if kept.iter().any(|x| span(x).intersects(span(&item))) {
+                overlapping.push(item);
+            } else {
+                kept.push(item);
+            }
+        }
+
+        // insertions before replacements at the same position
+        kept.sort_by_key(|x| (span(x).char().start, span(x).char().end));
+        (kept, overlapping)
+    }
+}
+
+impl std::str::FromStr for Conflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "earliest" => Conflict::Earliest,
+            "longest" => Conflict::Longest,
+            "priority" => Conflict::Priority,
+            x => return Err(format!("unknown conflict policy '{}'", x)),
+        })
+    }
+}
+
+/// A callback which observes suggestions as they are produced. See [ApplyOptions::on_suggestion].
+#[derive(Clone)]
+pub struct SuggestionObserver(Arc<dyn Fn(&Suggestion) + Send + Sync>);
+
+impl SuggestionObserver {
+    /// Creates a new observer from the given callback.
+    pub fn new<F: Fn(&Suggestion) + Send + Sync + 'static>(f: F) -> Self {
+        SuggestionObserver(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for SuggestionObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SuggestionObserver").finish()
+    }
+}
+
+/// A regular expression which is matched against the flagged text of suggestions.
+/// Matches anywhere in the text unless it is anchored. See [ApplyOptions::ignore_text_patterns].
+#[derive(Debug, Clone)]
+pub struct TextPattern(Arc<Regex>);
+
+impl TextPattern {
+    /// Compiles a pattern. Fails if it is not a valid regular expression.
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        Self::compile_all([pattern]).map(|mut x| x.remove(0))
+    }
+
+    /// Compiles multiple patterns. The error contains the index of the first invalid pattern.
+    pub fn compile_all<S: AsRef<str>>(
+        patterns: impl IntoIterator<Item = S>,
+    ) -> Result<Vec<Self>, Error> {
+        patterns
+            .into_iter()
+            .enumerate()
+            .map(|(index, pattern)| {
+                let regex = Regex::new(pattern.as_ref().to_owned());
+                regex.try_compile().map_err(|error| Error::InvalidPattern {
+                    index,
+                    message: error.to_string(),
+                })?;
+
+                Ok(TextPattern(Arc::new(regex)))
+            })
+            .collect()
+    }
+
+    /// Whether the pattern matches anywhere in the text.
+    pub fn is_match(&self, text: &str) -> bool {
+        // `Regex::is_match` matches the whole text with some backends
+        self.0.find_iter(text).next().is_some()
+    }
+}
+
+/// The reason why a suggestion was suppressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SuppressionReason {
+    /// The rule which produced the suggestion is disabled.
+    Disabled,
+    /// The suggestion overlaps with a suggestion from a rule with higher priority.
+    Overlap,
+    /// The rule which produced the suggestion is ignored for the type of the segment.
+    SegmentType,
+    /// The replacements are too different from the replaced text. See [ApplyOptions::max_replacement_distance].
+    ReplacementDistance,
+    /// The flagged text matches a pattern in [ApplyOptions::ignore_text_patterns].
+    IgnoredText,
+    /// The rule which produced the suggestion is less severe than [ApplyOptions::min_severity].
+    Severity,
+}
+
+impl fmt::Display for SuppressionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            SuppressionReason::Disabled => "disabled",
+            SuppressionReason::Overlap => "overlap",
+            SuppressionReason::SegmentType => "segment_type",
+            SuppressionReason::ReplacementDistance => "replacement_distance",
+            SuppressionReason::IgnoredText => "ignored_text",
+            SuppressionReason::Severity => "severity",
+        };
+
+        write!(f, "{}", reason)
+    }
+}
+
+/// What happened to a sentence when the rules were applied to it. See [CheckReport].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "disposition", rename_all = "snake_case")]
+pub enum SentenceDisposition {
+    /// All active rules were checked.
+    Checked,
+    /// The sentence contains oversized tokens (see [TokenizerOptions](crate::tokenizer::TokenizerOptions)).
+    /// Rules can not match the text of these tokens and suggestions overlapping them are dropped.
+    Truncated {
+        /// The number of oversized tokens in the sentence.
+        oversized_tokens: usize,
+    },
+    /// No rule was checked.
+    Skipped {
+        /// Why the sentence was skipped.
+        reason: SkipReason,
+    },
+}
+
+/// The reason why a sentence was not checked at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// All rules are disabled or ignored for the segment type.
+    NoActiveRules,
+}
+
+/// The disposition of one sentence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SentenceReport {
+    /// The char span of the sentence in the checked text.
+    pub char_span: (usize, usize),
+    /// What happened to the sentence.
+    #[serde(flatten)]
+    pub disposition: SentenceDisposition,
+}
+
+/// Describes what was actually checked, so an empty list of suggestions can be told apart from
+/// text which was not (fully) checked. See [ApplyOptions::report].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckReport {
+    /// The disposition of each checked sentence in order.
+    pub sentences: Vec<SentenceReport>,
+    /// IDs of active rules which need chunks but the tokenizer has no chunker e. g. because it was dropped by
+    /// [Tokenizer::apply_memory_budget]. These rules can not match.
+    pub rules_missing_chunker: Vec<String>,
+    /// The number of suggestions suppressed by [ApplyOptions::ignore_text_patterns].
+    #[serde(default)]
+    pub ignored_text: usize,
+    /// The number of suggestions which were dropped because their span is malformed i. e. it ends before it starts
+    /// or extends past the end of the sentence. Always a bug in the rule, the rule ID is logged.
+    #[serde(default)]
+    pub malformed_suggestions: usize,
+    /// Where checking stopped because the document budget was exhausted (see [ApplyOptions::max_sentences]).
+    /// Everything from this point to the end of the text was not checked.
+    #[serde(default)]
+    pub truncated_at: Option<TruncationPoint>,
+}
+
+/// The start of the first sentence which was not checked because the document budget was exhausted.
+/// See [CheckReport::truncated_at].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TruncationPoint {
+    /// The char offset in the text.
+    pub char_offset: usize,
+    /// The byte offset in the text.
+    pub byte_offset: usize,
+    /// The index of the sentence i. e. the number of sentences in [CheckReport::sentences] before it.
+    pub sentence_index: usize,
+}
+
+impl CheckReport {
+    /// Whether every sentence was checked with all active rules i. e. whether no suggestions means clean text.
+    pub fn is_complete(&self) -> bool {
+        self.rules_missing_chunker.is_empty()
+            && self.truncated_at.is_none()
+            && self
+                .sentences
+                .iter()
+                .all(|x| x.disposition == SentenceDisposition::Checked)
+    }
+}
+
+/// The output of [Rules::apply_with_options].
+#[derive(Debug, Clone, Default)]
+pub struct ApplyOutput {
+    /// The suggestions which would be returned by [Rules::apply].
+    pub active: Vec<Suggestion>,
+    /// Suggestions which were suppressed together with the reason why they were suppressed.
+    /// Only populated if [ApplyOptions::collect_suppressed] is set.
+    pub suppressed: Vec<(Suggestion, SuppressionReason)>,
+    /// What was actually checked. Only set if [ApplyOptions::report] is set.
+    pub report: Option<CheckReport>,
+    /// The time spent in the stages of the pipeline. Only set if [ApplyOptions::timing] is set.
+    /// Tokenization and disambiguation are only measured if the output was computed from text e. g. by [Rules::suggest_with_options].
+    pub timing: Option<Timing>,
+}
+
+impl ApplyOutput {
+    /// Appends the output of a sentence which starts at `char_offset` and `byte_offset` in the text.
+    pub fn append(&mut self, other: ApplyOutput, char_offset: usize, byte_offset: usize) {
+        let shift = |mut suggestion: Suggestion| {
+            suggestion.shift(char_offset, byte_offset);
+            suggestion
+        };
+
+        self.active.extend(other.active.into_iter().map(shift));
+        self.suppressed.extend(
+            other
+                .suppressed
+                .into_iter()
+                .map(|(suggestion, reason)| (shift(suggestion), reason)),
+        );
+
+        if let Some(other) = other.report {
+            let report = self.report.get_or_insert_with(CheckReport::default);
+
+            // the same for every sentence
+            if !other.sentences.is_empty() {
+                report.rules_missing_chunker = other.rules_missing_chunker;
+            }
+            report
+                .sentences
+                .extend(other.sentences.into_iter().map(|mut sentence| {
+                    sentence.char_span.0 += char_offset;
+                    sentence.char_span.1 += char_offset;
+                    sentence
+                }));
+            report.ignored_text += other.ignored_text;
+            report.malformed_suggestions += other.malformed_suggestions;
+
+            if let Some(truncated_at) = other.truncated_at {
+                report.truncated_at = Some(TruncationPoint {
+                    char_offset: truncated_at.char_offset + char_offset,
+                    byte_offset: truncated_at.byte_offset + byte_offset,
+                    sentence_index: report.sentences.len(),
+                });
+            }
+        }
+
+        if let Some(other) = other.timing {
+            *self.timing.get_or_insert_with(Timing::default) += other;
+        }
+    }
+}
+
+/// The runtime configuration of a rule set i. e. the state which can be changed after loading the binary.
+/// Can be persisted and applied to another rule set loaded from the same binary.
+/// See [Rules::export_config] and [Rules::apply_config].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RulesConfig {
+    /// The version of the configuration format. See [RulesConfig::VERSION].
+    pub version: u32,
+    /// IDs of the rules which are [enabled][Rule::enabled]. Whether a rule is used also depends on the toggles below,
+    /// see [Rules::is_enabled].
+    pub enabled: Vec<String>,
+    /// IDs of the rules which are disabled.
+    pub disabled: Vec<String>,
+    /// Categories disabled with [Rules::disable_category].
+    #[serde(default)]
+    pub disabled_categories: Vec<String>,
+    /// IDs of the rules enabled with [Rules::enable]. These are used even if their category is disabled.
+    #[serde(default)]
+    pub explicitly_enabled: Vec<String>,
+    /// Overridden message templates keyed by rule ID. See [Rules::set_messages].
+    #[serde(default)]
+    pub messages: BTreeMap<String, String>,
+    /// Overridden severities keyed by rule ID. See [Rules::set_severities].
+    #[serde(default)]
+    pub severities: BTreeMap<String, Severity>,
+    /// The options of the rule set.
+    pub options: RulesOptions,
+    /// See [ApplyOptions::case_exceptions]. These apply per call, so they are only exported with
+    /// [Rules::export_config_with_options] and used through [RulesConfig::apply_options].
+    #[serde(default)]
+    pub case_exceptions: Vec<String>,
+    /// See [ApplyOptions::lock_acronym_case].
+    #[serde(default)]
+    pub lock_acronym_case: bool,
+}
+
+impl RulesConfig {
+    /// The current version of the configuration format.
+    pub const VERSION: u32 = 1;
+
+    /// Gets the options to apply the configured rule set with i. e. the default options with the case exceptions of this configuration.
+    pub fn apply_options(&self) -> ApplyOptions {
+        ApplyOptions {
+            case_exceptions: self.case_exceptions.clone(),
+            lock_acronym_case: self.lock_acronym_case,
+            ..ApplyOptions::default()
+        }
+    }
+}
 
 /// Language-dependent options for a rule set.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +658,17 @@ pub(crate) struct RulesLangOptions {
     /// Grammar Rule selectors to ignore in this set.
     #[serde(default)]
     pub ignore_ids: Vec<Selector>,
+    /// Options for the word repetition rule. The rule is not created if this is not set.
+    #[cfg(feature = "compile")]
+    #[serde(default)]
+    pub word_repeat: Option<WordRepeatLangOptions>,
+    /// Options for the typographic quotes rules. The rules are not created if this is not set.
+    #[cfg(feature = "compile")]
+    #[serde(default)]
+    pub typographic_quotes: Option<TypographicQuotesLangOptions>,
+    /// See [RulesOptions::punctuation_inside_quotes].
+    #[serde(default)]
+    pub punctuation_inside_quotes: bool,
 }
 
 impl Default for RulesLangOptions {
@@ -34,15 +677,77 @@ impl Default for RulesLangOptions {
             allow_errors: true,
             ids: Vec::new(),
             ignore_ids: Vec::new(),
+            #[cfg(feature = "compile")]
+            word_repeat: None,
+            #[cfg(feature = "compile")]
+            typographic_quotes: None,
+            punctuation_inside_quotes: false,
         }
     }
 }
 
+/// Language-dependent options for the word repetition rule e. g. "the the".
+/// This rule is implemented in Java in LanguageTool, so it is not part of the grammar XML.
+#[cfg(feature = "compile")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct WordRepeatLangOptions {
+    /// The group ID of the rule in the `MISC` category.
+    pub id: String,
+    /// A human-readable name of the rule.
+    pub name: String,
+    /// The message of suggestions from this rule.
+    pub message: String,
+    /// Punctuation which may be between the repeated words e. g. "," in "the, the".
+    #[serde(default)]
+    pub skip: Vec<String>,
+    /// Words which are legitimately repeated e. g. "had" in "had had".
+    #[serde(default)]
+    pub exceptions: Vec<String>,
+}
+
+/// Language-dependent options for the rules which replace straight quotes (`"` and `'`) with typographic quotes.
+/// Pairing quotes needs state across the sentence, so these rules are built in instead of being part of the grammar XML.
+/// There is one rule for the opening and one for the closing quote of each nesting level (in this order),
+/// all in the group `MISC/<id>`.
+#[cfg(feature = "compile")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TypographicQuotesLangOptions {
+    /// The group ID of the rules in the `MISC` category.
+    pub id: String,
+    /// A human-readable name of the rules.
+    pub name: String,
+    /// The message of suggestions from these rules.
+    pub message: String,
+    /// The opening and closing quote for each nesting level e. g. `“…”` and `‘…’` in English.
+    /// Levels deeper than this repeat the quotes from the outermost level.
+    pub quotes: Vec<(String, String)>,
+}
+
+/// Toggles which apply on top of the state of each rule. See [Rules::disable_category].
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub(crate) struct Toggles {
+    disabled_categories: DefaultHashSet<Category>,
+    // rules enabled with `Rules::enable`, these win over a disabled category
+    explicitly_enabled: DefaultHashSet<Index>,
+}
+
+impl Toggles {
+    fn is_enabled(&self, rule: &Rule) -> bool {
+        rule.enabled()
+            && (!self
+                .disabled_categories
+                .contains(rule.id().parent().parent())
+                || self.explicitly_enabled.contains(rule.id()))
+    }
+}
+
 /// A set of grammatical error correction rules.
 #[derive(Serialize, Deserialize, Default)]
 pub struct Rules {
     pub(crate) rules: Vec<Rule>,
     pub(crate) options: RulesOptions,
+    pub(crate) info: ModelInfo,
+    pub(crate) toggles: Toggles,
 }
 
 impl Rules {
@@ -51,14 +756,14 @@ impl Rules {
     /// # Errors
     /// - If the file can not be opened.
     /// - If the file content can not be deserialized to a rules set.
+    /// - If the binary was built with an incompatible version of nlprule.
     pub fn new<P: AsRef<Path>>(p: P) -> Result<Self, Error> {
         Rules::new_with_options(p, RulesOptions::default())
     }
 
     /// Creates a new rule set with options. See [new][Rules::new].
     pub fn new_with_options<P: AsRef<Path>>(p: P, options: RulesOptions) -> Result<Self, Error> {
-        let reader = BufReader::new(File::open(p.as_ref())?);
-        let mut rules: Rules = bincode::deserialize_from(reader)?;
+        let mut rules = Rules::from_reader(File::open(p.as_ref())?)?;
 
         rules.options = options;
         Ok(rules)
@@ -74,9 +779,62 @@ impl Rules {
         &mut self.options
     }
 
-    /// Creates a new rules set from a reader.
+    /// Gets the version and provenance of the binary this rule set was loaded from.
+    pub fn info(&self) -> &ModelInfo {
+        &self.info
+    }
+
+    /// Estimates the memory used by the rules in this set. See [MemoryFootprint].
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        let (enabled, disabled): (Vec<&Rule>, Vec<&Rule>) =
+            self.rules.iter().partition(|rule| self.is_enabled(rule));
+        let mut footprint = MemoryFootprint::default();
+
+        footprint.add("rules", &enabled);
+        footprint.add("disabled_rules", &disabled);
+        footprint
+    }
+
+    /// Removes disabled rules from memory if the estimated [memory footprint][Rules::memory_footprint] is larger than `bytes`.
+    /// Removed rules can not be enabled again, so disable all rules which are not needed (e. g. whole categories) before
+    /// calling this.
+    ///
+    /// # Errors
+    /// - If the enabled rules do not fit into the budget.
+    pub fn apply_memory_budget(&mut self, bytes: usize) -> Result<MemoryFootprint, Error> {
+        if self.memory_footprint().total() > bytes {
+            let toggles = &self.toggles;
+            self.rules.retain(|rule| toggles.is_enabled(rule));
+        }
+
+        let footprint = self.memory_footprint();
+        if footprint.total() > bytes {
+            return Err(Error::MemoryBudgetExceeded {
+                budget: bytes,
+                required: footprint.total(),
+            });
+        }
+
+        Ok(footprint)
+    }
+
+    /// Creates a new rules set from a reader. See [new][Rules::new].
+    /// The binary may be compressed, see the [compression][crate::compression] module.
     pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
-        Ok(bincode::deserialize_from(reader)?)
+        let mut reader = decompress_reader(BufReader::new(reader))?;
+        BinaryHeader::read(&mut reader, BinaryKind::Rules)?;
+
+        let rules: Rules = bincode::deserialize_from(reader)?;
+        rules.info.check_compatible()?;
+
+        Ok(rules)
+    }
+
+    /// Writes the rules as binary (uncompressed) which can be loaded with [from_reader][Rules::from_reader].
+    pub fn to_writer<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        BinaryHeader::new(BinaryKind::Rules, &self.info.language).write(&mut writer)?;
+        bincode::serialize_into(writer, self)?;
+        Ok(())
     }
 
     /// All rules ordered by priority.
@@ -89,6 +847,15 @@ impl Rules {
         &mut self.rules
     }
 
+    /// Compiles all regexes of the rules now. Regexes are otherwise compiled on first use, so loading
+    /// the rules is fast but the first checked texts are slow. Useful for e. g. latency-sensitive servers.
+    /// Does not change the suggestions.
+    pub fn warm_up(&self) {
+        self.rules.maybe_par_iter().for_each(|rule| {
+            rule.regexes().into_iter().for_each(Regex::compile);
+        });
+    }
+
     /// Returns an iterator over all rules matching the selector.
     pub fn select<'a>(&'a self, selector: &'a Selector) -> RulesIter<'a> {
         RulesIter {
@@ -105,132 +872,3164 @@ impl Rules {
         }
     }
 
-    /// Compute the suggestions for the given tokens by checking all rules.
-    pub fn apply(&self, tokens: &[Token], tokenizer: &Tokenizer) -> Vec<Suggestion> {
-        if tokens.is_empty() {
-            return Vec::new();
+    /// Exports the runtime configuration of this rule set. See [RulesConfig].
+    pub fn export_config(&self) -> RulesConfig {
+        self.export_config_with_options(&ApplyOptions::default())
+    }
+
+    /// Exports the runtime configuration of this rule set together with the case exceptions of the options
+    /// the rule set is applied with. See [RulesConfig::apply_options].
+    pub fn export_config_with_options(&self, options: &ApplyOptions) -> RulesConfig {
+        let (enabled, disabled): (Vec<_>, Vec<_>) =
+            self.rules.iter().partition(|rule| rule.enabled());
+        let ids = |rules: Vec<&Rule>| rules.iter().map(|rule| rule.id().to_string()).collect();
+        let sorted = |mut ids: Vec<String>| {
+            ids.sort_unstable();
+            ids
+        };
+
+        RulesConfig {
+            version: RulesConfig::VERSION,
+            enabled: ids(enabled),
+            disabled: ids(disabled),
+            disabled_categories: sorted(
+                self.toggles
+                    .disabled_categories
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            ),
+            explicitly_enabled: sorted(
+                self.toggles
+                    .explicitly_enabled
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            ),
+            messages: self
+                .rules
+                .iter()
+                .filter(|rule| rule.message_overridden)
+                .map(|rule| (rule.id().to_string(), rule.message()))
+                .collect(),
+            severities: self
+                .rules
+                .iter()
+                .filter(|rule| rule.severity_overridden)
+                .map(|rule| (rule.id().to_string(), rule.severity()))
+                .collect(),
+            options: self.options.clone(),
+            case_exceptions: options.case_exceptions.clone(),
+            lock_acronym_case: options.lock_acronym_case,
+        }
+    }
+
+    /// Applies a runtime configuration to this rule set. Rules which are not mentioned in the configuration
+    /// keep their current state. The toggles and overrides of the configuration are added to the current ones
+    /// as if they were set with [Rules::disable_category], [Rules::enable], [Rules::set_messages] and [Rules::set_severities].
+    ///
+    /// # Errors
+    /// - If the version of the configuration is not supported.
+    /// - If the configuration contains rule IDs or categories which do not exist in this rule set.
+    /// - If an overridden message refers to a group which does not exist in its rule.
+    ///
+    /// In all cases the rule set is left unchanged.
+    pub fn apply_config(&mut self, config: &RulesConfig) -> Result<(), Error> {
+        if config.version != RulesConfig::VERSION {
+            return Err(Error::UnsupportedConfigVersion(config.version));
         }
 
-        let mut output: Vec<(usize, Suggestion)> = self
+        let states: HashMap<&str, bool> = config
+            .enabled
+            .iter()
+            .map(|id| (id.as_str(), true))
+            .chain(config.disabled.iter().map(|id| (id.as_str(), false)))
+            .collect();
+        let indices: HashMap<String, usize> = self
             .rules
-            .maybe_par_iter()
+            .iter()
             .enumerate()
-            .filter(|(_, rule)| rule.enabled())
-            .map(|(i, rule)| {
-                let mut output = Vec::new();
-
-                for suggestion in rule.apply(tokens, tokenizer) {
-                    output.push((i, suggestion));
-                }
+            .map(|(i, rule)| (rule.id().to_string(), i))
+            .collect();
 
-                output
-            })
-            .flatten()
+        let mut unknown: Vec<String> = states
+            .keys()
+            .copied()
+            .chain(config.explicitly_enabled.iter().map(String::as_str))
+            .chain(config.severities.keys().map(String::as_str))
+            .filter(|id| !indices.contains_key(*id))
+            .map(ToOwned::to_owned)
             .collect();
+        if !unknown.is_empty() {
+            unknown.sort_unstable();
+            unknown.dedup();
+            return Err(Error::UnknownRuleIds(unknown));
+        }
 
-        output.sort_by(|(ia, a), (ib, b)| a.start.cmp(&b.start).then_with(|| ib.cmp(ia)));
+        let categories = config
+            .disabled_categories
+            .iter()
+            .map(|category| self.known_category(category))
+            .collect::<Result<Vec<_>, _>>()?;
+        let messages = self.parse_messages(&config.messages)?;
 
-        let mut mask = vec![false; tokens[0].sentence.chars().count()];
+        for (id, enabled) in states {
+            let rule = &mut self.rules[indices[id]];
 
-        output
-            .into_iter()
-            .filter_map(|(_, suggestion)| {
-                if mask[suggestion.start..suggestion.end].iter().all(|x| !x) {
-                    mask[suggestion.start..suggestion.end]
-                        .iter_mut()
-                        .for_each(|x| *x = true);
-                    Some(suggestion)
-                } else {
-                    None
-                }
-            })
-            .collect()
+            if enabled {
+                rule.enable();
+            } else {
+                rule.disable();
+            }
+        }
+        self.toggles.disabled_categories.extend(categories);
+        for id in &config.explicitly_enabled {
+            let id = self.rules[indices[id]].id().clone();
+            self.toggles.explicitly_enabled.insert(id);
+        }
+        self.set_parsed_messages(messages);
+        for (id, severity) in &config.severities {
+            self.rules[indices[id]].set_severity(*severity);
+        }
+        self.options = config.options.clone();
+
+        Ok(())
     }
 
-    /// Compute the suggestions for a text by checking all rules.
-    pub fn suggest(&self, text: &str, tokenizer: &Tokenizer) -> Vec<Suggestion> {
-        if text.is_empty() {
-            return Vec::new();
-        }
+    /// Enables all rules matching any of the IDs. An ID is a [Selector] (e. g. `grammar/was_been` or `grammar`)
+    /// or the name of a rule group without its category (e. g. `WAS_BEEN`). Matching is case-insensitive.
+    ///
+    /// # Errors
+    /// - If an ID does not match any rule. The error lists close matches. In that case the rule set is left unchanged.
+    pub fn enable<S: AsRef<str>>(&mut self, ids: &[S]) -> Result<(), Error> {
+        self.set_enabled(ids, true)
+    }
 
-        let mut suggestions = Vec::new();
-        let mut char_offset = 0;
+    /// Disables all rules matching any of the IDs. Disabled rules are skipped entirely when checking.
+    /// See [Rules::enable] for the format of the IDs.
+    ///
+    /// # Errors
+    /// - If an ID does not match any rule. The error lists close matches. In that case the rule set is left unchanged.
+    pub fn disable<S: AsRef<str>>(&mut self, ids: &[S]) -> Result<(), Error> {
+        self.set_enabled(ids, false)
+    }
 
-        // get suggestions sentence by sentence
-        for tokens in tokenizer.pipe(text) {
-            if tokens.is_empty() {
-                continue;
+    fn set_enabled<S: AsRef<str>>(&mut self, ids: &[S], enabled: bool) -> Result<(), Error> {
+        let mut indices = Vec::new();
+
+        for id in ids {
+            let id = id.as_ref();
+            let matches = self.matching_indices(id);
+
+            if matches.is_empty() {
+                return Err(Error::NoMatchingRules {
+                    id: id.to_owned(),
+                    close_matches: self.close_matches(id),
+                });
             }
+            indices.extend(matches);
+        }
 
-            suggestions.extend(
-                self.apply(&tokens, tokenizer)
-                    .into_iter()
-                    .map(|mut suggestion| {
-                        suggestion.rshift(char_offset);
-                        suggestion
-                    }),
-            );
+        for i in indices {
+            let rule = &mut self.rules[i];
 
-            char_offset += tokens[0].sentence.chars().count();
+            if enabled {
+                rule.enable();
+                self.toggles.explicitly_enabled.insert(rule.id().clone());
+            } else {
+                rule.disable();
+                self.toggles.explicitly_enabled.remove(rule.id());
+            }
         }
 
-        suggestions
+        Ok(())
     }
 
-    /// Correct a text by first tokenizing, then finding all suggestions and choosing the first replacement of each suggestion.
-    pub fn correct(&self, text: &str, tokenizer: &Tokenizer) -> String {
-        let suggestions = self.suggest(text, tokenizer);
-        apply_suggestions(text, &suggestions)
+    /// Disables all rules in the category e. g. `STYLE` or `TYPOGRAPHY` (case-insensitive).
+    /// Rules enabled with [Rules::enable] stay enabled, regardless of whether they were enabled before or after
+    /// disabling the category. They are disabled with the category again once they are disabled with [Rules::disable].
+    ///
+    /// # Errors
+    /// - If there is no rule in the category. The error lists close matches.
+    pub fn disable_category(&mut self, category: &str) -> Result<(), Error> {
+        let category = self.known_category(category)?;
+        self.toggles.disabled_categories.insert(category);
+
+        Ok(())
     }
-}
 
-/// Correct a text by applying suggestions to it.
-/// In the case of multiple possible replacements, always chooses the first one.
+    /// Enables a category disabled with [Rules::disable_category] again. Its rules have their own state again,
+    /// so rules which are disabled (e. g. by default) stay disabled.
+    ///
+    /// # Errors
+    /// - If there is no rule in the category. The error lists close matches.
+    pub fn enable_category(&mut self, category: &str) -> Result<(), Error> {
+        let category = self.known_category(category)?;
+        self.toggles.disabled_categories.remove(&category);
+
+        Ok(())
+    }
+
+    fn known_category(&self, category: &str) -> Result<Category, Error> {
+        let category = Category::new(category);
+
+        if !self
+            .rules
+            .iter()
+            .any(|rule| rule.id().parent().parent() == &category)
+        {
+            return Err(Error::NoMatchingRules {
+                id: category.to_string(),
+                close_matches: self.close_matches(&category.to_string()),
+            });
+        }
+
+        Ok(category)
+    }
+
+    /// Whether the rule is used when checking. This is the case if the rule is [enabled][Rule::enabled]
+    /// and its category is not disabled with [Rules::disable_category] or the rule was enabled with [Rules::enable].
+    pub fn is_enabled(&self, rule: &Rule) -> bool {
+        self.toggles.is_enabled(rule)
+    }
+
+    /// Gets the indices of the rules matching the ID, see [Rules::enable].
+    fn matching_indices(&self, id: &str) -> Vec<usize> {
+        let selector = Selector::try_from(id).ok();
+        let is_group_name = !id.contains('/');
+
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| {
+                matches!(&selector, Some(x) if x.is_match(rule.id()))
+                    || (is_group_name
+                        && UniCase::new(rule.id().parent().name()) == UniCase::new(id))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Gets the IDs of categories and groups which are similar to the ID, closest first.
+    fn close_matches(&self, id: &str) -> Vec<String> {
+        const MAX_CLOSE_MATCHES: usize = 5;
+
+        let query = id.to_lowercase();
+        let max_distance = (query.chars().count() / 3).max(2);
+
+        let mut candidates: Vec<(usize, String)> = Vec::new();
+        for rule in &self.rules {
+            let group = rule.id().parent();
+            let distance = |x: &str| utils::levenshtein(&query, &x.to_lowercase());
+
+            candidates.push((
+                distance(&group.to_string()).min(distance(group.name())),
+                group.to_string(),
+            ));
+            candidates.push((
+                distance(&group.parent().to_string()),
+                group.parent().to_string(),
+            ));
+        }
+
+        candidates.sort_unstable();
+        candidates.dedup_by(|a, b| a.1 == b.1);
+        candidates.retain(|(distance, _)| *distance <= max_distance);
+
+        candidates
+            .into_iter()
+            .take(MAX_CLOSE_MATCHES)
+            .map(|(_, id)| id)
+            .collect()
+    }
+
+    /// Overrides the message of the rule with the given ID. See [Rule::set_message].
+    ///
+    /// # Errors
+    /// - If there is no rule with the given ID.
+    /// - If the template refers to a group which does not exist in the rule.
+    pub fn set_message(&mut self, id: &str, template: &str) -> Result<(), Error> {
+        let mut overrides = HashMap::new();
+        overrides.insert(id.to_owned(), template.to_owned());
+
+        self.set_messages(&overrides)
+    }
+
+    /// Overrides the messages of multiple rules at once. `overrides` maps rule IDs to message templates.
+    ///
+    /// # Errors
+    /// - If `overrides` contains rule IDs which do not exist in this rule set.
+    /// - If a template refers to a group which does not exist in its rule.
+    ///
+    /// In both cases the rule set is left unchanged.
+    pub fn set_messages(&mut self, overrides: &HashMap<String, String>) -> Result<(), Error> {
+        let messages = self.parse_messages(overrides)?;
+        self.set_parsed_messages(messages);
+
+        Ok(())
+    }
+
+    /// Parses message templates keyed by rule ID into the messages of the rules at the returned indices.
+    fn parse_messages<'a, I>(
+        &self,
+        overrides: I,
+    ) -> Result<Vec<(usize, grammar::Synthesizer)>, Error>
+    where
+        I: IntoIterator<Item = (&'a String, &'a String)> + Copy,
+    {
+        let indices: HashMap<String, usize> = self
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| (rule.id().to_string(), i))
+            .collect();
+
+        let mut unknown: Vec<String> = overrides
+            .into_iter()
+            .map(|(id, _)| id)
+            .filter(|id| !indices.contains_key(*id))
+            .cloned()
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort_unstable();
+            return Err(Error::UnknownRuleIds(unknown));
+        }
+
+        let mut messages = Vec::new();
+        for (id, template) in overrides {
+            let rule = &self.rules[indices[id]];
+            messages.push((indices[id], rule.parse_message(template)?));
+        }
+
+        Ok(messages)
+    }
+
+    fn set_parsed_messages(&mut self, messages: Vec<(usize, grammar::Synthesizer)>) {
+        for (i, message) in messages {
+            let rule = &mut self.rules[i];
+            rule.message = message;
+            rule.message_overridden = true;
+        }
+    }
+
+    /// Overrides the severity of the suggestions of multiple rules at once. `overrides` maps rule IDs to severities.
+    /// See [Rule::set_severity] to override the severity of rules selected with a [Selector].
+    ///
+    /// # Errors
+    /// - If `overrides` contains rule IDs which do not exist in this rule set. In that case the rule set is left unchanged.
+    pub fn set_severities(&mut self, overrides: &HashMap<String, Severity>) -> Result<(), Error> {
+        let ids: HashSet<String> = self
+            .rules
+            .iter()
+            .map(|rule| rule.id().to_string())
+            .collect();
+
+        let mut unknown: Vec<String> = overrides
+            .keys()
+            .filter(|id| !ids.contains(*id))
+            .cloned()
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort_unstable();
+            return Err(Error::UnknownRuleIds(unknown));
+        }
+
+        for rule in self.rules.iter_mut() {
+            if let Some(severity) = overrides.get(&rule.id().to_string()) {
+                rule.set_severity(*severity);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the message templates of all rules in this rule set, keyed by rule ID.
+    /// Includes overrides set with [Rules::set_message] or [Rules::set_messages].
+    pub fn messages(&self) -> HashMap<String, String> {
+        self.rules
+            .iter()
+            .map(|rule| (rule.id().to_string(), rule.message()))
+            .collect()
+    }
+
+    fn check_report(
+        &self,
+        tokens: &[Token],
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+    ) -> CheckReport {
+        let active: Vec<_> = self
+            .rules
+            .iter()
+            .filter(|rule| {
+                self.is_enabled(rule)
+                    && !self.is_ignored(rule, options.segment_type)
+                    && !options.is_below_min_severity(rule)
+            })
+            .collect();
+        let oversized_tokens = tokens.iter().filter(|token| token.oversized).count();
+
+        let disposition = if active.is_empty() {
+            SentenceDisposition::Skipped {
+                reason: SkipReason::NoActiveRules,
+            }
+        } else if oversized_tokens > 0 {
+            SentenceDisposition::Truncated { oversized_tokens }
+        } else {
+            SentenceDisposition::Checked
+        };
+
+        let rules_missing_chunker = if tokenizer.chunker().is_none() {
+            active
+                .iter()
+                .filter(|rule| rule.uses_chunks())
+                .map(|rule| rule.id().to_string())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        CheckReport {
+            sentences: vec![SentenceReport {
+                char_span: (0, tokens[0].sentence.chars().count()),
+                disposition,
+            }],
+            rules_missing_chunker,
+            ignored_text: 0,
+            malformed_suggestions: 0,
+            truncated_at: None,
+        }
+    }
+
+    /// Routes the degradations which occur when applying the rules to the tokens through [strictness].
+    fn check_degradations(
+        &self,
+        tokens: &[Token],
+        tokenizer: &Tokenizer,
+        segment_type: SegmentType,
+        strictness: Strictness,
+    ) -> Result<(), Error> {
+        if strictness == Strictness::Lenient {
+            return Ok(());
+        }
+
+        if tokenizer.chunker().is_none()
+            && self.rules.iter().any(|rule| {
+                self.is_enabled(rule) && !self.is_ignored(rule, segment_type) && rule.uses_chunks()
+            })
+        {
+            strictness::degrade(Degradation::MissingChunker, strictness)?;
+        }
+        if tokens.iter().any(|token| token.oversized) {
+            strictness::degrade(Degradation::OversizedTokens, strictness)?;
+        }
+        if tokens
+            .iter()
+            .any(|token| token.word.text.as_ref() != token.original_text.trim())
+        {
+            strictness::degrade(Degradation::NormalizedText, strictness)?;
+        }
+
+        Ok(())
+    }
+
+    fn is_ignored(&self, rule: &Rule, segment_type: SegmentType) -> bool {
+        self.options
+            .segment_ignore_ids
+            .get(&segment_type)
+            .into_iter()
+            .flatten()
+            .any(|id| id.is_match(rule.id()))
+    }
+
+    /// Compute the suggestions for the given tokens by checking all rules.
+    ///
+    /// Suggestions are ordered by start, then end and then the ID of their rule, so the order does not depend
+    /// on the order of the rules in the binary. All methods which return suggestions keep this order,
+    /// suggestions of a text are ordered the same way across sentences.
+    pub fn apply(&self, tokens: &[Token], tokenizer: &Tokenizer) -> Vec<Suggestion> {
+        let candidates: Vec<_> = self.rule_suggestions(tokens, tokenizer).collect();
+
+        // later rules take precedence
+        let (active, _) = Conflict::default().resolve(candidates, |(_, x)| &x.span, |(i, _)| *i);
+        let mut active: Vec<_> = active.into_iter().map(|(_, x)| x).collect();
+        active.sort_by(suggestion_order);
+        active
+    }
+
+    /// Lazily computes the suggestions for the given tokens, rule by rule. A rule is only applied once the iterator
+    /// is advanced past the suggestions of the previous rules, so e. g. `.next()` or `.take(n)` stop early.
+    ///
+    /// The suggestions of each rule are yielded in the order of [Rules::apply]. Overlaps between suggestions of
+    /// different rules are not resolved: this and sorting the suggestions of all rules is only possible once all rules
+    /// have been applied, which is what [Rules::apply] does with the suggestions of this iterator.
+    pub fn apply_iter<'a>(
+        &'a self,
+        tokens: &'a [Token<'a>],
+        tokenizer: &'a Tokenizer,
+    ) -> impl Iterator<Item = Suggestion> + 'a {
+        self.rule_suggestions(tokens, tokenizer)
+            .map(|(_, suggestion)| suggestion)
+    }
+
+    /// The suggestions of the rules with the default [ApplyOptions] together with the index of their rule.
+    /// Rules are applied lazily, the suggestions of each rule are buffered and sorted.
+    fn rule_suggestions<'a>(
+        &'a self,
+        tokens: &'a [Token<'a>],
+        tokenizer: &'a Tokenizer,
+    ) -> impl Iterator<Item = (usize, Suggestion)> + 'a {
+        let options = ApplyOptions::default();
+        let rules = if tokens.is_empty() {
+            &[]
+        } else {
+            self.check_degradations(
+                tokens,
+                tokenizer,
+                options.segment_type,
+                options.strictness().infallible(),
+            )
+            .expect("only strict mode fails");
+
+            &self.rules[..]
+        };
+        let words = SentenceWords::new(tokens);
+        let n_chars = tokens
+            .first()
+            .map_or(0, |token| token.sentence.chars().count());
+
+        rules
+            .iter()
+            .enumerate()
+            .filter(move |(_, rule)| {
+                self.is_enabled(rule) && !self.is_ignored(rule, SegmentType::default())
+            })
+            .flat_map(move |(i, rule)| {
+                let mut suggestions: Vec<_> = if rule.may_match(&words) {
+                    rule.apply(tokens, tokenizer, &self.options, &options)
+                        .filter(|suggestion| is_well_formed(rule, suggestion, n_chars))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                suggestions.sort_by(suggestion_order);
+
+                suggestions
+                    .into_iter()
+                    .map(move |suggestion| (i, suggestion))
+            })
+    }
+
+    /// Compute the suggestions for the given tokens by checking all rules with the given options.
+    /// See [ApplyOptions] and [ApplyOutput].
+    pub fn apply_with_options(
+        &self,
+        tokens: &[Token],
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+    ) -> ApplyOutput {
+        self.apply_observed(
+            tokens,
+            tokenizer,
+            options,
+            options.strictness().infallible(),
+        )
+        .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Like [Rules::apply_with_options], but fails with [Error::Degraded] in strict mode
+    /// (see [ApplyOptions::strictness]) and with [Error::ObserverPanicked] if the observer panics
+    /// (see [ApplyOptions::on_suggestion]).
+    pub fn try_apply_with_options(
+        &self,
+        tokens: &[Token],
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+    ) -> Result<ApplyOutput, Error> {
+        self.apply_observed(tokens, tokenizer, options, options.strictness())
+    }
+
+    fn apply_observed(
+        &self,
+        tokens: &[Token],
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+        strictness: Strictness,
+    ) -> Result<ApplyOutput, Error> {
+        let output = self.apply_within(tokens, tokenizer, options, None, strictness)?;
+        options.observe(&output.active)?;
+
+        Ok(output)
+    }
+
+    /// Compute the suggestions for the `target` tokens of one sentence. The `left` and `right` tokens
+    /// (e. g. the end of the previous sentence and the start of the next sentence) are used as context which
+    /// rules can match into. Suggestions which would extend into the context are dropped.
+    ///
+    /// Useful to check only an edited sentence without losing rules which depend on neighboring sentences.
+    /// The indices of the suggestions are relative to the target sentence.
+    pub fn apply_in_context(
+        &self,
+        target: &[Token],
+        left: &[Token],
+        right: &[Token],
+        tokenizer: &Tokenizer,
+    ) -> Vec<Suggestion> {
+        if target.is_empty() {
+            return Vec::new();
+        }
+
+        // tokens store spans relative to their sentence, so join the sentences and shift the spans
+        let mut text = String::new();
+        let mut offsets = Vec::new();
+        for part in &[left, target, right] {
+            offsets.push((text.chars().count(), text.len()));
+
+            if let Some(token) = part.first() {
+                text.push_str(token.sentence);
+            }
+        }
+        let (target_char_offset, target_byte_offset) = offsets[1];
+        let target_range = (
+            target_char_offset,
+            target_char_offset + target[0].sentence.chars().count(),
+        );
+
+        let tokens: Vec<Token> = [left, target, right]
+            .iter()
+            .zip(offsets)
+            .flat_map(|(part, (char_offset, byte_offset))| {
+                let text = &text;
+
+                part.iter().map(move |token| Token {
+                    word: token.word.clone(),
+                    original_text: token.original_text,
+                    span: token.span.shift(char_offset, byte_offset),
+                    has_space_before: token.has_space_before,
+                    chunks: token.chunks.clone(),
+                    oversized: token.oversized,
+                    is_known: token.is_known,
+                    sentence: text,
+                    tagger: token.tagger,
+                })
+            })
+            .collect();
+
+        let options = ApplyOptions::default();
+        self.apply_within(
+            &tokens,
+            tokenizer,
+            &options,
+            Some(target_range),
+            options.strictness().infallible(),
+        )
+        .expect("only strict mode fails")
+        .active
+        .into_iter()
+        .map(|mut suggestion| {
+            suggestion.span = suggestion
+                .span
+                .unshift(target_char_offset, target_byte_offset);
+            suggestion
+        })
+        .collect()
+    }
+
+    /// Applies the rules, dropping suggestions which are not within the char `range` (if set)
+    /// before resolving overlaps.
+    fn apply_within(
+        &self,
+        tokens: &[Token],
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+        range: Option<(usize, usize)>,
+        strictness: Strictness,
+    ) -> Result<ApplyOutput, Error> {
+        let start = if options.timing {
+            Some(Instant::now())
+        } else {
+            None
+        };
+        let mut output = ApplyOutput::default();
+
+        if tokens.is_empty() {
+            return Ok(output);
+        }
+
+        self.check_degradations(tokens, tokenizer, options.segment_type, strictness)?;
+
+        if options.report {
+            output.report = Some(self.check_report(tokens, tokenizer, options));
+        }
+
+        // the flagged text is taken from the original sentence
+        let sentence = tokens[0].sentence;
+        let n_ignored_text = AtomicUsize::new(0);
+        let n_chars = sentence.chars().count();
+        let n_malformed = AtomicUsize::new(0);
+        let words = SentenceWords::new(tokens);
+
+        let suppression_reason = |rule: &Rule| {
+            if !self.is_enabled(rule) {
+                Some(SuppressionReason::Disabled)
+            } else if self.is_ignored(rule, options.segment_type) {
+                Some(SuppressionReason::SegmentType)
+            } else if options.is_below_min_severity(rule) {
+                Some(SuppressionReason::Severity)
+            } else {
+                None
+            }
+        };
+
+        let suggestions: Vec<(usize, Option<SuppressionReason>, Suggestion)> = self
+            .rules
+            .maybe_par_iter()
+            .enumerate()
+            .filter_map(|(i, rule)| {
+                let reason = suppression_reason(rule);
+
+                if reason.is_some() && !options.collect_suppressed {
+                    None
+                } else {
+                    Some((i, rule, reason))
+                }
+            })
+            .map(|(i, rule, reason)| {
+                let mut output = Vec::new();
+
+                if !options.disable_prefilter && !rule.may_match(&words) {
+                    return output;
+                }
+
+                for mut suggestion in rule.apply(tokens, tokenizer, &self.options, options) {
+                    if !is_well_formed(rule, &suggestion, n_chars) {
+                        n_malformed.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+
+                    if let Some((start, end)) = range {
+                        if suggestion.span.char().start < start || suggestion.span.char().end > end
+                        {
+                            continue;
+                        }
+                    }
+
+                    suggestion.segment_type = options.segment_type;
+
+                    let reason = reason.or_else(|| {
+                        if options.exceeds_replacement_distance(rule, &suggestion) {
+                            Some(SuppressionReason::ReplacementDistance)
+                        } else if !options.ignore_text_patterns.is_empty()
+                            && options.ignores_text(suggestion.span.slice(sentence))
+                        {
+                            n_ignored_text.fetch_add(1, Ordering::Relaxed);
+                            Some(SuppressionReason::IgnoredText)
+                        } else {
+                            None
+                        }
+                    });
+
+                    if reason.is_some() && !options.collect_suppressed {
+                        continue;
+                    }
+                    output.push((i, reason, suggestion));
+                }
+
+                output
+            })
+            .flatten()
+            .collect();
+
+        if let Some(report) = output.report.as_mut() {
+            report.ignored_text = n_ignored_text.into_inner();
+            report.malformed_suggestions = n_malformed.into_inner();
+        }
+
+        let mut candidates = Vec::new();
+        for (i, reason, suggestion) in suggestions {
+            match reason {
+                Some(reason) => output.suppressed.push((suggestion, reason)),
+                None => candidates.push((i, suggestion)),
+            }
+        }
+
+        // later rules take precedence
+        let (active, overlapping) =
+            options
+                .conflict
+                .resolve(candidates, |(_, x)| &x.span, |(i, _)| *i);
+        output.active.extend(active.into_iter().map(|(_, x)| x));
+        output.active.sort_by(suggestion_order);
+        if options.collect_suppressed {
+            output.suppressed.extend(
+                overlapping
+                    .into_iter()
+                    .map(|(_, x)| (x, SuppressionReason::Overlap)),
+            );
+        }
+        output
+            .suppressed
+            .sort_by(|(a, _), (b, _)| suggestion_order(a, b));
+
+        if let Some(start) = start {
+            let rules = Timing::micros(start.elapsed());
+            output.timing = Some(Timing {
+                rules,
+                total: rules,
+                ..Timing::default()
+            });
+        }
+
+        Ok(output)
+    }
+
+    /// Checks whether any enabled rule produces a suggestion for the given tokens i. e. whether
+    /// [apply][Rules::apply] would return at least one suggestion.
+    /// Stops applying rules at the first suggestion (see [Rules::apply_iter]), so this is cheaper than
+    /// `!rules.apply(..).is_empty()`. Resolving overlaps never drops all suggestions, so the result is the same.
+    pub fn any_match(&self, tokens: &[Token], tokenizer: &Tokenizer) -> bool {
+        self.apply_iter(tokens, tokenizer).next().is_some()
+    }
+
+    /// Checks whether the text has any issues i. e. whether [suggest][Rules::suggest] would return at least one suggestion.
+    pub fn has_issues(&self, text: &str, tokenizer: &Tokenizer) -> bool {
+        if text.is_empty() {
+            return false;
+        }
+
+        tokenizer
+            .pipe(text)
+            .iter()
+            .any(|tokens| self.any_match(tokens, tokenizer))
+    }
+
+    /// Compute the suggestions for a text by checking all rules.
+    pub fn suggest(&self, text: &str, tokenizer: &Tokenizer) -> Vec<Suggestion> {
+        if text.is_empty() {
+            return Vec::new();
+        }
+
+        let mut suggestions = Vec::new();
+        let mut char_offset = 0;
+        let mut byte_offset = 0;
+
+        // get suggestions sentence by sentence
+        for tokens in tokenizer.pipe(text) {
+            if tokens.is_empty() {
+                continue;
+            }
+
+            suggestions.extend(
+                self.apply(&tokens, tokenizer)
+                    .into_iter()
+                    .map(|mut suggestion| {
+                        suggestion.shift(char_offset, byte_offset);
+                        suggestion
+                    }),
+            );
+
+            char_offset += tokens[0].sentence.chars().count();
+            byte_offset += tokens[0].sentence.len();
+        }
+
+        suggestions
+    }
+
+    /// Compute the suggestions for a text by checking all rules with the given options.
+    /// See [ApplyOptions] and [ApplyOutput].
+    pub fn suggest_with_options(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+    ) -> ApplyOutput {
+        self.suggest_within(text, tokenizer, options, options.strictness().infallible())
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Like [Rules::suggest_with_options], but fails with [Error::Degraded] in strict mode
+    /// (see [ApplyOptions::strictness]) and with [Error::ObserverPanicked] if the observer panics
+    /// (see [ApplyOptions::on_suggestion]).
+    pub fn try_suggest_with_options(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+    ) -> Result<ApplyOutput, Error> {
+        self.suggest_within(text, tokenizer, options, options.strictness())
+    }
+
+    fn suggest_within(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+        strictness: Strictness,
+    ) -> Result<ApplyOutput, Error> {
+        let start = Instant::now();
+        let mut output = ApplyOutput::default();
+        if options.report {
+            output.report = Some(CheckReport::default());
+        }
+        if options.timing {
+            output.timing = Some(Timing::default());
+        }
+
+        if text.is_empty() {
+            return Ok(output);
+        }
+
+        for sentence_output in
+            self.stream_within(tokenizer, tokenizer.sentences(text), options, strictness)
+        {
+            // the output is already relative to the text
+            output.append(sentence_output?, 0, 0);
+        }
+
+        if let Some(timing) = output.timing.as_mut() {
+            timing.total = Timing::micros(start.elapsed());
+        }
+
+        Ok(output)
+    }
+
+    /// Compute the suggestions for a stream of sentences. Each sentence is given together with its span
+    /// in the text it is part of e. g. from [Tokenizer::sentences] or an external sentence splitter.
+    /// Sentences are tokenized and checked lazily, the returned suggestions are relative to the text.
+    pub fn apply_stream<'a, I>(
+        &'a self,
+        tokenizer: &'a Tokenizer,
+        sentences: I,
+    ) -> impl Iterator<Item = Suggestion> + 'a
+    where
+        I: IntoIterator<Item = (Span, &'a str)>,
+        I::IntoIter: 'a,
+    {
+        sentences
+            .into_iter()
+            .filter(|(span, _)| !span.is_empty())
+            .flat_map(move |(span, sentence)| {
+                let tokens = tokenizer.pipe_sentence(sentence);
+
+                self.apply(&tokens, tokenizer)
+                    .into_iter()
+                    .map(move |mut suggestion| {
+                        suggestion.shift(span.char().start, span.byte().start);
+                        suggestion
+                    })
+            })
+    }
+
+    /// Compute the suggestions for the text of a stream without reading it into memory completely.
+    /// The stream is split into sentences with [Tokenizer::read_sentences], each suggestion is passed to `f`
+    /// as soon as its sentence is checked. The suggestions are relative to the whole stream.
+    ///
+    /// # Errors
+    /// - If the stream can not be read or is not valid UTF-8. Suggestions for the sentences before the error
+    ///   have already been passed to `f`.
+    pub fn apply_reader<R: BufRead>(
+        &self,
+        tokenizer: &Tokenizer,
+        reader: R,
+        mut f: impl FnMut(Suggestion),
+    ) -> Result<(), Error> {
+        for sentence in tokenizer.read_sentences(reader) {
+            let (span, sentence) = sentence?;
+            if span.is_empty() {
+                continue;
+            }
+
+            let tokens = tokenizer.pipe_sentence(&sentence);
+            for mut suggestion in self.apply(&tokens, tokenizer) {
+                suggestion.shift(span.char().start, span.byte().start);
+                f(suggestion);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compute the output for a stream of sentences with the given options, see [Rules::apply_stream].
+    /// Yields one output per non-empty sentence, its spans are relative to the text so outputs can be combined with
+    /// [`ApplyOutput::append(output, 0, 0)`][ApplyOutput::append]. Fails like [Rules::try_apply_with_options].
+    ///
+    /// If the document budget of the options (see [ApplyOptions::max_sentences]) is exhausted, one more output
+    /// which only contains the [truncation point][CheckReport::truncated_at] is yielded and the stream ends.
+    pub fn try_apply_stream_with_options<'a, I>(
+        &'a self,
+        tokenizer: &'a Tokenizer,
+        sentences: I,
+        options: &'a ApplyOptions,
+    ) -> impl Iterator<Item = Result<ApplyOutput, Error>> + 'a
+    where
+        I: IntoIterator<Item = (Span, &'a str)>,
+        I::IntoIter: 'a,
+    {
+        self.stream_within(tokenizer, sentences, options, options.strictness())
+    }
+
+    fn stream_within<'a, I>(
+        &'a self,
+        tokenizer: &'a Tokenizer,
+        sentences: I,
+        options: &'a ApplyOptions,
+        strictness: Strictness,
+    ) -> impl Iterator<Item = Result<ApplyOutput, Error>> + 'a
+    where
+        I: IntoIterator<Item = (Span, &'a str)>,
+        I::IntoIter: 'a,
+    {
+        let mut budget = DocumentBudget::new(options);
+        let mut is_exhausted = false;
+
+        sentences
+            .into_iter()
+            .filter(|(span, _)| !span.is_empty())
+            .map_while(move |(span, sentence)| {
+                if is_exhausted {
+                    return None;
+                }
+
+                if !budget.take(span.char().len()) {
+                    is_exhausted = true;
+
+                    let mut output = ApplyOutput::default();
+                    if options.report {
+                        output.append(
+                            ApplyOutput {
+                                report: Some(CheckReport {
+                                    truncated_at: Some(TruncationPoint::default()),
+                                    ..CheckReport::default()
+                                }),
+                                ..ApplyOutput::default()
+                            },
+                            span.char().start,
+                            span.byte().start,
+                        );
+                    }
+                    return Some(Ok(output));
+                }
+
+                Some(self.apply_sentence(tokenizer, span, sentence, options, strictness))
+            })
+    }
+
+    /// Checks one sentence of a stream. See [Rules::try_apply_stream_with_options].
+    fn apply_sentence(
+        &self,
+        tokenizer: &Tokenizer,
+        span: Span,
+        sentence: &str,
+        options: &ApplyOptions,
+        strictness: Strictness,
+    ) -> Result<ApplyOutput, Error> {
+        let (tokens, timing) = pipe_with_options(tokenizer, sentence, true, options);
+
+        let mut output = ApplyOutput {
+            timing,
+            ..ApplyOutput::default()
+        };
+        output.append(
+            self.apply_within(&tokens, tokenizer, options, None, strictness)?,
+            span.char().start,
+            span.byte().start,
+        );
+        options.observe(&output.active)?;
+
+        Ok(output)
+    }
+
+    /// Compute the suggestions for an isolated word or phrase e. g. a form field or a product title.
+    /// The text is tokenized as one unit without sentence semantics (see [Tokenizer::pipe_fragment]) and the rules
+    /// are applied with [SegmentType::Fragment] so rules which assume full sentences are skipped.
+    pub fn check_fragment(&self, text: &str, tokenizer: &Tokenizer) -> Vec<Suggestion> {
+        self.check_fragment_with_options(text, tokenizer, &ApplyOptions::default())
+            .active
+    }
+
+    /// Compute the suggestions for an isolated word or phrase with the given options. See [Rules::check_fragment].
+    /// The segment type of `options` is always [SegmentType::Fragment].
+    pub fn check_fragment_with_options(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+    ) -> ApplyOutput {
+        self.check_fragment_within(text, tokenizer, options, options.strictness().infallible())
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Like [Rules::check_fragment_with_options], but fails like [Rules::try_apply_with_options].
+    pub fn try_check_fragment_with_options(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+    ) -> Result<ApplyOutput, Error> {
+        self.check_fragment_within(text, tokenizer, options, options.strictness())
+    }
+
+    fn check_fragment_within(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        options: &ApplyOptions,
+        strictness: Strictness,
+    ) -> Result<ApplyOutput, Error> {
+        let options = ApplyOptions {
+            segment_type: SegmentType::Fragment,
+            ..options.clone()
+        };
+
+        let (tokens, timing) = pipe_with_options(tokenizer, text, false, &options);
+        let mut output = ApplyOutput {
+            timing,
+            ..ApplyOutput::default()
+        };
+        output.append(
+            self.apply_observed(&tokens, tokenizer, &options, strictness)?,
+            0,
+            0,
+        );
+
+        Ok(output)
+    }
+
+    /// Correct a text by first tokenizing, then finding all suggestions and choosing the first replacement of each suggestion.
+    pub fn correct(&self, text: &str, tokenizer: &Tokenizer) -> String {
+        self.correct_with_options(text, tokenizer, &CorrectOptions::default())
+    }
+
+    /// Correct a text like [Rules::correct] with the given options. See [CorrectOptions].
+    pub fn correct_with_options(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        options: &CorrectOptions,
+    ) -> String {
+        self.correct_with_mapping(text, tokenizer, options).0
+    }
+
+    /// Correct a text like [Rules::correct_with_options] and also return an [OffsetMap] which maps positions in the text
+    /// to positions in the corrected text e. g. to re-anchor annotations onto the corrected text.
+    pub fn correct_with_mapping(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        options: &CorrectOptions,
+    ) -> (String, OffsetMap) {
+        let suggestions = self.corrections(text, tokenizer, options, false).active;
+
+        apply_suggestions_with_mapping(text, &suggestions, options)
+    }
+
+    /// Correct a text like [Rules::correct_with_options] and also return which suggestions were applied and which
+    /// were skipped because they overlap, e. g. to show a diff. All spans refer to the original text,
+    /// like the spans of [Rules::suggest]. Slower than [Rules::correct_with_options] because overlapping
+    /// suggestions have to be collected.
+    pub fn correct_with_trace(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        options: &CorrectOptions,
+    ) -> (String, CorrectionTrace) {
+        let output = self.corrections(text, tokenizer, options, true);
+        let (corrected, mut trace) = apply_suggestions_with_trace(text, &output.active, options);
+
+        trace.skipped.extend(
+            output
+                .suppressed
+                .into_iter()
+                .filter(|(_, reason)| *reason == SuppressionReason::Overlap)
+                .map(|(x, _)| x),
+        );
+        trace.skipped.sort_by(suggestion_order);
+
+        (corrected, trace)
+    }
+
+    /// The suggestions to correct a text with, including hyphenation repairs if enabled.
+    fn corrections(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        options: &CorrectOptions,
+        collect_suppressed: bool,
+    ) -> ApplyOutput {
+        let apply_options = ApplyOptions {
+            conflict: options.conflict,
+            collect_suppressed,
+            ..ApplyOptions::default()
+        };
+        let mut output = self.suggest_with_options(text, tokenizer, &apply_options);
+
+        if options.repair_hyphenation {
+            let repairs: Vec<_> = hyphenation_repairs(text, tokenizer)
+                .into_iter()
+                .filter(|repair| {
+                    !output
+                        .active
+                        .iter()
+                        .any(|x| x.span.intersects(&repair.span))
+                })
+                .collect();
+            output.active.extend(repairs);
+        }
+
+        output
+    }
+}
+
+/// Options for applying suggestions to a text. See [apply_suggestions_with_options].
+#[derive(Debug, Clone, Default)]
+pub struct CorrectOptions {
+    /// Whether to wrap replacements which are next to text of another direction (e. g. an English replacement
+    /// next to a quoted Hebrew phrase) in FIRST STRONG ISOLATE (U+2068) and POP DIRECTIONAL ISOLATE (U+2069)
+    /// so the replacement can not change how the neighboring text is displayed. Only affects display,
+    /// the text is always changed in logical order.
+    pub isolate_bidi: bool,
+    /// Whether to also replace words which are hyphenated at a line break with the rejoined word
+    /// e. g. "infor-\nmation" with "information". Only has an effect in [Rules::correct_with_options] and only if
+    /// the tokenizer rejoins hyphenation, see [TokenizerOptions::rejoin_hyphenation][crate::tokenizer::TokenizerOptions::rejoin_hyphenation].
+    /// Suggestions of rules take precedence.
+    pub repair_hyphenation: bool,
+    /// Which of several overlapping suggestions to apply. In [Rules::correct_with_options] this also decides
+    /// which suggestions of the rules are kept, see [ApplyOptions::conflict].
+    pub conflict: Conflict,
+}
+
+/// A suggestion which was applied to a text. See [CorrectionTrace].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedCorrection {
+    /// The span of the replaced text in the original text. Includes the adjacent space removed with a deletion,
+    /// see [apply_suggestions].
+    pub span: Span,
+    /// The replaced text.
+    pub original: String,
+    /// The replacement which was chosen.
+    pub replacement: String,
+    /// The ID of the rule the suggestion is from.
+    pub source: String,
+}
+
+/// What was changed when correcting a text. See [Rules::correct_with_trace].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CorrectionTrace {
+    /// The applied suggestions in order of their position in the text.
+    pub applied: Vec<AppliedCorrection>,
+    /// Suggestions which were not applied because they overlap an applied suggestion, see [CorrectOptions::conflict].
+    pub skipped: Vec<Suggestion>,
+}
+
+/// Suggestions which replace the words hyphenated at a line break in the text with the word rejoined by the tokenizer.
+fn hyphenation_repairs(text: &str, tokenizer: &Tokenizer) -> Vec<Suggestion> {
+    let mut repairs = Vec::new();
+    let mut char_offset = 0;
+    let mut byte_offset = 0;
+
+    for tokens in tokenizer.pipe(text) {
+        if tokens.is_empty() {
+            continue;
+        }
+
+        for token in tokens
+            .iter()
+            .filter(|token| token.original_text.contains('\n'))
+            .filter(|token| token.word.text.as_ref() != token.original_text.trim())
+        {
+            let replacement = token.word.text.as_ref().to_owned();
+            let original = token.original_text;
+            // the original text of a token can include the whitespace around it
+            let leading = original.len() - original.trim_start().len();
+            let span = Span::from_bytes(
+                token.sentence,
+                token.span.byte().start + leading
+                    ..token.span.byte().start + original.trim_end().len(),
+            );
+
+            repairs.push(Suggestion {
+                source: "HYPHENATION_REPAIR".into(),
+                message: "Rejoin the word hyphenated at a line break.".into(),
+                short_message: "Hyphenation".into(),
+                url: None,
+                severity: Severity::Warning,
+                span: span.shift(char_offset, byte_offset),
+                replacement_distance: utils::levenshtein(original.trim(), &replacement),
+                replacements: vec![replacement],
+                segment_type: SegmentType::default(),
+            });
+        }
+
+        char_offset += tokens[0].sentence.chars().count();
+        byte_offset += tokens[0].sentence.len();
+    }
+
+    repairs
+}
+
+/// Whether the `replacement` of the chars in `start..end` is next to text of another direction.
+/// Neutral chars (e. g. whitespace and punctuation) between the replacement and the neighboring text are skipped.
+fn is_at_direction_boundary(chars: &[char], start: usize, end: usize, replacement: &str) -> bool {
+    let before = chars[..start]
+        .iter()
+        .rev()
+        .find_map(|c| utils::strong_direction(*c));
+    let after = chars[end..]
+        .iter()
+        .find_map(|c| utils::strong_direction(*c));
+    let mut directions = replacement.chars().filter_map(utils::strong_direction);
+
+    let first = match directions.next() {
+        Some(first) => first,
+        // replacements without strong direction take the direction of their surroundings
+        None => return false,
+    };
+    let is_other = |x: Option<Direction>| matches!(x, Some(x) if x != first);
+
+    directions.any(|x| x != first) || is_other(before) || is_other(after)
+}
+
+/// Applies the tokenization pipeline to a sentence (or a fragment if `is_sentence` is false)
+/// and measures the time it took if [ApplyOptions::timing] is set.
+fn pipe_with_options<'t>(
+    tokenizer: &'t Tokenizer,
+    sentence: &'t str,
+    is_sentence: bool,
+    options: &ApplyOptions,
+) -> (Vec<Token<'t>>, Option<Timing>) {
+    if options.timing {
+        let (tokens, timing) = if is_sentence {
+            tokenizer.pipe_sentence_timed(sentence)
+        } else {
+            tokenizer.pipe_fragment_timed(sentence)
+        };
+        (tokens, Some(timing))
+    } else if is_sentence {
+        (tokenizer.pipe_sentence(sentence), None)
+    } else {
+        (tokenizer.pipe_fragment(sentence), None)
+    }
+}
+
+/// The order of suggestions, see [Rules::apply].
+fn suggestion_order(a: &Suggestion, b: &Suggestion) -> std::cmp::Ordering {
+    let key = |x: &Suggestion| (x.span.char().start, x.span.char().end);
+
+    key(a).cmp(&key(b)).then_with(|| a.source.cmp(&b.source))
+}
+
+/// Whether the span of a suggestion of the `rule` is within a sentence with `n_chars` chars.
+/// Malformed spans are a bug in the rule, so they are logged and fail loudly in debug builds.
+fn is_well_formed(rule: &Rule, suggestion: &Suggestion, n_chars: usize) -> bool {
+    let range = suggestion.span.char();
+    if range.end <= n_chars {
+        return true;
+    }
+
+    let message = format!(
+        "rule {} produced a suggestion with malformed span {:?} in a sentence with {} chars",
+        rule.id(),
+        range,
+        n_chars
+    );
+    // unit tests check that malformed suggestions are dropped
+    if cfg!(all(debug_assertions, not(test))) {
+        panic!("{}", message);
+    }
+    warn!("{}. The suggestion is dropped.", message);
+
+    false
+}
+
+/// Correct a text by applying suggestions to it.
+/// In the case of multiple possible replacements, always chooses the first one. See [correct_with] to choose
+/// another one.
+///
+/// An empty replacement deletes the text. If that would leave two spaces or a space before punctuation,
+/// one adjacent space is deleted too e. g. deleting the second "the" in "the the next" gives "the next".
 pub fn apply_suggestions(text: &str, suggestions: &[Suggestion]) -> String {
+    apply_suggestions_with_options(text, suggestions, &CorrectOptions::default())
+}
+
+/// Correct a text by applying suggestions to it with the given options. See [apply_suggestions] and [CorrectOptions].
+///
+/// The text is changed strictly in logical order i. e. the order in which it is stored, not the order in which
+/// it is displayed, so right-to-left runs in the text are never reordered.
+pub fn apply_suggestions_with_options(
+    text: &str,
+    suggestions: &[Suggestion],
+    options: &CorrectOptions,
+) -> String {
+    apply_suggestions_with_mapping(text, suggestions, options).0
+}
+
+/// Correct a text by applying suggestions to it like [apply_suggestions_with_options] and also return an [OffsetMap]
+/// built from the applied suggestions. Suggestions which are not applied (e. g. because they overlap) are not in the map.
+pub fn apply_suggestions_with_mapping(
+    text: &str,
+    suggestions: &[Suggestion],
+    options: &CorrectOptions,
+) -> (String, OffsetMap) {
+    let (corrected, map, _) = apply_suggestions_traced(text, suggestions, options, |_| Some(0));
+    (corrected, map)
+}
+
+/// Applies suggestions like [apply_suggestions_with_options] and also returns which suggestions were applied
+/// and which were skipped because they overlap an applied suggestion.
+pub fn apply_suggestions_with_trace(
+    text: &str,
+    suggestions: &[Suggestion],
+    options: &CorrectOptions,
+) -> (String, CorrectionTrace) {
+    apply_suggestions_with_selector(text, suggestions, options, |_| Some(0))
+}
+
+/// Correct a text by applying suggestions to it, choosing the replacement of each suggestion with `select`.
+/// `select` returns the index of the replacement to apply or `None` to skip the suggestion. Indices which are out
+/// of bounds skip the suggestion too.
+///
+/// # Example
+/// ```
+/// # use nlprule::{rules::correct_with, types::*};
+/// let text = "I can due his homework.";
+/// let suggestion = Suggestion {
+///     source: "DUE_DO".into(),
+///     message: String::new(),
+///     short_message: String::new(),
+///     url: None,
+///     severity: Severity::default(),
+///     span: Span::from_chars(text, 6..9),
+///     replacements: vec!["does".into(), "do".into()],
+///     segment_type: SegmentType::default(),
+///     replacement_distance: 0,
+/// };
+///
+/// assert_eq!(correct_with(text, &[suggestion.clone()], |_| Some(1)), "I can do his homework.");
+/// assert_eq!(correct_with(text, &[suggestion], |_| None), text);
+/// ```
+pub fn correct_with(
+    text: &str,
+    suggestions: &[Suggestion],
+    select: impl FnMut(&Suggestion) -> Option<usize>,
+) -> String {
+    apply_suggestions_with_selector(text, suggestions, &CorrectOptions::default(), select).0
+}
+
+/// Correct a text like [correct_with] with the given options and also return which suggestions were applied
+/// (with the chosen replacement) and which were skipped because they overlap. Suggestions skipped by `select`
+/// are not in the trace. `select` is called once for each suggestion in order, before overlaps are resolved.
+pub fn apply_suggestions_with_selector(
+    text: &str,
+    suggestions: &[Suggestion],
+    options: &CorrectOptions,
+    select: impl FnMut(&Suggestion) -> Option<usize>,
+) -> (String, CorrectionTrace) {
+    let (corrected, _, trace) = apply_suggestions_traced(text, suggestions, options, select);
+    (corrected, trace)
+}
+
+fn apply_suggestions_traced(
+    text: &str,
+    suggestions: &[Suggestion],
+    options: &CorrectOptions,
+    mut select: impl FnMut(&Suggestion) -> Option<usize>,
+) -> (String, OffsetMap, CorrectionTrace) {
+    let original: Vec<_> = text.chars().collect();
+    let mut chars = original.clone();
     let mut offset: isize = 0;
-    let mut chars: Vec<_> = text.chars().collect();
 
-    for suggestion in suggestions {
-        let replacement: Vec<_> = suggestion.replacements[0].chars().collect();
-        chars.splice(
-            (suggestion.start as isize + offset) as usize
-                ..(suggestion.end as isize + offset) as usize,
-            replacement.iter().cloned(),
+    // malformed suggestions can not be applied
+    let n = suggestions.len();
+    let suggestions: Vec<_> = suggestions
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| x.span.char().end <= original.len())
+        .filter_map(|(i, x)| {
+            let replacement = select(x).and_then(|index| x.replacements.get(index))?;
+            Some((i, x, replacement))
+        })
+        .collect();
+    // the offset is only correct if the suggestions are applied from left to right
+    let (suggestions, overlapping) =
+        options
+            .conflict
+            .resolve(suggestions, |(_, x, _)| &x.span, |(i, _, _)| n - i);
+    let mut edits = Vec::new();
+    let mut trace = CorrectionTrace {
+        applied: Vec::new(),
+        skipped: overlapping.into_iter().map(|(_, x, _)| x.clone()).collect(),
+    };
+
+    // the end of the last edit in the corrected text
+    let mut corrected_end = 0;
+
+    for (i, (_, suggestion, chosen)) in suggestions.iter().enumerate() {
+        let mut range = suggestion.span.char();
+        if chosen.is_empty() {
+            let shift = |x: usize| (x as isize + offset) as usize;
+            let next_start = suggestions
+                .get(i + 1)
+                .map_or(original.len(), |(_, x, _)| x.span.char().start);
+
+            // the text around the deletion is not changed by other edits, so positions can be shifted back and forth
+            let collapsed = collapse_deletion(
+                &chars,
+                shift(range.start)..shift(range.end),
+                corrected_end..shift(next_start),
+            );
+            range = range.start - (shift(range.start) - collapsed.start)
+                ..range.end + (collapsed.end - shift(range.end));
+        }
+
+        let mut replacement: Vec<_> = chosen.chars().collect();
+        if options.isolate_bidi
+            && !replacement.is_empty()
+            && is_at_direction_boundary(&original, range.start, range.end, chosen)
+        {
+            replacement.insert(0, '\u{2068}');
+            replacement.push('\u{2069}');
+        }
+
+        let start = (range.start as isize + offset) as usize;
+        chars.splice(
+            start..(range.end as isize + offset) as usize,
+            replacement.iter().cloned(),
+        );
+        edits.push(AppliedEdit {
+            original: range.clone(),
+            corrected: start..start + replacement.len(),
+        });
+        corrected_end = start + replacement.len();
+        offset = offset + replacement.len() as isize - range.len() as isize;
+
+        trace.applied.push(AppliedCorrection {
+            span: Span::from_chars(text, range.clone()),
+            original: original[range].iter().collect(),
+            replacement: (*chosen).clone(),
+            source: suggestion.source.clone(),
+        });
+    }
+
+    let map = OffsetMap::new(edits, original.len(), chars.len());
+    (chars.into_iter().collect(), map, trace)
+}
+
+/// Extends the char `range` of a deletion in the `text` by one adjacent space if the deletion would leave
+/// two spaces or a space before punctuation e. g. "the the next" -> "the next" or "word x, next" -> "word, next".
+/// The range is only extended within `bounds` so it does not overlap other edits.
+/// The `text` already contains the edits before the deletion.
+fn collapse_deletion(text: &[char], range: Range<usize>, bounds: Range<usize>) -> Range<usize> {
+    if range.is_empty() {
+        return range;
+    }
+
+    let before = range.start.checked_sub(1).map(|i| text[i]);
+    let after = text.get(range.end).copied();
+    let is_closing = |c: char| matches!(c, ',' | '.' | ';' | ':' | '!' | '?' | ')' | ']');
+
+    if before == Some(' ')
+        && range.start > bounds.start
+        && after.map_or(true, |c| c == ' ' || is_closing(c))
+    {
+        range.start - 1..range.end
+    } else if before.is_none() && after == Some(' ') && range.end < bounds.end {
+        range.start..range.end + 1
+    } else {
+        range
+    }
+}
+
+/// An iterator over references to rules.
+pub struct RulesIter<'a> {
+    selector: Option<&'a Selector>,
+    inner: std::slice::Iter<'a, Rule>,
+}
+
+impl<'a> Iterator for RulesIter<'a> {
+    type Item = &'a Rule;
+    fn next(&mut self) -> Option<Self::Item> {
+        let selector = self.selector.as_ref();
+
+        self.inner
+            .find(|rule| selector.map_or(true, |s| s.is_match(rule.id())))
+    }
+}
+
+/// An iterator over mutable references to rules.
+pub struct RulesIterMut<'a> {
+    selector: Option<&'a Selector>,
+    inner: std::slice::IterMut<'a, Rule>,
+}
+
+impl<'a> Iterator for RulesIterMut<'a> {
+    type Item = &'a mut Rule;
+    fn next(&mut self) -> Option<Self::Item> {
+        let selector = self.selector.as_ref();
+
+        self.inner
+            .find(|rule| selector.map_or(true, |s| s.is_match(rule.id())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rule::{
+            engine::{
+                composition::{
+                    concrete::TextAtom, Atom, Composition, GraphId, Matcher, Part, Quantifier,
+                    TextMatcher, Trigger, TrueAtom,
+                },
+                Engine, TokenEngine,
+            },
+            grammar::{Synthesizer, SynthesizerPart},
+        },
+        tokenizer::tag::Tagger,
+    };
+
+    #[test]
+    fn config_version_is_checked() {
+        let mut rules = Rules::default();
+        let mut config = rules.export_config();
+
+        assert!(rules.apply_config(&config).is_ok());
+
+        config.version = RulesConfig::VERSION + 1;
+        assert!(matches!(
+            rules.apply_config(&config),
+            Err(Error::UnsupportedConfigVersion(_))
+        ));
+    }
+
+    #[test]
+    fn config_with_unknown_ids_is_rejected() {
+        let mut rules = Rules::default();
+        let mut config = rules.export_config();
+        config.disabled.push("TYPOS/can_not/0".into());
+        config.options.punctuation_inside_quotes = true;
+
+        assert!(matches!(
+            rules.apply_config(&config),
+            Err(Error::UnknownRuleIds(ids)) if ids == vec!["TYPOS/can_not/0".to_owned()]
+        ));
+        // the rule set is left unchanged
+        assert!(!rules.options().punctuation_inside_quotes);
+    }
+
+    #[test]
+    fn config_keeps_toggles_and_overrides() {
+        let rule_set = || {
+            let mut style = rule("wordy", "And", 3, 3);
+            style.id = Category::new("STYLE").join("wordy").join(0);
+
+            Rules {
+                rules: vec![style, rule("inside", "And", 3, 3)],
+                options: RulesOptions::default(),
+                info: ModelInfo::default(),
+                toggles: Toggles::default(),
+            }
+        };
+        let enabled = |rules: &Rules| -> Vec<_> {
+            rules
+                .rules()
+                .iter()
+                .map(|rule| rules.is_enabled(rule))
+                .collect()
+        };
+
+        let mut rules = rule_set();
+        rules.disable_category("style").unwrap();
+        rules.set_message("TEST/inside/0", "Fine.").unwrap();
+        rules
+            .select_mut(&Category::new("TEST").into())
+            .for_each(|rule| rule.set_severity(Severity::Error));
+
+        let config = rules.export_config();
+        assert_eq!(config.disabled_categories, vec!["style".to_owned()]);
+        assert_eq!(config.messages["TEST/inside/0"], "Fine.");
+        assert_eq!(config.severities["TEST/inside/0"], Severity::Error);
+        assert_eq!(config.severities.len(), 1);
+
+        let json = serde_json::to_string(&config).unwrap();
+        let mut fresh = rule_set();
+        fresh
+            .apply_config(&serde_json::from_str(&json).unwrap())
+            .unwrap();
+
+        assert_eq!(fresh.export_config(), config);
+        assert_eq!(enabled(&fresh), vec![false, true]);
+        assert_eq!(fresh.messages()["TEST/inside/0"], "Fine.");
+        assert_eq!(fresh.rules()[1].severity(), Severity::Error);
+
+        // the category is disabled as a whole, so enabling it restores its rules
+        fresh.enable_category("STYLE").unwrap();
+        assert_eq!(enabled(&fresh), vec![true, true]);
+    }
+
+    #[test]
+    fn config_with_invalid_overrides_is_rejected() {
+        let mut rules = Rules {
+            rules: vec![rule("inside", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let mut config = rules.export_config();
+        config.disabled.push("TEST/inside/0".into());
+        config
+            .messages
+            .insert("TEST/inside/0".into(), "Use \\9.".into());
+
+        assert!(matches!(
+            rules.apply_config(&config),
+            Err(Error::InvalidMessage { group: 9, .. })
+        ));
+
+        config.messages.clear();
+        config.disabled_categories.push("STYLE".into());
+        assert!(matches!(
+            rules.apply_config(&config),
+            Err(Error::NoMatchingRules { .. })
+        ));
+        // the rule set is left unchanged
+        assert!(rules.is_enabled(&rules.rules()[0]));
+    }
+
+    #[test]
+    fn acronyms_keep_replacement_casing() {
+        let options = ApplyOptions {
+            lock_acronym_case: true,
+            ..ApplyOptions::default()
+        };
+
+        assert!(options.is_case_locked("IOS", "iOS"));
+        assert!(options.is_case_locked("USA", "US"));
+        assert!(!options.is_case_locked("I", "me"));
+        assert!(!options.is_case_locked("HOUSES", "houses"));
+
+        // disabled by default
+        assert!(!ApplyOptions::default().is_case_locked("IOS", "iOS"));
+    }
+
+    #[test]
+    fn case_exceptions_keep_replacement_casing() {
+        let options = ApplyOptions {
+            case_exceptions: vec!["iPhone".into()],
+            ..ApplyOptions::default()
+        };
+
+        // e. g. at sentence start
+        assert!(options.is_case_locked("Iphone", "iPhone"));
+        assert!(options.is_case_locked("IPhones", "iPhone is"));
+        assert!(!options.is_case_locked("Phone", "phone"));
+    }
+
+    #[test]
+    fn inserted_punctuation_respects_closing_symbols() {
+        let english = RulesOptions {
+            punctuation_inside_quotes: true,
+            ..RulesOptions::default()
+        };
+        let german = RulesOptions::default();
+
+        let place = |options: &RulesOptions, sentence: &str, before: &str, replacement: &str| {
+            let end = sentence.find(before).unwrap() + before.len();
+            let end = sentence[..end].chars().count();
+
+            let (new_end, replacements) = options.place_inserted_punctuation(
+                sentence,
+                end,
+                before,
+                vec![replacement.to_owned()],
+            );
+            let start = end - before.chars().count();
+            apply_suggestions(
+                sentence,
+                &[Suggestion {
+                    source: String::new(),
+                    message: String::new(),
+                    short_message: String::new(),
+                    url: None,
+                    severity: Severity::default(),
+                    span: Span::from_chars(sentence, start..new_end),
+                    replacements,
+                    segment_type: SegmentType::default(),
+                    replacement_distance: 0,
+                }],
+            )
+        };
+
+        // quotes
+        assert_eq!(
+            place(&english, "He said \"yes\"", "yes", "yes."),
+            "He said \"yes.\""
+        );
+        assert_eq!(
+            place(&english, "He said \"yes\"", "yes\"", "yes\"."),
+            "He said \"yes.\""
+        );
+        assert_eq!(
+            place(&german, "Er sagte „ja“", "ja", "ja."),
+            "Er sagte „ja“."
+        );
+        assert_eq!(
+            place(&german, "Er sagte »ja«", "ja«", "ja«."),
+            "Er sagte »ja«."
+        );
+        // parentheses
+        assert_eq!(
+            place(&english, "See the appendix (page 4)", "4", "4."),
+            "See the appendix (page 4)."
+        );
+        assert_eq!(
+            place(&german, "Siehe Anhang (Seite 4)", "4", "4."),
+            "Siehe Anhang (Seite 4)."
+        );
+        // footnote markers
+        assert_eq!(
+            place(&english, "This is proven¹", "proven", "proven."),
+            "This is proven¹."
+        );
+        assert_eq!(
+            place(&english, "He said \"yes\"²", "yes", "yes."),
+            "He said \"yes.\"²"
+        );
+        assert_eq!(
+            place(&german, "Das ist bewiesen¹", "bewiesen", "bewiesen!"),
+            "Das ist bewiesen¹!"
+        );
+        // other replacements are not changed
+        assert_eq!(
+            place(&english, "He said \"yes\"", "yes", "no"),
+            "He said \"no\""
+        );
+        assert_eq!(place(&german, "Er sagte ja.", "ja", "ja."), "Er sagte ja..");
+    }
+
+    #[test]
+    fn suggestions_are_applied_in_logical_order() {
+        let text = "He said שלום עולם to to me.";
+        let suggestion = |start: usize, end: usize, replacement: &str| Suggestion {
+            source: String::new(),
+            message: String::new(),
+            short_message: String::new(),
+            url: None,
+            severity: Severity::default(),
+            span: Span::from_chars(text, start..end),
+            replacements: vec![replacement.to_owned()],
+            segment_type: SegmentType::default(),
+            replacement_distance: 0,
+        };
+
+        assert_eq!(suggestion(13, 17, "").flagged_text(text), "עולם");
+        assert_eq!(
+            apply_suggestions(text, &[suggestion(17, 20, "")]),
+            "He said שלום עולם to me."
+        );
+        // suggestions are applied from left to right regardless of their order
+        assert_eq!(
+            apply_suggestions(
+                text,
+                &[suggestion(24, 26, "us"), suggestion(13, 17, "עולמות")]
+            ),
+            "He said שלום עולמות to to us."
+        );
+
+        let options = CorrectOptions {
+            isolate_bidi: true,
+            ..CorrectOptions::default()
+        };
+        let isolated = |suggestions: &[Suggestion]| {
+            apply_suggestions_with_options(text, suggestions, &options)
+        };
+
+        assert_eq!(
+            isolated(&[suggestion(18, 20, "for")]),
+            "He said שלום עולם \u{2068}for\u{2069} to me."
+        );
+        assert_eq!(
+            isolated(&[suggestion(13, 17, "עולמות")]),
+            "He said שלום \u{2068}עולמות\u{2069} to to me."
+        );
+        // replacements within text of the same direction or without strong direction are not isolated
+        assert_eq!(
+            isolated(&[suggestion(24, 26, "us")]),
+            "He said שלום עולם to to us."
+        );
+        assert_eq!(
+            isolated(&[suggestion(17, 20, "")]),
+            "He said שלום עולם to me."
         );
-        offset = offset + replacement.len() as isize - (suggestion.end - suggestion.start) as isize;
     }
 
-    chars.into_iter().collect()
-}
+    #[test]
+    fn offsets_are_projected_onto_corrected_text() {
+        let text = "I has went to the the store.";
+        let suggestion = |start: usize, end: usize, replacement: &str| Suggestion {
+            source: String::new(),
+            message: String::new(),
+            short_message: String::new(),
+            url: None,
+            severity: Severity::default(),
+            span: Span::from_chars(text, start..end),
+            replacements: vec![replacement.to_owned()],
+            segment_type: SegmentType::default(),
+            replacement_distance: 0,
+        };
+        let (corrected, map) = apply_suggestions_with_mapping(
+            text,
+            &[
+                suggestion(22, 22, "big "),
+                suggestion(2, 10, "have gone"),
+                suggestion(14, 18, ""),
+            ],
+            &CorrectOptions::default(),
+        );
 
-/// An iterator over references to rules.
-pub struct RulesIter<'a> {
-    selector: Option<&'a Selector>,
-    inner: std::slice::Iter<'a, Rule>,
-}
+        assert_eq!(corrected, "I have gone to the big store.");
+        assert_eq!(map.edits().len(), 3);
 
-impl<'a> Iterator for RulesIter<'a> {
-    type Item = &'a Rule;
-    fn next(&mut self) -> Option<Self::Item> {
-        let selector = self.selector.as_ref();
+        // text boundaries
+        assert_eq!(map.project(0), Ok(0));
+        assert_eq!(map.project(28), Ok(29));
+        assert_eq!(map.project_back(29), Ok(28));
+        // before and after length-changing edits
+        assert_eq!(map.project(2), Ok(2));
+        assert_eq!(map.project(10), Ok(11));
+        assert_eq!(map.project(11), Ok(12));
+        assert_eq!(map.project(18), Ok(15));
+        assert_eq!(map.project(20), Ok(17));
+        // positions at insertions are kept before the inserted text, ranges do not grow
+        assert_eq!(map.project(22), Ok(19));
+        assert_eq!(map.project_range(22..27), Ok(23..28));
+        assert_eq!(map.project_back(15), Ok(14));
+        assert_eq!(map.project_range_back(15..18), Ok(18..21));
+        assert_eq!(map.project_range_back(23..28), Ok(22..27));
 
-        self.inner
-            .find(|rule| selector.map_or(true, |s| s.is_match(rule.id())))
+        // positions inside replaced spans are clamped by default
+        assert_eq!(map.project(5), Ok(2));
+        assert_eq!(map.project(16), Ok(15));
+        assert_eq!(map.project_range(5..7), Ok(2..11));
+        assert_eq!(map.project_back(21), Ok(22));
+
+        let map = map.with_policy(ProjectionPolicy::Error);
+        assert_eq!(map.project(5), Err(Deleted { start: 2, end: 10 }));
+        assert_eq!(
+            map.project_range(0..16),
+            Err(Deleted { start: 14, end: 18 })
+        );
+        assert_eq!(map.project_back(21), Err(Deleted { start: 19, end: 23 }));
+        assert_eq!(map.project(10), Ok(11));
     }
-}
 
-/// An iterator over mutable references to rules.
-pub struct RulesIterMut<'a> {
-    selector: Option<&'a Selector>,
-    inner: std::slice::IterMut<'a, Rule>,
-}
+    #[test]
+    fn german_nouns_are_not_case_locked() {
+        let options = ApplyOptions {
+            lock_acronym_case: true,
+            case_exceptions: vec!["iPhone".into()],
+            ..ApplyOptions::default()
+        };
 
-impl<'a> Iterator for RulesIterMut<'a> {
-    type Item = &'a mut Rule;
-    fn next(&mut self) -> Option<Self::Item> {
-        let selector = self.selector.as_ref();
+        // capitalization of nouns is grammatical and must still be adjusted
+        assert!(!options.is_case_locked("Haus", "Häuser"));
+        assert!(!options.is_case_locked("Straße", "Strasse"));
+    }
 
-        self.inner
-            .find(|rule| selector.map_or(true, |s| s.is_match(rule.id())))
+    fn text_atom(text: &str) -> Atom {
+        TextAtom {
+            matcher: TextMatcher {
+                matcher: Matcher {
+                    matcher: either::Left(either::Left(text.to_owned())),
+                    negate: false,
+                    case_sensitive: true,
+                    empty_always_false: true,
+                },
+                set: None,
+            },
+        }
+        .into()
+    }
+
+    /// A rule matching "." [SENT_START] "and" which suggests `replacement` for the text from group `start` to group `end`.
+    fn rule(group: &str, replacement: &str, start: usize, end: usize) -> Rule {
+        let parts: Vec<_> = vec![text_atom("."), TrueAtom {}.into(), text_atom("and")]
+            .into_iter()
+            .map(|atom| Part {
+                atom,
+                quantifier: Quantifier { min: 1, max: 1 },
+                greedy: true,
+                visible: true,
+                unify: None,
+            })
+            .collect();
+        let text = |text: &str| Synthesizer {
+            use_titlecase_adjust: false,
+            suppress_misspelled: false,
+            parts: vec![SynthesizerPart::Text(text.to_owned())],
+        };
+
+        Rule {
+            id: Category::new("TEST").join(group).join(0),
+            engine: Engine::Token(TokenEngine {
+                composition: Composition {
+                    id_to_idx: (0..=parts.len()).map(|i| (GraphId(i), i)).collect(),
+                    can_stop_mask: vec![false; parts.len()],
+                    parts,
+                },
+                antipatterns: Vec::new(),
+            }),
+            examples: Vec::new(),
+            suggesters: vec![text(replacement)],
+            message: text("Sentence starts with \"and\"."),
+            start: GraphId(start),
+            end: GraphId(end),
+            url: None,
+            short: None,
+            name: String::new(),
+            category_name: String::new(),
+            category_type: None,
+            severity: Severity::default(),
+            unification: None,
+            filter: None,
+            enabled: true,
+            message_overridden: false,
+            severity_overridden: false,
+            triggers: None,
+        }
+    }
+
+    fn tokens<'t>(sentence: &'t str, tagger: &'t Tagger) -> Vec<Token<'t>> {
+        let mut start = 0;
+
+        // a stand-in for the SENT_START token, the default tagger has no tags
+        std::iter::once("")
+            .chain(sentence.split_whitespace())
+            .map(|word| {
+                let byte_start = start + sentence[start..].find(word).unwrap();
+                let char_start = sentence[..byte_start].chars().count();
+                start = byte_start + word.len();
+
+                Token {
+                    word: Word::new_with_tags(tagger.id_word(word.into()), Vec::new()),
+                    original_text: word,
+                    span: Span::new(
+                        char_start..char_start + word.chars().count(),
+                        byte_start..start,
+                    ),
+                    has_space_before: byte_start > 0,
+                    chunks: Vec::new(),
+                    oversized: false,
+                    is_known: true,
+                    sentence,
+                    tagger,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rules_can_match_into_context() {
+        let tokenizer = Tokenizer::default();
+        let rules = Rules {
+            rules: vec![rule("inside", "And", 3, 3), rule("spanning", "And", 1, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+
+        let left = tokens("It ended . ", tokenizer.tagger());
+        let target = tokens("and then it began .", tokenizer.tagger());
+
+        // without context, the rule does not see the end of the previous sentence
+        assert!(rules.apply(&target, &tokenizer).is_empty());
+
+        let suggestions = rules.apply_in_context(&target, &left[left.len() - 1..], &[], &tokenizer);
+
+        // the suggestion spanning into the context is dropped
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].source, "TEST/inside/0");
+        assert_eq!(suggestions[0].span.char(), 0..3);
+        assert_eq!(suggestions[0].replacements, vec!["And".to_owned()]);
+    }
+
+    #[test]
+    fn suggestions_in_context_are_dropped() {
+        let tokenizer = Tokenizer::default();
+        let rules = Rules {
+            rules: vec![rule("inside", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+
+        let target = tokens("It ended . ", tokenizer.tagger());
+        let right = tokens("and then it began .", tokenizer.tagger());
+
+        assert!(rules
+            .apply_in_context(&target, &[], &right, &tokenizer)
+            .is_empty());
+    }
+
+    #[test]
+    fn prefilter_skips_rules_without_trigger_words() {
+        let tokenizer = Tokenizer::default();
+        let mut triggered = rule("triggered", "And", 3, 3);
+        triggered.triggers = Some(vec![Trigger::Word(".".into())]);
+        // triggers are extracted when compiling
+        #[cfg(feature = "compile")]
+        assert_eq!(triggered.engine.triggers(), triggered.triggers);
+
+        // a rule with wrong triggers shows that the rule is actually skipped
+        let mut skipped = rule("skipped", "And", 3, 3);
+        skipped.triggers = Some(vec![Trigger::Word("but".into())]);
+
+        let rules = Rules {
+            rules: vec![triggered, skipped],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began", tokenizer.tagger());
+        let sources = |options: &ApplyOptions| -> Vec<String> {
+            rules
+                .apply_with_options(&tokens, &tokenizer, options)
+                .active
+                .into_iter()
+                .map(|x| x.source)
+                .collect()
+        };
+
+        assert_eq!(sources(&ApplyOptions::default()), vec!["TEST/triggered/0"]);
+        assert!(rules.any_match(&tokens, &tokenizer));
+        let other = self::tokens("It ended . and then it began", tokenizer.tagger());
+        assert!(!rules.any_match(&other, &tokenizer));
+        assert!(rules.apply(&other, &tokenizer).is_empty());
+
+        let options = ApplyOptions {
+            disable_prefilter: true,
+            ..ApplyOptions::default()
+        };
+        assert_eq!(sources(&options), vec!["TEST/skipped/0"]);
+    }
+
+    #[test]
+    fn suggestions_can_be_iterated_rule_by_rule() {
+        let tokenizer = Tokenizer::default();
+        let rules = Rules {
+            rules: vec![rule("first", "And", 3, 3), rule("second", "AND", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+        let sources = |suggestions: Vec<Suggestion>| -> Vec<_> {
+            suggestions.into_iter().map(|x| x.source).collect()
+        };
+
+        // overlaps are only resolved by `apply`
+        assert_eq!(
+            sources(rules.apply_iter(&tokens, &tokenizer).collect()),
+            vec!["TEST/first/0", "TEST/second/0"]
+        );
+        assert_eq!(
+            sources(rules.apply_iter(&tokens, &tokenizer).take(1).collect()),
+            vec!["TEST/first/0"]
+        );
+        assert_eq!(
+            sources(rules.apply(&tokens, &tokenizer)),
+            vec!["TEST/second/0"]
+        );
+        assert_eq!(
+            rules.apply(&tokens, &tokenizer),
+            rules
+                .apply_with_options(&tokens, &tokenizer, &ApplyOptions::default())
+                .active
+        );
+        assert_eq!(rules.apply_iter(&[], &tokenizer).count(), 0);
+    }
+
+    #[test]
+    fn warm_up_compiles_regexes() {
+        let tokenizer = Tokenizer::default();
+        let mut rule = rule("regex", "And", 3, 3);
+        if let Engine::Token(engine) = &mut rule.engine {
+            engine.composition.parts[2].atom = TextAtom {
+                matcher: TextMatcher {
+                    matcher: Matcher {
+                        matcher: either::Right(Regex::new("^(?:and|or)$".into())),
+                        negate: false,
+                        case_sensitive: true,
+                        empty_always_false: true,
+                    },
+                    set: None,
+                },
+            }
+            .into();
+        }
+        let rules = Rules {
+            rules: vec![rule],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let is_compiled = || rules.rules[0].regexes().iter().all(|x| x.is_compiled());
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+
+        assert!(!is_compiled());
+        rules.warm_up();
+        assert!(is_compiled());
+
+        assert_eq!(rules.apply(&tokens, &tokenizer)[0].source, "TEST/regex/0");
+    }
+
+    #[test]
+    fn replacement_distance_guards_against_template_bugs() {
+        let tokenizer = Tokenizer::default();
+        // a broken template which replaces "and" with a whole phrase
+        let rules = Rules {
+            rules: vec![
+                rule("fine", "And", 3, 3),
+                rule("broken", "And \\1 was <suggestion>", 3, 3),
+            ],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+
+        let suggestions = rules.apply(&tokens, &tokenizer);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].source, "TEST/broken/0");
+        assert_eq!(suggestions[0].replacement_distance, 21);
+
+        let mut options = ApplyOptions {
+            max_replacement_distance: Some(3),
+            collect_suppressed: true,
+            ..ApplyOptions::default()
+        };
+
+        let output = rules.apply_with_options(&tokens, &tokenizer, &options);
+        assert_eq!(output.active.len(), 1);
+        assert_eq!(output.active[0].source, "TEST/fine/0");
+        assert_eq!(output.active[0].replacement_distance, 1);
+        assert_eq!(output.suppressed.len(), 1);
+        assert_eq!(
+            output.suppressed[0].1,
+            SuppressionReason::ReplacementDistance
+        );
+
+        options.replacement_distance_exceptions = vec![Category::new("TEST").join("broken").into()];
+        let output = rules.apply_with_options(&tokens, &tokenizer, &options);
+        assert_eq!(output.active[0].source, "TEST/broken/0");
+    }
+
+    #[test]
+    fn ignored_text_does_not_hide_other_suggestions() {
+        let tokenizer = Tokenizer::default();
+        let rules = Rules {
+            rules: vec![rule("narrow", "And", 3, 3), rule("wide", "And", 2, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+
+        let suggestions = rules.apply(&tokens, &tokenizer);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].source, "TEST/wide/0");
+
+        let options = ApplyOptions {
+            ignore_text_patterns: TextPattern::compile_all(["^- "]).unwrap(),
+            collect_suppressed: true,
+            report: true,
+            ..ApplyOptions::default()
+        };
+        let output = rules.apply_with_options(&tokens, &tokenizer, &options);
+
+        assert_eq!(output.active.len(), 1);
+        assert_eq!(output.active[0].source, "TEST/narrow/0");
+        assert_eq!(output.suppressed.len(), 1);
+        assert_eq!(output.suppressed[0].1, SuppressionReason::IgnoredText);
+        assert_eq!(output.report.unwrap().ignored_text, 1);
+
+        assert!(matches!(
+            TextPattern::compile_all(["[A-Z]+", "(unclosed"]),
+            Err(Error::InvalidPattern { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn overlaps_are_resolved_by_policy() {
+        let text = "I saw your car yesterday.";
+        let suggestion = |start: usize, end: usize, replacement: &str| Suggestion {
+            source: String::new(),
+            message: String::new(),
+            short_message: String::new(),
+            url: None,
+            severity: Severity::default(),
+            span: Span::from_chars(text, start..end),
+            replacements: vec![replacement.to_owned()],
+            segment_type: SegmentType::default(),
+            replacement_distance: 0,
+        };
+        // in order of priority
+        let suggestions = [
+            suggestion(6, 10, "you're"),
+            suggestion(2, 14, "saw the car"),
+            suggestion(6, 24, "the car today"),
+        ];
+        let correct = |conflict| {
+            let options = CorrectOptions {
+                conflict,
+                ..CorrectOptions::default()
+            };
+            apply_suggestions_with_options(text, &suggestions, &options)
+        };
+
+        assert_eq!(correct(Conflict::Earliest), "I saw the car yesterday.");
+        assert_eq!(correct(Conflict::Longest), "I saw the car today.");
+        assert_eq!(correct(Conflict::Priority), "I saw you're car yesterday.");
+
+        // an insertion at the start of a replacement does not overlap it
+        assert_eq!(
+            apply_suggestions(
+                text,
+                &[suggestion(2, 5, "bought"), suggestion(2, 2, "never ")]
+            ),
+            "I never bought your car yesterday."
+        );
+
+        let tokenizer = Tokenizer::default();
+        let rules = Rules {
+            rules: vec![rule("wide", "And", 2, 3), rule("narrow", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+        let source = |conflict| {
+            let options = ApplyOptions {
+                conflict,
+                collect_suppressed: true,
+                ..ApplyOptions::default()
+            };
+            let output = rules.apply_with_options(&tokens, &tokenizer, &options);
+            assert_eq!(output.suppressed[0].1, SuppressionReason::Overlap);
+            output.active[0].source.clone()
+        };
+
+        assert_eq!(source(Conflict::Earliest), "TEST/wide/0");
+        assert_eq!(source(Conflict::Longest), "TEST/wide/0");
+        assert_eq!(source(Conflict::Priority), "TEST/narrow/0");
+    }
+
+    #[test]
+    fn deletions_collapse_adjacent_spaces() {
+        let deletion = |text: &str, start: usize, end: usize| Suggestion {
+            source: String::new(),
+            message: String::new(),
+            short_message: String::new(),
+            url: None,
+            severity: Severity::default(),
+            span: Span::from_chars(text, start..end),
+            replacements: vec![String::new()],
+            segment_type: SegmentType::default(),
+            replacement_distance: 0,
+        };
+        let correct = |text: &str, ranges: &[(usize, usize)]| {
+            let suggestions: Vec<_> = ranges
+                .iter()
+                .map(|(start, end)| deletion(text, *start, *end))
+                .collect();
+            apply_suggestions(text, &suggestions)
+        };
+
+        assert_eq!(correct("the the next", &[(4, 7)]), "the next");
+        assert_eq!(correct("word x, next", &[(5, 6)]), "word, next");
+        assert_eq!(correct("word , next", &[(4, 5)]), "word, next");
+        assert_eq!(correct("at the end the", &[(11, 14)]), "at the end");
+        assert_eq!(correct("the next", &[(0, 3)]), "next");
+        // the space is not taken from an adjacent edit
+        assert_eq!(correct("a b c", &[(0, 1), (2, 3)]), "c");
+        assert_eq!(correct("a b c", &[(2, 3), (4, 5)]), "a");
+
+        let (corrected, map) = apply_suggestions_with_mapping(
+            "the the next",
+            &[deletion("the the next", 4, 7)],
+            &CorrectOptions::default(),
+        );
+        assert_eq!(corrected, "the next");
+        assert_eq!(map.project(8), Ok(4));
+
+        // rules can suggest deletions
+        let tokenizer = Tokenizer::default();
+        let rules = Rules {
+            rules: vec![rule("delete", "", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let text = "It ended . - and then it began .";
+        let suggestions = rules.apply(&tokens(text, tokenizer.tagger()), &tokenizer);
+        assert_eq!(suggestions[0].replacements, vec![String::new()]);
+        assert_eq!(
+            apply_suggestions(text, &suggestions),
+            "It ended . - then it began ."
+        );
+    }
+
+    #[test]
+    fn correction_traces_refer_to_the_original_text() {
+        let text = "It ended . - and then then it began .";
+        let suggestion = |source: &str, start: usize, end: usize, replacement: &str| Suggestion {
+            source: source.to_owned(),
+            message: String::new(),
+            short_message: String::new(),
+            url: None,
+            severity: Severity::default(),
+            span: Span::from_chars(text, start..end),
+            replacements: vec![replacement.to_owned()],
+            segment_type: SegmentType::default(),
+            replacement_distance: 0,
+        };
+        let suggestions = [
+            suggestion("wide", 11, 16, "And"),
+            suggestion("narrow", 13, 16, "And"),
+            suggestion("delete", 22, 26, ""),
+        ];
+
+        let (corrected, trace) =
+            apply_suggestions_with_trace(text, &suggestions, &CorrectOptions::default());
+        assert_eq!(corrected, "It ended . And then it began .");
+        assert_eq!(corrected, apply_suggestions(text, &suggestions));
+
+        let applied: Vec<_> = trace
+            .applied
+            .iter()
+            .map(|x| {
+                (
+                    x.source.as_str(),
+                    x.original.as_str(),
+                    x.replacement.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            applied,
+            vec![("wide", "- and", "And"), ("delete", " then", "")]
+        );
+        // the span includes the space removed with the deletion
+        assert_eq!(trace.applied[1].span, Span::from_chars(text, 21..26));
+
+        assert_eq!(trace.skipped.len(), 1);
+        assert_eq!(trace.skipped[0].source, "narrow");
+    }
+
+    #[test]
+    fn selected_replacements_are_applied() {
+        let text = "He go to the the store yesterday.";
+        let suggestion =
+            |source: &str, start: usize, end: usize, replacements: &[&str]| Suggestion {
+                source: source.to_owned(),
+                message: String::new(),
+                short_message: String::new(),
+                url: None,
+                severity: Severity::default(),
+                span: Span::from_chars(text, start..end),
+                replacements: replacements.iter().map(|x| (*x).to_owned()).collect(),
+                segment_type: SegmentType::default(),
+                replacement_distance: 0,
+            };
+        let suggestions = [
+            suggestion("verb", 3, 5, &["goes", "went"]),
+            suggestion("duplicate", 9, 13, &[""]),
+            suggestion("time", 23, 32, &["today", "on Monday"]),
+        ];
+        let select = |choices: &[(&str, Option<usize>)]| {
+            let choices: HashMap<_, _> = choices.iter().cloned().collect();
+            correct_with(text, &suggestions, |x| choices[x.source.as_str()])
+        };
+
+        assert_eq!(
+            select(&[("verb", Some(0)), ("duplicate", Some(0)), ("time", Some(0))]),
+            apply_suggestions(text, &suggestions)
+        );
+        assert_eq!(
+            select(&[("verb", None), ("duplicate", None), ("time", None)]),
+            text
+        );
+        // the offsets of later suggestions account for the length of the chosen replacement
+        assert_eq!(
+            select(&[("verb", Some(1)), ("duplicate", Some(0)), ("time", Some(1))]),
+            "He went to the store on Monday."
+        );
+        assert_eq!(
+            select(&[("verb", Some(1)), ("duplicate", None), ("time", Some(1))]),
+            "He went to the the store on Monday."
+        );
+        // out of bounds indices skip the suggestion
+        assert_eq!(
+            select(&[("verb", Some(2)), ("duplicate", Some(0)), ("time", Some(1))]),
+            "He go to the store on Monday."
+        );
+
+        // a skipped suggestion does not prevent an overlapping one from being applied
+        let overlapping = [
+            suggestion("wide", 3, 8, &["went to"]),
+            suggestion("narrow", 3, 5, &["goes", "went"]),
+        ];
+        let (corrected, trace) =
+            apply_suggestions_with_selector(text, &overlapping, &CorrectOptions::default(), |x| {
+                if x.source == "wide" {
+                    None
+                } else {
+                    Some(1)
+                }
+            });
+        assert_eq!(corrected, "He went to the the store yesterday.");
+        assert_eq!(trace.applied[0].replacement, "went");
+        assert!(trace.skipped.is_empty());
+    }
+
+    #[test]
+    fn suggestion_order_does_not_depend_on_rule_order() {
+        let tokenizer = Tokenizer::default();
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+        let options = ApplyOptions {
+            collect_suppressed: true,
+            ..ApplyOptions::default()
+        };
+        let output = |rules: Vec<Rule>| {
+            let rules = Rules {
+                rules,
+                options: RulesOptions::default(),
+                info: ModelInfo::default(),
+                toggles: Toggles::default(),
+            };
+            let output = rules.apply_with_options(&tokens, &tokenizer, &options);
+            format!("{:?} {:?}", output.active, output.suppressed)
+        };
+
+        let forward = output(vec![
+            rule("wide", "And", 2, 3),
+            rule("b", "And", 3, 3),
+            rule("a", "And", 3, 3),
+        ]);
+        let backward = output(vec![
+            rule("a", "And", 3, 3),
+            rule("b", "And", 3, 3),
+            rule("wide", "And", 2, 3),
+        ]);
+
+        assert_eq!(forward, backward);
+        assert!(forward.find("TEST/a/0").unwrap() < forward.find("TEST/b/0").unwrap());
+    }
+
+    #[test]
+    fn replacements_take_over_the_case_of_the_replaced_text() {
+        let tokenizer = Tokenizer::default();
+        let correct = |text: &str, replacement: &str| {
+            let mut rule = rule("case", replacement, 3, 3);
+            rule.suggesters[0].use_titlecase_adjust = true;
+            if let Engine::Token(engine) = &mut rule.engine {
+                if let Atom::TextAtom(atom) = &mut engine.composition.parts[2].atom {
+                    atom.matcher.matcher.case_sensitive = false;
+                }
+            }
+            let rules = Rules {
+                rules: vec![rule],
+                options: RulesOptions::default(),
+                info: ModelInfo::default(),
+                toggles: Toggles::default(),
+            };
+
+            let tokens = tokens(text, tokenizer.tagger());
+            apply_suggestions(text, &rules.apply(&tokens, &tokenizer))
+        };
+
+        assert_eq!(
+            correct("It ended . - and then", "but"),
+            "It ended . - but then"
+        );
+        assert_eq!(
+            correct("It ended . - And then", "but"),
+            "It ended . - But then"
+        );
+        // only the first word is title cased
+        assert_eq!(
+            correct("It ended . - And then", "but then again"),
+            "It ended . - But then again then"
+        );
+        assert_eq!(
+            correct("It ended . - And then", "'tis"),
+            "It ended . - 'Tis then"
+        );
+        // short words in all caps may be acronyms, unless the whole sentence is in all caps
+        assert_eq!(
+            correct("It ended . - AND then", "but"),
+            "It ended . - But then"
+        );
+        assert_eq!(
+            correct("IT ENDED . - AND THEN", "but"),
+            "IT ENDED . - BUT THEN"
+        );
+        assert_eq!(
+            correct("It ended . - AND then", "but also"),
+            "It ended . - But also then"
+        );
+    }
+
+    #[test]
+    fn case_locked_replacements_keep_their_casing() {
+        let tokenizer = Tokenizer::default();
+        let correct = |text: &str, word: &str, replacement: &str, options: &ApplyOptions| {
+            let mut rule = rule("case", replacement, 3, 3);
+            rule.suggesters[0].use_titlecase_adjust = true;
+            if let Engine::Token(engine) = &mut rule.engine {
+                engine.composition.parts[2].atom = text_atom(word);
+            }
+            let rules = Rules {
+                rules: vec![rule],
+                options: RulesOptions::default(),
+                info: ModelInfo::default(),
+                toggles: Toggles::default(),
+            };
+
+            let tokens = tokens(text, tokenizer.tagger());
+            apply_suggestions(
+                text,
+                &rules
+                    .apply_with_options(&tokens, &tokenizer, options)
+                    .active,
+            )
+        };
+        let locked = ApplyOptions {
+            lock_acronym_case: true,
+            case_exceptions: vec!["iPhone".into()],
+            ..ApplyOptions::default()
+        };
+
+        // the casing is adjusted by default
+        let options = ApplyOptions::default();
+        assert_eq!(
+            correct("It ended . - IOS then", "IOS", "iOS", &options),
+            "It ended . - IOS then"
+        );
+        assert_eq!(
+            correct("It ended . - IPHONE then", "IPHONE", "iPhone", &options),
+            "It ended . - IPHONE then"
+        );
+
+        // but not per call with case-locked terms
+        assert_eq!(
+            correct("It ended . - IOS then", "IOS", "iOS", &locked),
+            "It ended . - iOS then"
+        );
+        assert_eq!(
+            correct("It ended . - IPHONE then", "IPHONE", "iPhone", &locked),
+            "It ended . - iPhone then"
+        );
+        assert_eq!(
+            correct("It ended . - Haus then", "Haus", "häuser", &locked),
+            "It ended . - Häuser then"
+        );
+    }
+
+    #[test]
+    fn corrections_use_the_original_text() {
+        let tokenizer = Tokenizer::default();
+        let tagger = tokenizer.tagger();
+        let mut rule = rule("case", "but", 3, 3);
+        rule.suggesters[0].use_titlecase_adjust = true;
+        if let Engine::Token(engine) = &mut rule.engine {
+            if let Atom::TextAtom(atom) = &mut engine.composition.parts[2].atom {
+                atom.matcher.matcher.case_sensitive = false;
+            }
+        }
+        let rules = Rules {
+            rules: vec![rule],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+
+        let text = "IT ENDED . - AN\u{ad}D THE\u{ad}N";
+        let mut tokens = tokens(text, tagger);
+        // like the tokenizer, remove the invisible chars from the token text
+        for token in tokens.iter_mut() {
+            let word = token.original_text.replace('\u{ad}', "");
+            token.word = Word::new_with_tags(tagger.id_word(word.into()), Vec::new());
+        }
+
+        let suggestions = rules.apply(&tokens, &tokenizer);
+        assert_eq!(suggestions[0].span.slice(text), "AN\u{ad}D");
+        // the case of the replaced text is taken from the original text
+        assert_eq!(
+            apply_suggestions(text, &suggestions),
+            "IT ENDED . - BUT THE\u{ad}N"
+        );
+    }
+
+    #[test]
+    fn malformed_suggestions_are_dropped() {
+        let text = "It ended . - and then it began .";
+        let n_chars = text.chars().count();
+        // the text is ASCII, so char and byte offsets coincide, also out of range
+        let suggestion = |start: usize, end: usize, replacements: &[&str]| Suggestion {
+            source: String::new(),
+            message: String::new(),
+            short_message: String::new(),
+            url: None,
+            severity: Severity::default(),
+            span: Span::new(start..end, start..end),
+            replacements: replacements.iter().map(|x| x.to_string()).collect(),
+            segment_type: SegmentType::default(),
+            replacement_distance: 0,
+        };
+
+        let rule = rule("wide", "And", 2, 3);
+        assert!(is_well_formed(
+            &rule,
+            &suggestion(13, 16, &["And"]),
+            n_chars
+        ));
+        assert!(is_well_formed(&rule, &suggestion(32, 32, &["."]), n_chars));
+        assert!(!is_well_formed(&rule, &suggestion(30, 40, &["x"]), n_chars));
+
+        // `correct` survives suggestions which did not come from `apply`
+        assert_eq!(
+            apply_suggestions(
+                text,
+                &[
+                    suggestion(13, 16, &["And"]),
+                    suggestion(30, 40, &["x"]),
+                    suggestion(14, 21, &["x"]),
+                    suggestion(0, 2, &[]),
+                ]
+            ),
+            "It ended . - And then it began ."
+        );
+    }
+
+    #[test]
+    fn observer_sees_returned_suggestions() {
+        let tokenizer = Tokenizer::default();
+        let rules = Rules {
+            rules: vec![rule("narrow", "And", 3, 3), rule("wide", "And", 2, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+
+        let observed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let options = ApplyOptions {
+            collect_suppressed: true,
+            on_suggestion: Some(SuggestionObserver::new({
+                let observed = observed.clone();
+                move |x| observed.lock().unwrap().push(x.source.clone())
+            })),
+            ..ApplyOptions::default()
+        };
+        let output = rules.apply_with_options(&tokens, &tokenizer, &options);
+
+        assert_eq!(output.suppressed.len(), 1);
+        assert_eq!(
+            *observed.lock().unwrap(),
+            output
+                .active
+                .iter()
+                .map(|x| x.source.clone())
+                .collect::<Vec<_>>()
+        );
+
+        let options = ApplyOptions {
+            on_suggestion: Some(SuggestionObserver::new(|_| panic!("observer failed"))),
+            ..ApplyOptions::default()
+        };
+        match rules.try_apply_with_options(&tokens, &tokenizer, &options) {
+            Err(Error::ObserverPanicked(message)) => assert_eq!(message, "observer failed"),
+            x => panic!("expected the observer to panic, got {:?}", x),
+        }
+        // the rules are still usable
+        assert_eq!(rules.apply(&tokens, &tokenizer).len(), 1);
+    }
+
+    #[test]
+    fn degradations_depend_on_strictness() {
+        let tokenizer = Tokenizer::default();
+        let rules = Rules {
+            rules: vec![rule("inside", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let sentence = "It ended . - and then it began .";
+        let clean = tokens(sentence, tokenizer.tagger());
+
+        let mut oversized = tokens(sentence, tokenizer.tagger());
+        oversized[7].oversized = true;
+        let mut normalized = tokens(sentence, tokenizer.tagger());
+        normalized[1].original_text = "I\u{ad}t";
+
+        for (tokens, kind) in &[
+            (&oversized, Degradation::OversizedTokens),
+            (&normalized, Degradation::NormalizedText),
+        ] {
+            for strictness in &[Strictness::Lenient, Strictness::Warn, Strictness::Strict] {
+                let options = ApplyOptions {
+                    strictness: Some(*strictness),
+                    ..ApplyOptions::default()
+                };
+
+                let output = rules.try_apply_with_options(tokens, &tokenizer, &options);
+                if *strictness == Strictness::Strict {
+                    assert!(matches!(output, Err(Error::Degraded(x)) if x == *kind));
+                } else {
+                    assert_eq!(output.unwrap().active.len(), 1);
+                }
+
+                // the infallible method never fails
+                let output = rules.apply_with_options(tokens, &tokenizer, &options);
+                assert_eq!(output.active.len(), 1);
+            }
+        }
+
+        let options = ApplyOptions {
+            strictness: Some(Strictness::Strict),
+            ..ApplyOptions::default()
+        };
+        assert!(rules
+            .try_apply_with_options(&clean, &tokenizer, &options)
+            .is_ok());
+    }
+
+    #[test]
+    fn messages_can_be_overridden() {
+        let tokenizer = Tokenizer::default();
+        let mut rules = Rules {
+            rules: vec![rule("inside", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+
+        rules
+            .set_message("TEST/inside/0", "Consider not starting with \"\\3\".")
+            .unwrap();
+        assert_eq!(
+            rules.apply(&tokens, &tokenizer)[0].message,
+            "Consider not starting with \"and\"."
+        );
+        assert_eq!(
+            rules.messages()["TEST/inside/0"],
+            "Consider not starting with \"\\3\"."
+        );
+
+        let mut overrides = HashMap::new();
+        overrides.insert("TEST/inside/0".to_owned(), "Use \\9.".to_owned());
+        assert!(matches!(
+            rules.set_messages(&overrides),
+            Err(Error::InvalidMessage { group: 9, .. })
+        ));
+
+        overrides.insert("TEST/inside/0".to_owned(), "Fine.".to_owned());
+        overrides.insert("TEST/outside/0".to_owned(), "Fine.".to_owned());
+        assert!(matches!(
+            rules.set_messages(&overrides),
+            Err(Error::UnknownRuleIds(ids)) if ids == vec!["TEST/outside/0".to_owned()]
+        ));
+        // the rule set is left unchanged
+        assert_eq!(
+            rules.messages()["TEST/inside/0"],
+            "Consider not starting with \"\\3\"."
+        );
+    }
+
+    #[test]
+    fn suggestions_carry_the_short_message_and_url() {
+        let tokenizer = Tokenizer::default();
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+        let rules = |rule: Rule| Rules {
+            rules: vec![rule],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+
+        let mut with_short = rule("short", "And", 3, 3);
+        with_short.short = Some("Style".to_owned());
+        with_short.url = Some("https://example.com/and".to_owned());
+        let suggestions = rules(with_short).apply(&tokens, &tokenizer);
+        assert_eq!(suggestions[0].short_message, "Style");
+        assert_eq!(
+            suggestions[0].url.as_deref(),
+            Some("https://example.com/and")
+        );
+
+        // rules without a short message fall back to an empty one
+        let suggestions = rules(rule("long", "And", 3, 3)).apply(&tokens, &tokenizer);
+        assert_eq!(suggestions[0].short_message, "");
+        assert_eq!(suggestions[0].url, None);
+    }
+
+    #[test]
+    fn suggestions_can_be_filtered_by_severity() {
+        let tokenizer = Tokenizer::default();
+        let mut rules = Rules {
+            rules: vec![rule("inside", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+        let options = |min_severity| ApplyOptions {
+            min_severity: Some(min_severity),
+            collect_suppressed: true,
+            ..ApplyOptions::default()
+        };
+
+        rules.rules[0].severity = Severity::from_category_type(Some("style"));
+        assert_eq!(rules.apply(&tokens, &tokenizer)[0].severity, Severity::Hint);
+
+        let output = rules.apply_with_options(&tokens, &tokenizer, &options(Severity::Hint));
+        assert_eq!(output.active.len(), 1);
+        let output = rules.apply_with_options(&tokens, &tokenizer, &options(Severity::Warning));
+        assert!(output.active.is_empty());
+        assert_eq!(output.suppressed[0].1, SuppressionReason::Severity);
+
+        let mut overrides = HashMap::new();
+        overrides.insert("TEST/inside/0".to_owned(), Severity::Error);
+        rules.set_severities(&overrides).unwrap();
+        let output = rules.apply_with_options(&tokens, &tokenizer, &options(Severity::Error));
+        assert_eq!(output.active[0].severity, Severity::Error);
+
+        overrides.insert("TEST/outside/0".to_owned(), Severity::Hint);
+        assert!(matches!(
+            rules.set_severities(&overrides),
+            Err(Error::UnknownRuleIds(ids)) if ids == vec!["TEST/outside/0".to_owned()]
+        ));
+        assert_eq!(rules.rules[0].severity(), Severity::Error);
+    }
+
+    #[test]
+    fn timing_is_only_collected_if_requested() {
+        let tokenizer = Tokenizer::default();
+        let rules = Rules {
+            rules: vec![rule("inside", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+
+        let output = rules.apply_with_options(&tokens, &tokenizer, &ApplyOptions::default());
+        assert!(output.timing.is_none());
+
+        let options = ApplyOptions {
+            timing: true,
+            ..ApplyOptions::default()
+        };
+        let mut output = rules.apply_with_options(&tokens, &tokenizer, &options);
+        let timing = output.timing.unwrap();
+        assert_eq!((timing.tokenize, timing.disambiguate), (0, 0));
+        assert_eq!(timing.rules, timing.total);
+
+        let other = rules.apply_with_options(&tokens, &tokenizer, &options);
+        let other_timing = other.timing.unwrap();
+        output.append(other, 0, 0);
+        assert_eq!(
+            output.timing.unwrap().rules,
+            timing.rules + other_timing.rules
+        );
+    }
+
+    #[test]
+    fn document_budget_never_splits_sentences() {
+        let options = ApplyOptions {
+            max_sentences: Some(3),
+            max_chars: Some(10),
+            ..ApplyOptions::default()
+        };
+        let mut budget = DocumentBudget::new(&options);
+
+        assert!(budget.take(4));
+        assert!(!budget.take(7));
+        // a sentence which does not fit leaves the budget unchanged
+        assert!(budget.take(6));
+        assert!(!budget.take(1));
+
+        let mut budget = DocumentBudget::new(&options);
+        assert!(budget.take(0) && budget.take(0) && budget.take(0));
+        assert!(!budget.take(0));
+
+        let mut budget = DocumentBudget::new(&ApplyOptions::default());
+        assert!(budget.take(usize::MAX));
+    }
+
+    #[test]
+    fn reports_what_was_checked() {
+        let tokenizer = Tokenizer::default();
+        let mut rules = Rules {
+            rules: vec![rule("inside", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let mut tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+        let n_chars = tokens[0].sentence.chars().count();
+        let options = ApplyOptions {
+            report: true,
+            ..ApplyOptions::default()
+        };
+
+        let report = rules
+            .apply_with_options(&tokens, &tokenizer, &options)
+            .report
+            .unwrap();
+        assert!(report.is_complete());
+        assert_eq!(report.sentences[0].char_span, (0, n_chars));
+
+        tokens[6].oversized = true;
+        let mut output = ApplyOutput::default();
+        output.append(
+            rules.apply_with_options(&tokens, &tokenizer, &options),
+            10,
+            10,
+        );
+        let report = output.report.unwrap();
+        assert!(!report.is_complete());
+        assert_eq!(report.sentences[0].char_span, (10, n_chars + 10));
+        assert_eq!(
+            report.sentences[0].disposition,
+            SentenceDisposition::Truncated {
+                oversized_tokens: 1
+            }
+        );
+
+        rules.rules[0].disable();
+        let report = rules
+            .apply_with_options(&tokens, &tokenizer, &options)
+            .report
+            .unwrap();
+        assert_eq!(
+            report.sentences[0].disposition,
+            SentenceDisposition::Skipped {
+                reason: SkipReason::NoActiveRules
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&report.sentences[0]).unwrap(),
+            serde_json::json!({
+                "char_span": [0, n_chars],
+                "disposition": "skipped",
+                "reason": "no_active_rules"
+            })
+        );
+
+        // no report unless requested
+        assert!(rules
+            .apply_with_options(&tokens, &tokenizer, &ApplyOptions::default())
+            .report
+            .is_none());
+    }
+
+    #[test]
+    fn memory_budget_drops_disabled_rules() {
+        let mut rules = Rules {
+            rules: vec![rule("kept", "And", 3, 3), rule("dropped", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        rules.rules[1].disable();
+
+        let footprint = rules.memory_footprint();
+        let enabled_size = footprint.get("rules").unwrap();
+        assert!(footprint.get("disabled_rules").unwrap() > 8);
+
+        // fits already, nothing is dropped
+        rules.apply_memory_budget(footprint.total()).unwrap();
+        assert_eq!(rules.rules().len(), 2);
+
+        assert!(rules.apply_memory_budget(enabled_size - 1).is_err());
+        assert_eq!(rules.rules().len(), 1);
+        assert_eq!(rules.rules()[0].id().to_string(), "TEST/kept/0");
+
+        let footprint = rules.apply_memory_budget(enabled_size + 8).unwrap();
+        assert!(footprint.total() <= enabled_size + 8);
+    }
+
+    #[test]
+    fn rules_can_be_toggled_by_id() {
+        let tokenizer = Tokenizer::default();
+        let mut rules = Rules {
+            rules: vec![rule("inside", "And", 3, 3), rule("after", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+        let enabled =
+            |rules: &Rules| -> Vec<_> { rules.rules().iter().map(Rule::enabled).collect() };
+
+        rules.disable(&["INSIDE"]).unwrap();
+        assert_eq!(enabled(&rules), vec![false, true]);
+        assert_eq!(rules.apply(&tokens, &tokenizer)[0].source, "TEST/after/0");
+
+        rules.disable(&["test"]).unwrap();
+        assert!(rules.apply(&tokens, &tokenizer).is_empty());
+
+        rules.enable(&["TEST/inside/0", "test/after"]).unwrap();
+        assert_eq!(enabled(&rules), vec![true, true]);
+
+        // unknown IDs leave the rule set unchanged
+        assert!(matches!(
+            rules.disable(&["inside", "insdie"]),
+            Err(Error::NoMatchingRules { id, close_matches })
+                if id == "insdie" && close_matches == vec!["TEST/inside".to_owned()]
+        ));
+        assert_eq!(enabled(&rules), vec![true, true]);
+    }
+
+    #[test]
+    fn categories_can_be_toggled() {
+        let tokenizer = Tokenizer::default();
+        let mut style = rule("wordy", "And", 3, 3);
+        style.id = Category::new("STYLE").join("wordy").join(0);
+        let mut rules = Rules {
+            rules: vec![style, rule("inside", "And", 3, 3)],
+            options: RulesOptions::default(),
+            info: ModelInfo::default(),
+            toggles: Toggles::default(),
+        };
+        let tokens = tokens("It ended . - and then it began .", tokenizer.tagger());
+        let enabled = |rules: &Rules| -> Vec<_> {
+            rules
+                .rules()
+                .iter()
+                .map(|rule| rules.is_enabled(rule))
+                .collect()
+        };
+
+        rules.disable_category("style").unwrap();
+        assert_eq!(enabled(&rules), vec![false, true]);
+        assert_eq!(rules.apply(&tokens, &tokenizer)[0].source, "TEST/inside/0");
+
+        // an explicitly enabled rule wins over its disabled category ...
+        rules.enable(&["wordy"]).unwrap();
+        assert_eq!(enabled(&rules), vec![true, true]);
+
+        // ... until it is disabled again
+        rules.disable(&["wordy"]).unwrap();
+        rules.enable_category("STYLE").unwrap();
+        assert_eq!(enabled(&rules), vec![false, true]);
+
+        assert!(matches!(
+            rules.disable_category("styel"),
+            Err(Error::NoMatchingRules { close_matches, .. }) if close_matches == vec!["STYLE".to_owned()]
+        ));
     }
 }
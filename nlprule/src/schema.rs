@@ -0,0 +1,115 @@
+//! The data structures which nlprule serializes to JSON, collected in one place so the JSON outputs of the library,
+//! the binaries and the Python bindings can not drift apart.
+//!
+//! # Compatibility
+//! Every JSON document nlprule outputs is wrapped in [Versioned] i. e. it has a top-level `schema_version` field
+//! which is [SCHEMA_VERSION] at the time it was written. Within one schema version, the schema only evolves additively:
+//! - Fields may be added. Readers must ignore fields they do not know.
+//! - Fields are never renamed or removed and never change their type or meaning.
+//! - Enum values (e. g. the `disposition` of a [SentenceReport]) may be added.
+//!
+//! Any other change increments [SCHEMA_VERSION]. The golden files in `tests/schema` contain one document per structure.
+//! The tests of this module check that each of them can still be read and is written back unchanged, so renaming or
+//! removing a field fails the tests.
+
+use serde::{Deserialize, Serialize};
+
+pub use crate::bench::{BenchConfig, BenchReport, EnvironmentInfo, StageReport};
+pub use crate::rules::{
+    CheckReport, RulesConfig, RulesOptions, SentenceDisposition, SentenceReport, SkipReason,
+};
+pub use crate::types::{InlineEdit, ModelInfo, SegmentType, Suggestion};
+
+/// The version of the JSON schema. See the [module documentation](self) for the compatibility policy.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A JSON document with the [SCHEMA_VERSION] it was written with.
+/// The fields of the data are flattened into the document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    /// The schema version the document was written with.
+    pub schema_version: u32,
+    /// The data of the document.
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    /// Wraps the data with the current [SCHEMA_VERSION].
+    pub fn new(data: T) -> Self {
+        Versioned {
+            schema_version: SCHEMA_VERSION,
+            data,
+        }
+    }
+}
+
+/// The result of running the tests of the rules, written by the `test` binary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestReport {
+    /// The language code of the rules.
+    pub language: String,
+    /// The number of rules which were run.
+    pub runnable: usize,
+    /// The number of rules whose tests pass.
+    pub passing: usize,
+    /// The IDs of the rules whose tests fail.
+    pub failing: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use serde_json::Value;
+
+    /// Whether everything in `golden` is also in `value`. Objects in `value` may have additional fields.
+    fn is_superset(value: &Value, golden: &Value) -> bool {
+        match (value, golden) {
+            (Value::Object(value), Value::Object(golden)) => golden.iter().all(
+                |(key, golden)| matches!(value.get(key), Some(value) if is_superset(value, golden)),
+            ),
+            (Value::Array(value), Value::Array(golden)) => {
+                value.len() == golden.len()
+                    && value.iter().zip(golden).all(|(x, y)| is_superset(x, y))
+            }
+            (value, golden) => value == golden,
+        }
+    }
+
+    fn check_golden<T: Serialize + DeserializeOwned>(golden: &str) {
+        let golden: Value = serde_json::from_str(golden).unwrap();
+        let document: Versioned<T> = serde_json::from_value(golden.clone()).unwrap();
+
+        assert_eq!(document.schema_version, SCHEMA_VERSION);
+        assert!(
+            is_superset(&serde_json::to_value(&document).unwrap(), &golden),
+            "{}",
+            serde_json::to_string_pretty(&document).unwrap()
+        );
+    }
+
+    #[test]
+    fn golden_files_roundtrip() {
+        check_golden::<Suggestion>(include_str!("../tests/schema/suggestion.json"));
+        check_golden::<CheckReport>(include_str!("../tests/schema/check_report.json"));
+        check_golden::<RulesConfig>(include_str!("../tests/schema/rules_config.json"));
+        check_golden::<BenchReport>(include_str!("../tests/schema/bench_report.json"));
+        check_golden::<TestReport>(include_str!("../tests/schema/test_report.json"));
+    }
+
+    #[test]
+    fn superset_allows_additions_only() {
+        let golden = serde_json::json!({"a": 1, "b": [{"c": "x"}]});
+
+        assert!(is_superset(
+            &serde_json::json!({"a": 1, "b": [{"c": "x", "d": 2}], "e": null}),
+            &golden
+        ));
+        assert!(!is_superset(&serde_json::json!({"a": 1}), &golden));
+        assert!(!is_superset(
+            &serde_json::json!({"a": 1, "b": [{"c": "y"}]}),
+            &golden
+        ));
+    }
+}
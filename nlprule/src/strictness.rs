@@ -0,0 +1,144 @@
+//! Controls what happens when nlprule silently degrades its behavior e. g. skips rules which need a chunker
+//! if the tokenizer has none. By default, degradations are silent which is right for production
+//! but can hide problems during development. See [Strictness].
+
+use crate::Error;
+use log::warn;
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+};
+
+/// How to handle degraded behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Strictness {
+    /// Degrade silently.
+    #[default]
+    Lenient,
+    /// Degrade, but log a warning the first time each kind of [Degradation] occurs in the process.
+    Warn,
+    /// Return an [Error::Degraded] instead of degrading.
+    Strict,
+}
+
+impl Strictness {
+    /// The strictness to use where errors can not be returned. Strict mode warns instead.
+    pub(crate) fn infallible(self) -> Self {
+        match self {
+            Strictness::Strict => Strictness::Warn,
+            x => x,
+        }
+    }
+}
+
+impl std::str::FromStr for Strictness {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "lenient" => Strictness::Lenient,
+            "warn" => Strictness::Warn,
+            "strict" => Strictness::Strict,
+            x => return Err(format!("unknown strictness '{}'", x)),
+        })
+    }
+}
+
+/// A kind of degraded behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Degradation {
+    /// Active rules need chunks but the tokenizer has no chunker, so these rules can not match.
+    MissingChunker,
+    /// Tokens are too long to be tagged and checked.
+    /// See [TokenizerOptions::max_token_length](crate::tokenizer::TokenizerOptions::max_token_length).
+    OversizedTokens,
+    /// The text of tokens was normalized e. g. invisible characters were removed, so rules see different text than the input.
+    NormalizedText,
+}
+
+impl Degradation {
+    const COUNT: usize = 3;
+}
+
+impl fmt::Display for Degradation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Degradation::MissingChunker => {
+                "rules which need chunks are skipped because the tokenizer has no chunker"
+            }
+            Degradation::OversizedTokens => "tokens are too long to be tagged and checked",
+            Degradation::NormalizedText => "the text of tokens was normalized",
+        };
+
+        write!(f, "{}", description)
+    }
+}
+
+static STRICTNESS: AtomicU8 = AtomicU8::new(0);
+static WARNED: [AtomicBool; Degradation::COUNT] = [
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+];
+
+/// Sets the process-wide strictness. Can be overridden with [ApplyOptions::strictness](crate::rules::ApplyOptions::strictness).
+pub fn set_strictness(strictness: Strictness) {
+    STRICTNESS.store(strictness as u8, Ordering::Relaxed);
+}
+
+/// Gets the process-wide strictness. [Strictness::Lenient] by default.
+pub fn strictness() -> Strictness {
+    match STRICTNESS.load(Ordering::Relaxed) {
+        0 => Strictness::Lenient,
+        1 => Strictness::Warn,
+        _ => Strictness::Strict,
+    }
+}
+
+/// Handles the occurrence of a degradation. Every place where behavior is degraded must go through this function.
+pub(crate) fn degrade(kind: Degradation, strictness: Strictness) -> Result<(), Error> {
+    match strictness {
+        Strictness::Lenient => Ok(()),
+        Strictness::Warn => {
+            if !WARNED[kind as usize].swap(true, Ordering::Relaxed) {
+                warn!(
+                    "{}. This is only logged once, see `nlprule::strictness`.",
+                    kind
+                );
+            }
+            Ok(())
+        }
+        Strictness::Strict => Err(Error::Degraded(kind)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degradations_depend_on_strictness() {
+        // warnings are global, so use a kind which no other test degrades with
+        let kind = Degradation::MissingChunker;
+
+        assert!(degrade(kind, Strictness::Lenient).is_ok());
+        assert!(!WARNED[kind as usize].load(Ordering::Relaxed));
+
+        assert!(degrade(kind, Strictness::Warn).is_ok());
+        assert!(WARNED[kind as usize].load(Ordering::Relaxed));
+        // only the first occurrence is logged
+        assert!(degrade(kind, Strictness::Warn).is_ok());
+
+        assert!(matches!(
+            degrade(kind, Strictness::Strict),
+            Err(Error::Degraded(Degradation::MissingChunker))
+        ));
+        assert_eq!(Strictness::Strict.infallible(), Strictness::Warn);
+    }
+
+    #[test]
+    fn strictness_can_be_parsed() {
+        assert_eq!("warn".parse(), Ok(Strictness::Warn));
+        assert!("pedantic".parse::<Strictness>().is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! Assertions on the suggestions of a rule set for tests of nlprule and of crates using it.
+//! Only available with the `test-util` feature. See [assert_suggests][crate::assert_suggests] and [assert_clean][crate::assert_clean].
+
+use crate::{types::Suggestion, Rules, Tokenizer};
+
+fn format_suggestions(suggestions: &[Suggestion]) -> String {
+    if suggestions.is_empty() {
+        return "  (no suggestions)".into();
+    }
+
+    suggestions
+        .iter()
+        .map(|x| {
+            format!(
+                "  {} ({}..{}): {:?} - {}",
+                x.source,
+                x.span.char().start,
+                x.span.char().end,
+                x.replacements,
+                x.message
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Panics if no suggestion for `text` offers all of the `replacements`.
+/// If `replacements` is empty, panics if there is no suggestion at all. See [assert_suggests][crate::assert_suggests].
+#[track_caller]
+pub fn check_suggests(rules: &Rules, tokenizer: &Tokenizer, text: &str, replacements: &[&str]) {
+    let suggestions = rules.suggest(text, tokenizer);
+
+    let is_match = |suggestion: &Suggestion| {
+        replacements
+            .iter()
+            .all(|replacement| suggestion.replacements.iter().any(|x| x == replacement))
+    };
+
+    if !suggestions.iter().any(is_match) {
+        panic!(
+            "expected a suggestion with the replacements {:?} for {:?}, got:\n{}",
+            replacements,
+            text,
+            format_suggestions(&suggestions)
+        );
+    }
+}
+
+/// Panics if there is any suggestion for `text`. See [assert_clean][crate::assert_clean].
+#[track_caller]
+pub fn check_clean(rules: &Rules, tokenizer: &Tokenizer, text: &str) {
+    let suggestions = rules.suggest(text, tokenizer);
+
+    if !suggestions.is_empty() {
+        panic!(
+            "expected no suggestions for {:?}, got:\n{}",
+            text,
+            format_suggestions(&suggestions)
+        );
+    }
+}
+
+/// Asserts that the rules produce a suggestion for a text which offers all of the given replacements.
+/// With an empty list of replacements, asserts that there is any suggestion.
+/// The failure message includes all suggestions for the text.
+///
+/// ```ignore
+/// assert_suggests!(rules, tokenizer, "I can due his homework.", ["do"]);
+/// ```
+#[macro_export]
+macro_rules! assert_suggests {
+    ($rules:expr, $tokenizer:expr, $text:expr, [$($replacement:expr),* $(,)?] $(,)?) => {
+        $crate::test_util::check_suggests(&$rules, &$tokenizer, $text, &[$($replacement),*])
+    };
+}
+
+/// Asserts that the rules produce no suggestions for a text.
+/// The failure message includes all suggestions for the text.
+///
+/// ```ignore
+/// assert_clean!(rules, tokenizer, "I can do his homework.");
+/// ```
+#[macro_export]
+macro_rules! assert_clean {
+    ($rules:expr, $tokenizer:expr, $text:expr $(,)?) => {
+        $crate::test_util::check_clean(&$rules, &$tokenizer, $text)
+    };
+}
@@ -5,6 +5,8 @@
 //! [DisambiguationRule][crate::rule::DisambiguationRule]s.
 
 use crate::{
+    bench::Timing,
+    compression::decompress_reader,
     rule::id::{Index, Selector},
     types::*,
     utils::{parallelism::MaybeParallelRefIterator, regex::Regex},
@@ -13,12 +15,18 @@ use crate::{
 use fs_err::File;
 use serde::{Deserialize, Serialize};
 use std::{
-    io::{BufReader, Read},
+    borrow::Cow,
+    cmp::Reverse,
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Read, Write},
+    ops::Range,
     path::Path,
     sync::Arc,
+    time::Instant,
 };
 
 pub mod chunk;
+pub mod export;
 pub mod multiword;
 pub mod tag;
 
@@ -62,8 +70,133 @@ pub fn finalize(tokens: Vec<DisambiguatedToken>) -> Vec<Token> {
     finalized
 }
 
+/// Reconstructs the input text of the tokens from the start of the first to the end of the last token.
+/// Uses the [original text][Token::original_text] of the tokens, so normalization of the token text
+/// (e. g. removed [invisible chars][TokenizerOptions::invisible_chars] or
+/// [rejoined hyphenation][TokenizerOptions::rejoin_hyphenation]) is not visible in the output.
+/// The text between tokens is taken from the sentence. The tokens must be from one sentence and in order.
+pub fn detokenize<'a, 't: 'a>(tokens: impl IntoIterator<Item = &'a Token<'t>>) -> String {
+    let mut output = String::new();
+    let mut prev_end = None;
+
+    for token in tokens {
+        let start = token.span.byte().start;
+        output.push_str(&token.sentence[prev_end.unwrap_or(start)..start]);
+        output.push_str(token.original_text);
+        prev_end = Some(token.span.byte().end);
+    }
+
+    output
+}
+
 /// Options for a tokenizer.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenizerOptions {
+    /// Tokens with more chars than this are marked as [oversized][Token::oversized] e. g. long URLs or minified code.
+    pub max_token_length: usize,
+    /// Characters which are invisible in rendered text e. g. soft hyphens in documents exported from DTP tools.
+    /// They are stripped from the text of tokens for tagging and rule matching but spans and the original text keep them.
+    /// Defaults to the soft hyphen (U+00AD) and the zero-width space, non-joiner and joiner (U+200B - U+200D).
+    pub invisible_chars: Vec<char>,
+    /// Words which are known in addition to the words in the tagger dictionary e. g. product names.
+    /// See [Tokenizer::is_known].
+    pub known_words: DefaultHashSet<String>,
+    /// Whether to rejoin words which are hyphenated at a line break e. g. "infor-\nmation" in text extracted from PDFs.
+    /// The parts are rejoined into one token if the joined word is [known][Tokenizer::is_known] or if neither part is known,
+    /// so compounds like "well-\nknown" stay separate. The token keeps the span and original text of the hyphenated word.
+    /// See [CorrectOptions::repair_hyphenation][crate::rules::CorrectOptions::repair_hyphenation].
+    pub rejoin_hyphenation: bool,
+}
+
+impl Default for TokenizerOptions {
+    fn default() -> Self {
+        TokenizerOptions {
+            max_token_length: 200,
+            invisible_chars: vec!['\u{ad}', '\u{200b}', '\u{200c}', '\u{200d}'],
+            known_words: DefaultHashSet::default(),
+            rejoin_hyphenation: false,
+        }
+    }
+}
+
+impl TokenizerOptions {
+    /// Creates a builder for options starting from the defaults.
+    pub fn builder() -> TokenizerOptionsBuilder {
+        TokenizerOptionsBuilder::default()
+    }
+}
+
+/// Builds [TokenizerOptions] and checks that they are consistent. See [TokenizerOptions::builder].
+#[derive(Debug, Clone, Default)]
+pub struct TokenizerOptionsBuilder {
+    options: TokenizerOptions,
+}
+
+impl From<TokenizerOptions> for TokenizerOptionsBuilder {
+    /// Creates a builder starting from existing options e. g. to change some options of a tokenizer.
+    fn from(options: TokenizerOptions) -> Self {
+        TokenizerOptionsBuilder { options }
+    }
+}
+
+impl TokenizerOptionsBuilder {
+    /// Sets the maximum number of chars of a token. See [TokenizerOptions::max_token_length].
+    pub fn max_token_length(mut self, max_token_length: usize) -> Self {
+        self.options.max_token_length = max_token_length;
+        self
+    }
+
+    /// Sets the chars which are invisible for tagging and rule matching. See [TokenizerOptions::invisible_chars].
+    pub fn invisible_chars(mut self, invisible_chars: impl IntoIterator<Item = char>) -> Self {
+        self.options.invisible_chars = invisible_chars.into_iter().collect();
+        self
+    }
+
+    /// Sets the words which are known in addition to the dictionary. See [TokenizerOptions::known_words].
+    pub fn known_words<S: Into<String>>(
+        mut self,
+        known_words: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.options.known_words = known_words.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets whether to rejoin words hyphenated at line breaks. See [TokenizerOptions::rejoin_hyphenation].
+    pub fn rejoin_hyphenation(mut self, rejoin_hyphenation: bool) -> Self {
+        self.options.rejoin_hyphenation = rejoin_hyphenation;
+        self
+    }
+
+    /// Checks and returns the options.
+    ///
+    /// # Errors
+    /// - If the maximum token length is zero.
+    /// - If an invisible char is whitespace or splits tokens, since it would then not be part of any token.
+    pub fn build(self) -> Result<TokenizerOptions, Error> {
+        if self.options.max_token_length == 0 {
+            return Err(Error::InvalidOptions(
+                "the maximum token length must be positive".into(),
+            ));
+        }
+
+        if let Some(c) = self
+            .options
+            .invisible_chars
+            .iter()
+            .find(|c| c.is_whitespace() || crate::utils::splitting_chars().contains(**c))
+        {
+            return Err(Error::InvalidOptions(format!(
+                "invisible char {:?} splits tokens",
+                c
+            )));
+        }
+
+        Ok(self.options)
+    }
+}
+
+/// Language-dependent options for a tokenizer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct TokenizerLangOptions {
     /// Whether to allow errors while constructing the tokenizer.
     pub allow_errors: bool,
@@ -85,6 +218,10 @@ pub(crate) struct TokenizerLangOptions {
     /// Extra language-specific Regexes of which the matches will *not* be split into multiple tokens.
     #[serde(default)]
     pub extra_join_regexes: Vec<Regex>,
+    /// Extra language-specific strings which are never split into multiple tokens e. g. "e.g.".
+    /// Compiled into the [token exceptions][Tokenizer::add_token_exceptions] together with the exceptions of the build directory.
+    #[serde(default)]
+    pub token_exceptions: Vec<String>,
 }
 
 impl Default for TokenizerLangOptions {
@@ -97,10 +234,77 @@ impl Default for TokenizerLangOptions {
             known_failures: Vec::new(),
             extra_split_chars: Vec::new(),
             extra_join_regexes: Vec::new(),
+            token_exceptions: Vec::new(),
         }
     }
 }
 
+/// Strings which are never split into multiple tokens e. g. "e.g." or "C++". See [Tokenizer::add_token_exceptions].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct TokenExceptions {
+    // sorted by descending length, so the first match is the longest
+    by_first_char: DefaultHashMap<char, Vec<String>>,
+}
+
+impl TokenExceptions {
+    fn insert(&mut self, exception: &str) {
+        let first = match exception.chars().next() {
+            Some(first) => first,
+            None => return,
+        };
+
+        let exceptions = self.by_first_char.entry(first).or_default();
+        if !exceptions.iter().any(|x| x == exception) {
+            exceptions.push(exception.to_owned());
+            exceptions.sort_by_key(|x| Reverse(x.len()));
+        }
+    }
+
+    /// Finds the byte ranges of the exceptions in the text. A match must start and end at a token boundary
+    /// i. e. next to a char on which text is split or at the start / end of the text.
+    /// Overlaps are resolved in favor of the leftmost, then the longest match.
+    fn find_iter(&self, text: &str, split_char: impl Fn(char) -> bool) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        if self.by_first_char.is_empty() {
+            return ranges;
+        }
+
+        let mut prev = None;
+        let mut min_start = 0;
+
+        for (i, c) in text.char_indices() {
+            let starts_token = prev.map(&split_char).unwrap_or(true) || split_char(c);
+            prev = Some(c);
+
+            if i < min_start || !starts_token {
+                continue;
+            }
+
+            let exceptions = match self.by_first_char.get(&c) {
+                Some(exceptions) => exceptions,
+                None => continue,
+            };
+
+            let found = exceptions.iter().find(|exception| {
+                text[i..].starts_with(exception.as_str())
+                    && (exception.ends_with(&split_char)
+                        || text[i + exception.len()..]
+                            .chars()
+                            .next()
+                            .map(&split_char)
+                            .unwrap_or(true))
+            });
+
+            if let Some(exception) = found {
+                ranges.push(i..i + exception.len());
+                min_start = i + exception.len();
+            }
+        }
+
+        ranges
+    }
+}
+
 /// The complete Tokenizer doing tagging, chunking and disambiguation.
 #[derive(Serialize, Deserialize, Default)]
 pub struct Tokenizer {
@@ -110,6 +314,9 @@ pub struct Tokenizer {
     pub(crate) multiword_tagger: Option<MultiwordTagger>,
     pub(crate) tagger: Arc<Tagger>,
     pub(crate) lang_options: TokenizerLangOptions,
+    pub(crate) token_exceptions: TokenExceptions,
+    pub(crate) options: TokenizerOptions,
+    pub(crate) info: ModelInfo,
 }
 
 impl Tokenizer {
@@ -117,15 +324,138 @@ impl Tokenizer {
     ///
     /// # Errors
     /// - If the file can not be opened.
-    /// - If the file content can not be deserialized to a rules set.
+    /// - If the file content can not be deserialized to a tokenizer.
+    /// - If the binary was built with an incompatible version of nlprule.
     pub fn new<P: AsRef<Path>>(p: P) -> Result<Self, Error> {
-        let reader = BufReader::new(File::open(p.as_ref())?);
-        Ok(bincode::deserialize_from(reader)?)
+        Tokenizer::from_reader(File::open(p.as_ref())?)
+    }
+
+    /// Creates a new tokenizer with options. See [new][Tokenizer::new].
+    pub fn new_with_options<P: AsRef<Path>>(
+        p: P,
+        options: TokenizerOptions,
+    ) -> Result<Self, Error> {
+        let mut tokenizer = Tokenizer::new(p)?;
+
+        tokenizer.options = options;
+        Ok(tokenizer)
     }
 
-    /// Creates a new tokenizer from a reader.
+    /// Gets the options of this tokenizer.
+    pub fn options(&self) -> &TokenizerOptions {
+        &self.options
+    }
+
+    /// Gets the options of this tokenizer (mutable).
+    pub fn options_mut(&mut self) -> &mut TokenizerOptions {
+        &mut self.options
+    }
+
+    /// Adds strings which are never split into multiple tokens e. g. "C++" or "e.g.", in addition to the
+    /// exceptions compiled into the binary. Where exceptions overlap, the longest one is kept as one token.
+    /// An exception is only used where it starts and ends at a token boundary, so "C++" does not match in "XC++".
+    pub fn add_token_exceptions<S: AsRef<str>>(&mut self, exceptions: &[S]) {
+        for exception in exceptions {
+            self.token_exceptions.insert(exception.as_ref());
+        }
+    }
+
+    /// Adds entries of the form `(word, lemma, part-of-speech)` to the tagger dictionary of this tokenizer.
+    /// See [Tagger::add_words].
+    ///
+    /// # Errors
+    /// - If the tagger is shared, see [Tokenizer::tagger_mut].
+    /// - If a part-of-speech tag is not in the tag set of the binary.
+    pub fn add_words<S: AsRef<str>>(&mut self, entries: &[(S, S, S)]) -> Result<(), crate::Error> {
+        self.tagger_mut()?.add_words(entries)
+    }
+
+    /// Replaces the entries of a word in the tagger dictionary of this tokenizer. See [Tagger::override_tags].
+    ///
+    /// # Errors
+    /// - If the tagger is shared, see [Tokenizer::tagger_mut].
+    /// - If a part-of-speech tag is not in the tag set of the binary.
+    pub fn override_tags<S: AsRef<str>>(
+        &mut self,
+        word: &str,
+        tags: &[(S, S)],
+    ) -> Result<(), crate::Error> {
+        self.tagger_mut()?.override_tags(word, tags)
+    }
+
+    /// Removes all entries of a word from the tagger dictionary of this tokenizer. See [Tagger::remove_word].
+    /// Returns whether the word had any entries.
+    ///
+    /// # Errors
+    /// - If the tagger is shared, see [Tokenizer::tagger_mut].
+    pub fn remove_word(&mut self, word: &str) -> Result<bool, crate::Error> {
+        Ok(self.tagger_mut()?.remove_word(word))
+    }
+
+    /// Gets the version and provenance of the binary this tokenizer was loaded from.
+    pub fn info(&self) -> &ModelInfo {
+        &self.info
+    }
+
+    /// Estimates the memory used by the components of this tokenizer. See [MemoryFootprint].
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        let mut footprint = MemoryFootprint::default();
+
+        footprint.add("tagger", self.tagger.as_ref());
+        footprint.add("sentencizer", &self.sentencizer);
+        footprint.add("disambiguation", &self.rules);
+        if let Some(chunker) = &self.chunker {
+            footprint.add("chunker", chunker);
+        }
+        if let Some(multiword_tagger) = &self.multiword_tagger {
+            footprint.add("multiword_tagger", multiword_tagger);
+        }
+
+        footprint
+    }
+
+    /// Drops optional components until the estimated [memory footprint][Tokenizer::memory_footprint] is at most `bytes`.
+    /// The chunker is dropped first, then the multiword tagger. Without them, rules which depend on chunks
+    /// (e. g. noun phrases) or multiword tags can not match, other rules are not affected.
+    ///
+    /// # Errors
+    /// - If the tokenizer does not fit into the budget even without the optional components.
+    pub fn apply_memory_budget(&mut self, bytes: usize) -> Result<MemoryFootprint, Error> {
+        if self.memory_footprint().total() > bytes {
+            self.chunker = None;
+        }
+        if self.memory_footprint().total() > bytes {
+            self.multiword_tagger = None;
+        }
+
+        let footprint = self.memory_footprint();
+        if footprint.total() > bytes {
+            return Err(Error::MemoryBudgetExceeded {
+                budget: bytes,
+                required: footprint.total(),
+            });
+        }
+
+        Ok(footprint)
+    }
+
+    /// Creates a new tokenizer from a reader. See [new][Tokenizer::new].
+    /// The binary may be compressed, see the [compression][crate::compression] module.
     pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
-        Ok(bincode::deserialize_from(reader)?)
+        let mut reader = decompress_reader(BufReader::new(reader))?;
+        BinaryHeader::read(&mut reader, BinaryKind::Tokenizer)?;
+
+        let tokenizer: Tokenizer = bincode::deserialize_from(reader)?;
+        tokenizer.info.check_compatible()?;
+
+        Ok(tokenizer)
+    }
+
+    /// Writes the tokenizer as binary (uncompressed) which can be loaded with [from_reader][Tokenizer::from_reader].
+    pub fn to_writer<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        BinaryHeader::new(BinaryKind::Tokenizer, &self.info.language).write(&mut writer)?;
+        bincode::serialize_into(writer, self)?;
+        Ok(())
     }
 
     /// Gets all disambigation rules in the order they are applied.
@@ -138,6 +468,15 @@ impl Tokenizer {
         &self.tagger
     }
 
+    /// Gets the lexical tagger (mutable).
+    ///
+    /// # Errors
+    /// - If the tagger is shared i. e. a clone of the [Arc] returned by [Tokenizer::tagger] is alive.
+    ///   The tagger is never copied implicitly, so changes can not silently miss the other references.
+    pub fn tagger_mut(&mut self) -> Result<&mut Tagger, crate::Error> {
+        Arc::get_mut(&mut self.tagger).ok_or(crate::Error::SharedTagger)
+    }
+
     /// Gets the chunker if one exists.
     pub fn chunker(&self) -> &Option<Chunker> {
         &self.chunker
@@ -196,6 +535,143 @@ impl Tokenizer {
         self.disambiguate_up_to_id(tokens, None)
     }
 
+    /// Removes the [invisible chars][TokenizerOptions::invisible_chars] from a token.
+    fn strip_invisible<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        let invisible_chars = &self.options.invisible_chars;
+
+        if text.contains(|c| invisible_chars.contains(&c)) {
+            Cow::Owned(
+                text.chars()
+                    .filter(|c| !invisible_chars.contains(c))
+                    .collect(),
+            )
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    /// Removes the hyphen and line break from a word which is hyphenated at a line break e. g. "infor-\nmation".
+    /// See [TokenizerOptions::rejoin_hyphenation].
+    fn remove_hyphenation<'t>(&self, text: Cow<'t, str>) -> Cow<'t, str> {
+        if !self.options.rejoin_hyphenation || !text.contains('\n') {
+            return text;
+        }
+
+        Cow::Owned(
+            text.split('\n')
+                .map(|line| {
+                    let line = line.trim();
+                    line.strip_suffix('-').unwrap_or(line)
+                })
+                .collect(),
+        )
+    }
+
+    /// Finds words which are hyphenated at a line break and should be rejoined in the text.
+    /// Returns the byte range of each word including the hyphens and line breaks. A word hyphenated
+    /// over more than two lines (e. g. "ab-\ncd-\nef") is one range, so the ranges never overlap.
+    fn find_hyphenation(&self, text: &str) -> Vec<Range<usize>> {
+        let is_word_char =
+            |c: char| c.is_alphanumeric() || self.options.invisible_chars.contains(&c);
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+
+        for (i, _) in text.match_indices('-') {
+            let left_start = text[..i]
+                .char_indices()
+                .rev()
+                .take_while(|(_, c)| is_word_char(*c))
+                .last()
+                .map(|(i, _)| i);
+
+            // the hyphen must be followed by a line break, optionally surrounded by spaces
+            let is_space = |c: char| c == ' ' || c == '\t' || c == '\r';
+            let right = match text[i + 1..]
+                .trim_start_matches(is_space)
+                .strip_prefix('\n')
+            {
+                Some(right) => right.trim_start_matches(is_space),
+                None => continue,
+            };
+            let right_start = text.len() - right.len();
+            let right_len = right
+                .char_indices()
+                .find(|(_, c)| !is_word_char(*c))
+                .map_or(right.len(), |(i, _)| i);
+
+            let left_start = match left_start {
+                Some(left_start) if right_len > 0 => left_start,
+                _ => continue,
+            };
+            let left = &text[left_start..i];
+            let right = &text[right_start..right_start + right_len];
+
+            let joined = format!("{}{}", left, right);
+            if self.is_known(&joined) || (!self.is_known(left) && !self.is_known(right)) {
+                let range = left_start..right_start + right_len;
+
+                match ranges.last_mut() {
+                    // the left part is the right part of the previous hyphenation
+                    Some(prev) if prev.end > range.start => prev.end = range.end,
+                    _ => ranges.push(range),
+                }
+            }
+        }
+
+        ranges
+    }
+
+    /// Whether the word is known i. e. it is in the tagger dictionary, it is one of the
+    /// [known words][TokenizerOptions::known_words] or it has no letters (e. g. numbers and punctuation).
+    /// Approximates whether a spellchecker would accept the word.
+    pub fn is_known(&self, word: &str) -> bool {
+        let word = self.strip_invisible(word);
+
+        !self.tagger.get_tags(&word).is_empty() || self.is_known_without_tags(&word)
+    }
+
+    /// Whether all tokens of the text are known. The text is split, normalized and tagged like a sentence
+    /// when tokenizing, so this agrees with [IncompleteToken::is_known] of the tokens.
+    pub(crate) fn all_known(&self, text: &str) -> bool {
+        self.get_token_strs(text).into_iter().all(|token_str| {
+            let text = self.token_text(token_str);
+            let oversized = token_str.trim().chars().count() > self.options.max_token_length;
+
+            text.is_empty() || self.tag_token(&text, oversized, false, None).1
+        })
+    }
+
+    fn is_known_without_tags(&self, word: &str) -> bool {
+        !word.chars().any(char::is_alphabetic) || self.options.known_words.contains(word)
+    }
+
+    /// The text a token is tagged and matched with i. e. without surrounding whitespace,
+    /// invisible chars and hyphenation at line breaks.
+    fn token_text<'t>(&self, token_str: &'t str) -> Cow<'t, str> {
+        self.remove_hyphenation(self.strip_invisible(token_str.trim()))
+    }
+
+    /// The tags of a token and whether it is known. Oversized tokens are not tagged.
+    fn tag_token(
+        &self,
+        text: &str,
+        oversized: bool,
+        is_sentence_start: bool,
+        use_compound_split_heuristic: Option<bool>,
+    ) -> (Vec<WordData<'_>>, bool) {
+        let tags = if oversized {
+            Vec::new()
+        } else {
+            self.tagger.get_tags_with_options(
+                text,
+                if is_sentence_start { Some(true) } else { None },
+                use_compound_split_heuristic,
+            )
+        };
+        let is_known = !tags.is_empty() || self.is_known_without_tags(text);
+
+        (tags, is_known)
+    }
+
     fn get_token_strs<'t>(&self, text: &'t str) -> Vec<&'t str> {
         let mut tokens = Vec::new();
 
@@ -204,7 +680,12 @@ impl Tokenizer {
             let mut tokens = Vec::new();
             for pretoken in split(text, split_char) {
                 // if the token is in the dictionary, we add it right away
-                if self.tagger.id_word(pretoken.into()).1.is_some() {
+                if self
+                    .tagger
+                    .id_word(self.strip_invisible(pretoken))
+                    .1
+                    .is_some()
+                {
                     tokens.push(pretoken);
                 } else {
                     // otherwise, potentially split it again with `extra_split_chars` e. g. "-"
@@ -219,6 +700,24 @@ impl Tokenizer {
         let mut joined_mask = vec![false; text.len()];
         let mut joins = Vec::new();
 
+        if self.options.rejoin_hyphenation {
+            for range in self.find_hyphenation(text) {
+                joined_mask[range.clone()]
+                    .iter_mut()
+                    .for_each(|x| *x = true);
+                joins.push(range);
+            }
+        }
+
+        for range in self.token_exceptions.find_iter(text, split_char) {
+            if !joined_mask[range.clone()].iter().any(|x| *x) {
+                joined_mask[range.clone()]
+                    .iter_mut()
+                    .for_each(|x| *x = true);
+                joins.push(range);
+            }
+        }
+
         for regex in self.lang_options.extra_join_regexes.iter() {
             for mat in regex.find_iter(text) {
                 if !joined_mask[mat.start()..mat.end()].iter().any(|x| *x) {
@@ -246,6 +745,18 @@ impl Tokenizer {
     /// Tokenize the given sentence. This applies chunking and tagging, but does not do disambiguation.
     // NB: this is not public because it could be easily misused by passing a text instead of one sentence.
     pub(crate) fn tokenize<'t>(&'t self, sentence: &'t str) -> Vec<IncompleteToken<'t>> {
+        self.tokenize_with_start(sentence, true, None)
+    }
+
+    /// Tokenizes a sentence. If `is_sentence` is false, the first token is not treated as the start
+    /// of a sentence i. e. it is tagged with its own casing only.
+    /// `use_compound_split_heuristic` overrides the option of the tagger if set.
+    fn tokenize_with_start<'t>(
+        &'t self,
+        sentence: &'t str,
+        is_sentence: bool,
+        use_compound_split_heuristic: Option<bool>,
+    ) -> Vec<IncompleteToken<'t>> {
         let mut current_char = 0;
         let token_strs = self.get_token_strs(sentence);
         let mut tokens: Vec<_> = token_strs
@@ -257,25 +768,28 @@ impl Tokenizer {
                 current_char += x.chars().count();
 
                 let byte_start = ptr - sentence.as_ptr() as usize;
-                let trimmed = x.trim();
+                let text = self.token_text(x);
 
-                let is_sentence_start = i == 0;
+                let is_sentence_start = is_sentence && i == 0;
                 let is_sentence_end = i == token_strs.len() - 1;
+                let oversized = x.trim().chars().count() > self.options.max_token_length;
+
+                let (tags, is_known) = self.tag_token(
+                    &text,
+                    oversized,
+                    is_sentence_start,
+                    use_compound_split_heuristic,
+                );
 
                 IncompleteToken {
-                    word: Word::new_with_tags(
-                        self.tagger.id_word(trimmed.into()),
-                        self.tagger.get_tags_with_options(
-                            trimmed,
-                            if is_sentence_start { Some(true) } else { None },
-                            None,
-                        ),
-                    ),
-                    char_span: (char_start, current_char),
-                    byte_span: (byte_start, byte_start + x.len()),
+                    word: Word::new_with_tags(self.tagger.id_word(text.clone()), tags),
+                    original_text: x,
+                    span: Span::new(char_start..current_char, byte_start..byte_start + x.len()),
                     is_sentence_end,
                     has_space_before: sentence[..byte_start].ends_with(char::is_whitespace),
                     chunks: Vec::new(),
+                    oversized,
+                    is_known,
                     multiword_data: None,
                     sentence,
                     tagger: self.tagger.as_ref(),
@@ -309,11 +823,622 @@ impl Tokenizer {
             .collect()
     }
 
+    /// Splits the text into sentences and returns the char span of each sentence.
+    /// The spans are contiguous and cover the whole text.
+    pub fn sentence_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        self.sentences(text)
+            .into_iter()
+            .map(|(span, _)| (span.char().start, span.char().end))
+            .collect()
+    }
+
+    /// Splits the text into sentences and returns each sentence together with its span in the text.
+    /// The spans are contiguous and cover the whole text. See [Rules::apply_stream][crate::Rules::apply_stream].
+    pub fn sentences<'t>(&self, text: &'t str) -> Vec<(Span, &'t str)> {
+        let mut start = Span::default();
+
+        self.sentencizer
+            .split(text)
+            .map(|sentence| {
+                let span = Span::new(0..sentence.chars().count(), 0..sentence.len())
+                    .shift(start.char().end, start.byte().end);
+                start = span;
+                (span, sentence)
+            })
+            .collect()
+    }
+
+    /// Splits the text of a stream into sentences without reading it into memory completely, like [Tokenizer::sentences].
+    /// Yields each sentence together with its span in the whole stream. The spans are contiguous and cover the
+    /// whole stream. Fails if the stream can not be read or is not valid UTF-8.
+    /// See [Rules::apply_reader][crate::Rules::apply_reader].
+    ///
+    /// The stream is read line by line into a buffer of about [ReadSentences::DEFAULT_CHUNK_SIZE] bytes which is
+    /// then split into sentences. The last sentence of the buffer may continue in the stream, so it is kept
+    /// and split again together with the next lines.
+    pub fn read_sentences<R: BufRead>(&self, reader: R) -> ReadSentences<'_, R> {
+        ReadSentences {
+            tokenizer: self,
+            reader,
+            buffer: String::new(),
+            pending: VecDeque::new(),
+            end: Span::default(),
+            chunk_size: ReadSentences::<R>::DEFAULT_CHUNK_SIZE,
+            done: false,
+        }
+    }
+
     /// Applies the entire tokenization pipeline including sentencization, tagging, chunking and disambiguation.
     pub fn pipe<'t>(&'t self, text: &'t str) -> Vec<Vec<Token<'t>>> {
-        self.sentencize(text)
+        self.pipe_with_options(text, None)
+    }
+
+    /// Like [Tokenizer::pipe] with an override of the compound splitting heuristic,
+    /// see [Tokenizer::pipe_sentence_with_options].
+    pub fn pipe_with_options<'t>(
+        &'t self,
+        text: &'t str,
+        use_compound_split_heuristic: Option<bool>,
+    ) -> Vec<Vec<Token<'t>>> {
+        self.sentencizer
+            .split(text)
+            .map(|sentence| {
+                finalize(self.disambiguate(self.tokenize_with_start(
+                    sentence,
+                    true,
+                    use_compound_split_heuristic,
+                )))
+            })
+            .collect()
+    }
+
+    /// Applies the entire tokenization pipeline like [Tokenizer::pipe] and measures the time spent
+    /// in tokenization and disambiguation.
+    pub fn pipe_timed<'t>(&'t self, text: &'t str) -> (Vec<Vec<Token<'t>>>, Timing) {
+        let start = Instant::now();
+        let sentences = self.sentencize(text);
+        let tokenize = start.elapsed();
+
+        let sentences = sentences
             .into_iter()
             .map(|tokens| finalize(self.disambiguate(tokens)))
+            .collect();
+        let total = start.elapsed();
+
+        let timing = Timing {
+            tokenize: Timing::micros(tokenize),
+            disambiguate: Timing::micros(total - tokenize),
+            rules: 0,
+            total: Timing::micros(total),
+        };
+        (sentences, timing)
+    }
+
+    /// Applies the entire tokenization pipeline like [Tokenizer::pipe_sentence] and measures the time spent
+    /// in tokenization and disambiguation.
+    pub fn pipe_sentence_timed<'t>(&'t self, sentence: &'t str) -> (Vec<Token<'t>>, Timing) {
+        self.pipe_timed_with_start(sentence, true, None)
+    }
+
+    /// Like [Tokenizer::pipe_sentence_timed] with an override of the compound splitting heuristic,
+    /// see [Tokenizer::pipe_sentence_with_options].
+    pub fn pipe_sentence_timed_with_options<'t>(
+        &'t self,
+        sentence: &'t str,
+        use_compound_split_heuristic: Option<bool>,
+    ) -> (Vec<Token<'t>>, Timing) {
+        self.pipe_timed_with_start(sentence, true, use_compound_split_heuristic)
+    }
+
+    /// Like [Tokenizer::pipe_sentence_timed], but for a fragment. See [Tokenizer::pipe_fragment].
+    pub(crate) fn pipe_fragment_timed<'t>(&'t self, text: &'t str) -> (Vec<Token<'t>>, Timing) {
+        self.pipe_timed_with_start(text, false, None)
+    }
+
+    fn pipe_timed_with_start<'t>(
+        &'t self,
+        sentence: &'t str,
+        is_sentence: bool,
+        use_compound_split_heuristic: Option<bool>,
+    ) -> (Vec<Token<'t>>, Timing) {
+        let start = Instant::now();
+        let tokens = self.tokenize_with_start(sentence, is_sentence, use_compound_split_heuristic);
+        let tokenize = start.elapsed();
+
+        let tokens = finalize(self.disambiguate(tokens));
+        let total = start.elapsed();
+
+        let timing = Timing {
+            tokenize: Timing::micros(tokenize),
+            disambiguate: Timing::micros(total - tokenize),
+            rules: 0,
+            total: Timing::micros(total),
+        };
+        (tokens, timing)
+    }
+
+    /// Applies the entire tokenization pipeline to a text which is known to be exactly one sentence
+    /// e. g. because it was split by an external sentence splitter.
+    pub fn pipe_sentence<'t>(&'t self, sentence: &'t str) -> Vec<Token<'t>> {
+        finalize(self.disambiguate(self.tokenize(sentence)))
+    }
+
+    /// Like [Tokenizer::pipe_sentence] but overrides whether words are tagged with the compound splitting
+    /// heuristic for this call e. g. to turn it off for hashtags or identifiers. If `None`, the option of the
+    /// tagger is used. See [Tagger::use_compound_split_heuristic].
+    pub fn pipe_sentence_with_options<'t>(
+        &'t self,
+        sentence: &'t str,
+        use_compound_split_heuristic: Option<bool>,
+    ) -> Vec<Token<'t>> {
+        finalize(self.disambiguate(self.tokenize_with_start(
+            sentence,
+            true,
+            use_compound_split_heuristic,
+        )))
+    }
+
+    /// Applies the entire tokenization pipeline to many sentences at once, see [Tokenizer::pipe_sentence_with_options].
+    /// The sentences are processed in parallel unless disabled with the `NLPRULE_PARALLELISM` environment variable,
+    /// the number of threads is the one of the current rayon thread pool. The tokens of each sentence are at the
+    /// index of the sentence.
+    pub fn pipe_sentence_batch<'t>(
+        &'t self,
+        sentences: &[&'t str],
+        use_compound_split_heuristic: Option<bool>,
+    ) -> Vec<Vec<Token<'t>>> {
+        sentences
+            .maybe_par_iter()
+            .map(|sentence| self.pipe_sentence_with_options(sentence, use_compound_split_heuristic))
             .collect()
     }
+
+    /// Applies the entire tokenization pipeline to the text and writes the tokens to the `writer`
+    /// in a compact binary format. See [export] for the format and how to read it back.
+    pub fn tokenize_to_writer<W: Write>(&self, text: &str, writer: &mut W) -> io::Result<()> {
+        export::write_document(writer, text, &self.pipe(text))
+    }
+
+    /// Applies the tokenization pipeline to an isolated word or phrase e. g. a form field.
+    /// The text is not split into sentences and its first word is not treated as the start of a sentence.
+    pub fn pipe_fragment<'t>(&'t self, text: &'t str) -> Vec<Token<'t>> {
+        finalize(self.disambiguate(self.tokenize_with_start(text, false, None)))
+    }
+}
+
+/// An iterator over the sentences of a stream. See [Tokenizer::read_sentences].
+pub struct ReadSentences<'a, R> {
+    tokenizer: &'a Tokenizer,
+    reader: R,
+    buffer: String,
+    pending: VecDeque<(Span, String)>,
+    end: Span,
+    chunk_size: usize,
+    done: bool,
+}
+
+impl<'a, R: BufRead> ReadSentences<'a, R> {
+    /// The default size of the buffer which is split into sentences at once, in bytes.
+    pub const DEFAULT_CHUNK_SIZE: usize = 1 << 16;
+
+    /// Sets the size of the buffer which is split into sentences at once, in bytes. The buffer can be larger
+    /// e. g. if a line or sentence is longer.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Reads at least one line and then until the buffer is full, and splits the buffer into sentences.
+    fn fill(&mut self) -> Result<(), Error> {
+        loop {
+            if self.reader.read_line(&mut self.buffer)? == 0 {
+                self.done = true;
+                break;
+            }
+            if self.buffer.len() >= self.chunk_size {
+                break;
+            }
+        }
+
+        let mut sentences: Vec<&str> = self.tokenizer.sentencizer.split(&self.buffer).collect();
+        // the last sentence may continue in the stream
+        let rest = if self.done {
+            ""
+        } else {
+            sentences.pop().unwrap_or_default()
+        };
+
+        for sentence in sentences {
+            let span = Span::new(0..sentence.chars().count(), 0..sentence.len())
+                .shift(self.end.char().end, self.end.byte().end);
+            self.end = span;
+            self.pending.push_back((span, sentence.to_owned()));
+        }
+        self.buffer = rest.to_owned();
+
+        Ok(())
+    }
+}
+
+impl<'a, R: BufRead> Iterator for ReadSentences<'a, R> {
+    type Item = Result<(Span, String), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sentence) = self.pending.pop_front() {
+                return Some(Ok(sentence));
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(err) = self.fill() {
+                self.done = true;
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_sentences_cover_the_stream() {
+        let tokenizer = Tokenizer::default();
+        let text = "A first line.\nÄ second line!\n\nThe end, without newline";
+
+        for chunk_size in [1, 5, 16, ReadSentences::<&[u8]>::DEFAULT_CHUNK_SIZE] {
+            let sentences: Vec<_> = tokenizer
+                .read_sentences(text.as_bytes())
+                .with_chunk_size(chunk_size)
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+            let mut end = Span::default();
+            for (span, sentence) in &sentences {
+                assert_eq!(span.byte().start, end.byte().end);
+                assert_eq!(span.char().start, end.char().end);
+                assert_eq!(&text[span.byte().clone()], sentence);
+                end = *span;
+            }
+            assert_eq!(end.byte().end, text.len());
+            assert_eq!(end.char().end, text.chars().count());
+        }
+    }
+
+    #[test]
+    fn read_sentences_fail_on_invalid_utf8() {
+        let tokenizer = Tokenizer::default();
+        let mut sentences = tokenizer.read_sentences(&b"valid\n\xff\xfe\n"[..]);
+
+        assert!(sentences.any(|x| matches!(x, Err(Error::Io(_)))));
+        assert!(sentences.next().is_none());
+    }
+
+    #[test]
+    fn options_builder_validates() {
+        let options = TokenizerOptions::builder()
+            .max_token_length(50)
+            .invisible_chars(vec!['\u{ad}'])
+            .build()
+            .unwrap();
+        assert_eq!(options.max_token_length, 50);
+        assert_eq!(options.invisible_chars, vec!['\u{ad}']);
+
+        assert!(TokenizerOptions::builder()
+            .max_token_length(0)
+            .build()
+            .is_err());
+        assert!(TokenizerOptions::builder()
+            .invisible_chars(vec!['\u{200b}', ' '])
+            .build()
+            .is_err());
+        assert!(TokenizerOptions::builder()
+            .invisible_chars(vec!['.'])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn invisible_chars_are_transparent() {
+        let tokenizer = Tokenizer::default();
+
+        let sentence = "co\u{ad}operate and \u{200b} zero\u{200d}width";
+        let tokens = tokenizer.tokenize(sentence);
+        let texts: Vec<_> = tokens.iter().map(|x| x.word.text.as_ref()).collect();
+
+        assert_eq!(texts, vec!["cooperate", "and", "zerowidth"]);
+        assert_eq!(tokens[0].original_text, "co\u{ad}operate");
+        assert_eq!(tokens[0].span.char(), 0..10);
+        assert_eq!(tokens[2].span.char(), 17..27);
+    }
+
+    #[test]
+    fn whitespace_before_reproduces_the_sentence() {
+        let tokenizer = Tokenizer::default();
+
+        let sentence = " This  is\ta \n\n test ,\u{a0}ok.";
+        // the default tagger has no tags, so the tokens are not disambiguated
+        let tokens: Vec<Token> = tokenizer
+            .tokenize(sentence)
+            .into_iter()
+            .map(|x| Token {
+                word: x.word,
+                original_text: x.original_text,
+                span: x.span,
+                has_space_before: x.has_space_before,
+                chunks: x.chunks,
+                oversized: x.oversized,
+                is_known: x.is_known,
+                sentence: x.sentence,
+                tagger: x.tagger,
+            })
+            .collect();
+        let joined: String = tokens
+            .iter()
+            .map(|x| format!("{}{}", x.whitespace_before(), x.original_text))
+            .collect();
+
+        assert_eq!(joined, sentence);
+        assert!(tokens
+            .iter()
+            .all(|x| x.has_space_before != x.whitespace_before().is_empty()));
+        assert_eq!(tokens[3].whitespace_before(), " \n\n ");
+    }
+
+    #[test]
+    fn hyphenation_at_line_breaks_is_rejoined() {
+        let mut tokenizer = Tokenizer::default();
+        tokenizer.options_mut().known_words = ["well", "known", "e", "mail", "email"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+
+        let sentence = "The infor-\nmation is well-\nknown, send an e- \r\n mail.";
+        let texts = |tokenizer: &Tokenizer| -> Vec<String> {
+            tokenizer
+                .tokenize(sentence)
+                .iter()
+                .map(|x| x.word.text.as_ref().to_owned())
+                .collect()
+        };
+
+        assert_eq!(
+            texts(&tokenizer),
+            vec![
+                "The", "infor-", "mation", "is", "well-", "known", ",", "send", "an", "e-", "mail",
+                "."
+            ]
+        );
+
+        tokenizer.options_mut().rejoin_hyphenation = true;
+        assert_eq!(
+            texts(&tokenizer),
+            vec![
+                "The",
+                "information",
+                "is",
+                "well-",
+                "known",
+                ",",
+                "send",
+                "an",
+                "email",
+                "."
+            ]
+        );
+
+        let tokens = tokenizer.tokenize(sentence);
+        // spans and the original text are kept
+        assert_eq!(tokens[1].original_text, "infor-\nmation");
+        assert_eq!(tokens[1].span.char(), 4..17);
+        assert_eq!(tokens[2].span.char(), 18..20);
+        assert_eq!(tokens[8].original_text, "e- \r\n mail");
+    }
+
+    #[test]
+    fn hyphenation_over_multiple_lines_is_rejoined() {
+        let mut tokenizer = Tokenizer::default();
+        tokenizer.options_mut().rejoin_hyphenation = true;
+
+        let sentence = "The ab-\ncd-\nef is here.";
+        assert_eq!(tokenizer.find_hyphenation(sentence), vec![4..14]);
+
+        let tokens = &tokenizer.sentencize(sentence)[0];
+        let texts: Vec<_> = tokens.iter().map(|x| x.word.text.as_ref()).collect();
+        assert_eq!(texts, vec!["The", "abcdef", "is", "here", "."]);
+        assert_eq!(tokens[1].original_text, "ab-\ncd-\nef");
+        assert_eq!(tokens[2].span.char(), 15..17);
+    }
+
+    #[test]
+    fn detokenize_reproduces_normalized_tokens() {
+        let mut tokenizer = Tokenizer::default();
+        tokenizer.options_mut().rejoin_hyphenation = true;
+        tokenizer.options_mut().known_words =
+            ["information"].iter().map(|x| x.to_string()).collect();
+
+        let sentence = "The infor-\nmation is co\u{ad}operative  and \u{200b} fine.";
+        // the default tagger has no tags, so the tokens are not disambiguated
+        let tokens: Vec<Token> = tokenizer
+            .tokenize(sentence)
+            .into_iter()
+            .map(|x| Token {
+                word: x.word,
+                original_text: x.original_text,
+                span: x.span,
+                has_space_before: x.has_space_before,
+                chunks: x.chunks,
+                oversized: x.oversized,
+                is_known: x.is_known,
+                sentence: x.sentence,
+                tagger: x.tagger,
+            })
+            .collect();
+
+        let texts: Vec<_> = tokens.iter().map(|x| x.word.text.as_ref()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "The",
+                "information",
+                "is",
+                "cooperative",
+                "and",
+                "fine",
+                "."
+            ]
+        );
+        assert_eq!(detokenize(&tokens), sentence);
+        assert_eq!(
+            detokenize(&tokens[1..4]),
+            "infor-\nmation is co\u{ad}operative"
+        );
+        assert_eq!(detokenize(&tokens[..0]), "");
+    }
+
+    #[test]
+    fn token_exceptions_are_not_split() {
+        let mut tokenizer = Tokenizer::default();
+        let sentence = "Use C++, e.g. XC++ or (C++) etc.";
+        let texts = |tokenizer: &Tokenizer| -> Vec<String> {
+            tokenizer
+                .tokenize(sentence)
+                .iter()
+                .map(|x| x.original_text.to_owned())
+                .collect()
+        };
+
+        assert_eq!(
+            texts(&tokenizer),
+            vec![
+                "Use", "C", "+", "+", ",", "e", ".", "g", ".", "XC", "+", "+", "or", "(", "C", "+",
+                "+", ")", "etc", "."
+            ]
+        );
+
+        tokenizer.add_token_exceptions(&["C++", "e.g", "e.g.", "etc"]);
+        assert_eq!(
+            texts(&tokenizer),
+            vec!["Use", "C++", ",", "e.g.", "XC", "+", "+", "or", "(", "C++", ")", "etc", "."]
+        );
+
+        // spans still slice the original text
+        for token in tokenizer.tokenize(sentence) {
+            assert_eq!(token.span.slice(sentence), token.original_text);
+        }
+    }
+
+    #[test]
+    fn long_tokens_are_oversized() {
+        let mut tokenizer = Tokenizer::default();
+        tokenizer.options_mut().max_token_length = 5;
+
+        let sentence = "short loooooong";
+        let tokens = tokenizer.tokenize(sentence);
+
+        assert_eq!(tokens.len(), 2);
+        assert!(!tokens[0].oversized);
+        assert!(tokens[1].oversized);
+        assert!(tokens[1].word.tags.is_empty());
+        assert_eq!(tokens[1].span.char(), 6..15);
+        assert_eq!(tokens[1].span.byte(), 6..15);
+    }
+
+    #[test]
+    fn known_words_are_known() {
+        let mut tokenizer = Tokenizer::default();
+
+        // the default tokenizer has no dictionary, so only words without letters are known
+        assert!(tokenizer.is_known("42"));
+        assert!(tokenizer.is_known("."));
+        assert!(!tokenizer.is_known("nlprule"));
+
+        tokenizer.options_mut().known_words = vec!["nlprule".to_owned()].into_iter().collect();
+        assert!(tokenizer.is_known("nlprule"));
+        assert!(tokenizer.is_known("nlp\u{ad}rule"));
+        assert!(tokenizer.all_known("nlprule 42"));
+        assert!(!tokenizer.all_known("nlprule rocks"));
+
+        let tokens = tokenizer.tokenize("nlprule rocks");
+        assert!(tokens[0].is_known);
+        assert!(!tokens[1].is_known);
+    }
+
+    #[test]
+    fn shared_tagger_is_not_copied() {
+        let mut tokenizer = Tokenizer::default();
+
+        let tagger = tokenizer.tagger().clone();
+        assert!(matches!(
+            tokenizer.remove_word("house"),
+            Err(crate::Error::SharedTagger)
+        ));
+        assert!(tokenizer.tagger_mut().is_err());
+
+        drop(tagger);
+        assert!(!tokenizer.remove_word("house").unwrap());
+    }
+
+    #[test]
+    fn all_known_normalizes_like_tokenizing() {
+        let mut tokenizer = Tokenizer::default();
+        let tagger = tokenizer.tagger_mut().unwrap();
+        tagger.tag_store.insert("NN".into(), PosIdInt(0));
+        tagger.add_words(&[("house", "house", "NN")]).unwrap();
+
+        // multi-word and punctuated text is checked per token
+        assert!(tokenizer.all_known("house, house."));
+        assert!(tokenizer.all_known("(house) 42"));
+        assert!(!tokenizer.all_known("house rocks"));
+
+        // case variants get the tags of the lowercase version like when tokenizing
+        for text in &["House HOUSE", "House hOuse", "hou\u{ad}se", "hou-\nse"] {
+            assert_eq!(
+                tokenizer.all_known(text),
+                tokenizer.tokenize(text).iter().all(|x| x.is_known),
+                "{:?}",
+                text
+            );
+        }
+
+        // hyphenation at line breaks is removed before tagging
+        assert!(!tokenizer.all_known("hou-\nse"));
+        tokenizer.options_mut().rejoin_hyphenation = true;
+        assert!(tokenizer.all_known("hou-\nse"));
+        assert!(tokenizer.tokenize("hou-\nse").iter().all(|x| x.is_known));
+    }
+
+    #[test]
+    fn compound_split_heuristic_can_be_overridden() {
+        let mut tokenizer = Tokenizer::default();
+        let tagger = tokenizer.tagger_mut().unwrap();
+        tagger.tag_store.insert("NN".into(), PosIdInt(0));
+        tagger.add_words(&[("house", "house", "NN")]).unwrap();
+        tagger.set_use_compound_split_heuristic(false);
+
+        fn lemmas(
+            tokenizer: &Tokenizer,
+            use_compound_split_heuristic: Option<bool>,
+        ) -> Vec<String> {
+            tokenizer.tokenize_with_start("bluehouse", true, use_compound_split_heuristic)[0]
+                .word
+                .tags
+                .iter()
+                .map(|x| x.lemma.as_ref().to_string())
+                .collect()
+        }
+
+        assert!(lemmas(&tokenizer, None).is_empty());
+        assert!(lemmas(&tokenizer, Some(false)).is_empty());
+        assert_eq!(lemmas(&tokenizer, Some(true)), vec!["bluehouse"]);
+
+        tokenizer
+            .tagger_mut()
+            .unwrap()
+            .set_use_compound_split_heuristic(true);
+        assert_eq!(lemmas(&tokenizer, None), vec!["bluehouse"]);
+        assert!(lemmas(&tokenizer, Some(false)).is_empty());
+    }
 }
@@ -2,6 +2,7 @@
 
 use half::bf16;
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
 use std::{cmp::Ordering, collections::BinaryHeap};
 
@@ -41,15 +42,19 @@ impl<'a> PartialEq for Sequence<'a> {
     }
 }
 
+// ties are broken by the outcomes so the order does not depend on the order the sequences were pushed in
 impl<'a> Ord for Sequence<'a> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(&other).unwrap()
+        other
+            .log_prob
+            .total_cmp(&self.log_prob)
+            .then_with(|| self.outcomes.cmp(&other.outcomes))
     }
 }
 
 impl<'a> PartialOrd for Sequence<'a> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        other.log_prob.partial_cmp(&self.log_prob)
+        Some(self.cmp(other))
     }
 }
 
@@ -141,7 +146,11 @@ impl From<Model> for ModelFields {
         let mut rows = Vec::new();
         let mut values = Vec::new();
 
-        for (key, context) in model.pmap.iter() {
+        // sort by key so the serialized model does not depend on the iteration order of the map
+        let mut contexts: Vec<_> = model.pmap.iter().collect();
+        contexts.sort_unstable_by_key(|(key, _)| **key);
+
+        for (key, context) in contexts {
             assert_eq!(context.outcomes.len(), context.parameters.len());
             assert!(context.outcomes.len() <= std::u8::MAX as usize);
             cols.push((*key, context.outcomes.len() as u8));
@@ -163,8 +172,32 @@ impl From<Model> for ModelFields {
     }
 }
 
-impl From<ModelFields> for Model {
-    fn from(data: ModelFields) -> Self {
+impl TryFrom<ModelFields> for Model {
+    type Error = String;
+
+    fn try_from(data: ModelFields) -> Result<Self, Self::Error> {
+        let n_entries: usize = data.cols.iter().map(|(_, n)| *n as usize).sum();
+        if data.rows.len() != n_entries || data.values.len() != n_entries {
+            return Err(format!(
+                "invalid chunker model: {} contexts with {} entries, but {} outcomes and {} values",
+                data.cols.len(),
+                n_entries,
+                data.rows.len(),
+                data.values.len()
+            ));
+        }
+        if let Some(label) = data
+            .rows
+            .iter()
+            .find(|label| **label as usize >= data.outcome_labels.len())
+        {
+            return Err(format!(
+                "invalid chunker model: outcome {} out of range for {} labels",
+                label,
+                data.outcome_labels.len()
+            ));
+        }
+
         let mut pmap = DefaultHashMap::new();
 
         let mut row_iter = data.rows.iter();
@@ -172,15 +205,10 @@ impl From<ModelFields> for Model {
 
         for (key, n) in data.cols.iter() {
             let outcomes: Vec<_> = (0..*n as usize)
-                .map(|_| *row_iter.next().expect("checked in From<Model> impl") as usize)
+                .map(|_| *row_iter.next().expect("length is checked above") as usize)
                 .collect();
             let parameters: Vec<_> = (0..*n as usize)
-                .map(|_| {
-                    value_iter
-                        .next()
-                        .expect("checked in From<Model> impl")
-                        .to_f32()
-                })
+                .map(|_| value_iter.next().expect("length is checked above").to_f32())
                 .collect();
 
             pmap.insert(
@@ -192,15 +220,15 @@ impl From<ModelFields> for Model {
             );
         }
 
-        Model {
+        Ok(Model {
             outcome_labels: data.outcome_labels,
             pmap,
-        }
+        })
     }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
-#[serde(from = "ModelFields", into = "ModelFields")]
+#[serde(try_from = "ModelFields", into = "ModelFields")]
 pub(crate) struct Model {
     pub(crate) outcome_labels: Vec<String>,
     pub(crate) pmap: DefaultHashMap<u64, Context>,
@@ -752,7 +780,7 @@ impl Chunker {
                     .get(&(byte_start + token.len()))
                     .expect("byte index is at char boundary");
 
-                (*chunk, (char_start, char_end))
+                (*chunk, char_start..char_end)
             })
             .collect();
         let mut chunks = Vec::new();
@@ -771,7 +799,7 @@ impl Chunker {
 
                     if tokens
                         .iter()
-                        .find(|token| token.char_span == char_span)
+                        .find(|token| token.span.char() == char_span)
                         .map(|token| token.word.tags.iter().any(|tag| tag.pos.as_ref() == "NNS"))
                         .unwrap_or(false)
                     {
@@ -804,10 +832,58 @@ impl Chunker {
         // chunks with exactly the same char span as the input tokens get assigned to the token to match LT
         for token in tokens.iter_mut() {
             for (chunk, (_, char_span)) in chunks.iter().zip(internal_chunks.iter()) {
-                if *char_span == token.char_span {
+                if *char_span == token.span.char() {
                     token.chunks = (*chunk).clone();
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialized_model_does_not_depend_on_map_order() {
+        let model = |keys: &mut dyn Iterator<Item = u64>| {
+            let pmap = keys
+                .map(|key| {
+                    let context = Context {
+                        parameters: vec![key as f32],
+                        outcomes: vec![0],
+                    };
+                    (key, context)
+                })
+                .collect();
+
+            ModelFields::from(Model {
+                outcome_labels: vec!["O".into()],
+                pmap,
+            })
+        };
+
+        let a = model(&mut (0..100));
+        let b = model(&mut (0..100).rev());
+
+        assert_eq!(a.cols, b.cols);
+        assert_eq!(a.values, b.values);
+        assert!(a.cols.windows(2).all(|x| x[0].0 < x[1].0));
+    }
+
+    #[test]
+    fn sequence_ties_are_broken_by_outcomes() {
+        let a = Sequence::new(vec!["B-NP"], vec![0.5]);
+        let b = Sequence::new(vec!["I-NP"], vec![0.5]);
+        let c = Sequence::new(vec!["O"], vec![0.9]);
+
+        for order in &[[&a, &b, &c], [&b, &c, &a], [&c, &b, &a]] {
+            let mut heap: BinaryHeap<_> = order.iter().map(|x| (*x).clone()).collect();
+            heap.pop();
+
+            let mut sequences: Vec<_> = heap.into_vec();
+            sequences.sort();
+            assert_eq!(sequences, vec![c.clone(), a.clone()]);
+        }
+    }
+}
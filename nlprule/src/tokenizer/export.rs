@@ -0,0 +1,429 @@
+//! A compact binary format to export tokens in bulk e. g. the tokenization of a large corpus.
+//! Exported tokens can be read back with a [TokenReader] without the tokenizer which created them.
+//!
+//! A file is a sequence of documents, one per exported text. All integers are little-endian.
+//! Strings are stored once per document in a string table and referred to by their index in the table.
+//!
+//! ```text
+//! document := magic:b"NLPT" version:u32 n_strings:u32 string* n_sentences:u32 sentence*
+//! string   := len:u32 utf8:[u8; len]
+//! sentence := char_start:u32 byte_start:u32 n_tokens:u32 token*
+//! token    := char_start:u32 char_end:u32 byte_start:u32 byte_end:u32
+//!             text:u32 original_text:u32 flags:u8
+//!             n_tags:u16 (lemma:u32 pos:u32)* n_chunks:u16 chunk:u32*
+//! ```
+//!
+//! The spans of a sentence are relative to the text, the spans of a token are relative to its sentence
+//! (the same as in [Token]). The `flags` of a token are a bitset of `has_space_before` (1),
+//! `oversized` (2) and `is_known` (4). The first token of each sentence is the special sentence start token.
+
+use crate::types::{DefaultHashMap, Token};
+use std::{
+    convert::TryFrom,
+    io::{self, Read, Write},
+};
+
+const MAGIC: &[u8; 4] = b"NLPT";
+/// The version of the format. Increased on every incompatible change.
+pub const VERSION: u32 = 1;
+
+const HAS_SPACE_BEFORE: u8 = 1;
+const OVERSIZED: u8 = 2;
+const IS_KNOWN: u8 = 4;
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn push_u32(buffer: &mut Vec<u8>, value: usize) -> io::Result<()> {
+    let value = u32::try_from(value).map_err(invalid_data)?;
+    buffer.extend_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+fn push_u16(buffer: &mut Vec<u8>, value: usize) -> io::Result<()> {
+    let value = u16::try_from(value).map_err(invalid_data)?;
+    buffer.extend_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+#[derive(Default)]
+struct StringTable<'a> {
+    ids: DefaultHashMap<&'a str, usize>,
+    strings: Vec<&'a str>,
+}
+
+impl<'a> StringTable<'a> {
+    fn id(&mut self, string: &'a str) -> usize {
+        let strings = &mut self.strings;
+
+        *self.ids.entry(string).or_insert_with(|| {
+            strings.push(string);
+            strings.len() - 1
+        })
+    }
+}
+
+/// Writes the sentences of one text as a document, see the [module-level docs][self].
+/// The sentences must be slices of the `text` e. g. the output of [Tokenizer::pipe][crate::Tokenizer::pipe].
+pub fn write_document<W: Write>(
+    writer: &mut W,
+    text: &str,
+    sentences: &[Vec<Token>],
+) -> io::Result<()> {
+    let mut table = StringTable::default();
+    let mut records = Vec::new();
+
+    push_u32(&mut records, sentences.len())?;
+
+    for tokens in sentences {
+        let sentence = tokens.first().map_or("", |token| token.sentence);
+        // the offset of the sentence in the text, it must be a slice of the text
+        let byte_start = (sentence.as_ptr() as usize).wrapping_sub(text.as_ptr() as usize);
+        let end = byte_start.checked_add(sentence.len());
+        if tokens.is_empty() || !matches!(end, Some(end) if end <= text.len()) {
+            return Err(invalid_data("sentence is not part of the text"));
+        }
+
+        push_u32(&mut records, text[..byte_start].chars().count())?;
+        push_u32(&mut records, byte_start)?;
+        push_u32(&mut records, tokens.len())?;
+
+        for token in tokens {
+            push_u32(&mut records, token.span.char().start)?;
+            push_u32(&mut records, token.span.char().end)?;
+            push_u32(&mut records, token.span.byte().start)?;
+            push_u32(&mut records, token.span.byte().end)?;
+            push_u32(&mut records, table.id(token.word.text.as_ref()))?;
+            push_u32(&mut records, table.id(token.original_text))?;
+
+            let mut flags = 0;
+            if token.has_space_before {
+                flags |= HAS_SPACE_BEFORE;
+            }
+            if token.oversized {
+                flags |= OVERSIZED;
+            }
+            if token.is_known {
+                flags |= IS_KNOWN;
+            }
+            records.push(flags);
+
+            push_u16(&mut records, token.word.tags.len())?;
+            for data in token.word.tags.iter() {
+                push_u32(&mut records, table.id(data.lemma.as_ref()))?;
+                push_u32(&mut records, table.id(data.pos.as_ref()))?;
+            }
+
+            push_u16(&mut records, token.chunks.len())?;
+            for chunk in token.chunks.iter() {
+                push_u32(&mut records, table.id(chunk))?;
+            }
+        }
+    }
+
+    let mut header = Vec::new();
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    push_u32(&mut header, table.strings.len())?;
+    for string in table.strings {
+        push_u32(&mut header, string.len())?;
+        header.extend_from_slice(string.as_bytes());
+    }
+
+    writer.write_all(&header)?;
+    writer.write_all(&records)
+}
+
+/// A lemma and part-of-speech tag of an [ExportedToken].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedTag {
+    /// The lemma.
+    pub lemma: String,
+    /// The part-of-speech tag.
+    pub pos: String,
+}
+
+/// A token read from an export. Has the same attributes as a [Token].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedToken {
+    /// The text of the token.
+    pub text: String,
+    /// The exact slice of the input this token was created from.
+    pub original_text: String,
+    /// The char span of the token in its sentence.
+    pub char_span: (usize, usize),
+    /// The byte span of the token in its sentence.
+    pub byte_span: (usize, usize),
+    /// Whether there is whitespace before the token.
+    pub has_space_before: bool,
+    /// Whether the token is too long to be tagged and checked.
+    pub oversized: bool,
+    /// Whether the token is known, see [Tokenizer::is_known][crate::Tokenizer::is_known].
+    pub is_known: bool,
+    /// The lemmas and part-of-speech tags of the token.
+    pub tags: Vec<ExportedTag>,
+    /// The chunks of the token.
+    pub chunks: Vec<String>,
+}
+
+/// A sentence read from an export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedSentence {
+    /// The char offset of the sentence in the text.
+    pub char_start: usize,
+    /// The byte offset of the sentence in the text.
+    pub byte_start: usize,
+    /// The tokens of the sentence starting with the sentence start token.
+    pub tokens: Vec<ExportedToken>,
+}
+
+/// Reads documents written by [write_document]. Yields the sentences of one text at a time.
+pub struct TokenReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> TokenReader<R> {
+    /// Creates a new reader. Wrapping the reader in a [BufReader][std::io::BufReader] is recommended.
+    pub fn new(reader: R) -> Self {
+        TokenReader { reader }
+    }
+
+    fn read_u8(&mut self) -> io::Result<u8> {
+        let mut bytes = [0; 1];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(bytes[0])
+    }
+
+    fn read_u16(&mut self) -> io::Result<usize> {
+        let mut bytes = [0; 2];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(u16::from_le_bytes(bytes) as usize)
+    }
+
+    fn read_u32(&mut self) -> io::Result<usize> {
+        let mut bytes = [0; 4];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes) as usize)
+    }
+
+    fn read_document(&mut self, magic: [u8; 4]) -> io::Result<Vec<ExportedSentence>> {
+        if &magic != MAGIC {
+            return Err(invalid_data("not an export of tokens"));
+        }
+        let version = self.read_u32()?;
+        if version != VERSION as usize {
+            return Err(invalid_data(format!(
+                "unsupported export version: {}",
+                version
+            )));
+        }
+
+        let n_strings = self.read_u32()?;
+        let mut strings = Vec::new();
+        for _ in 0..n_strings {
+            let len = self.read_u32()?;
+            let mut bytes = Vec::new();
+            (&mut self.reader)
+                .take(len as u64)
+                .read_to_end(&mut bytes)?;
+            if bytes.len() != len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            strings.push(String::from_utf8(bytes).map_err(invalid_data)?);
+        }
+
+        let string = |reader: &mut Self| -> io::Result<String> {
+            let id = reader.read_u32()?;
+            strings
+                .get(id)
+                .cloned()
+                .ok_or_else(|| invalid_data(format!("string {} is not in the table", id)))
+        };
+
+        let n_sentences = self.read_u32()?;
+        let mut sentences = Vec::new();
+        for _ in 0..n_sentences {
+            let char_start = self.read_u32()?;
+            let byte_start = self.read_u32()?;
+            let n_tokens = self.read_u32()?;
+
+            let mut tokens = Vec::new();
+            for _ in 0..n_tokens {
+                let char_span = (self.read_u32()?, self.read_u32()?);
+                let byte_span = (self.read_u32()?, self.read_u32()?);
+                let text = string(self)?;
+                let original_text = string(self)?;
+                let flags = self.read_u8()?;
+
+                let n_tags = self.read_u16()?;
+                let mut tags = Vec::new();
+                for _ in 0..n_tags {
+                    tags.push(ExportedTag {
+                        lemma: string(self)?,
+                        pos: string(self)?,
+                    });
+                }
+
+                let n_chunks = self.read_u16()?;
+                let mut chunks = Vec::new();
+                for _ in 0..n_chunks {
+                    chunks.push(string(self)?);
+                }
+
+                tokens.push(ExportedToken {
+                    text,
+                    original_text,
+                    char_span,
+                    byte_span,
+                    has_space_before: flags & HAS_SPACE_BEFORE != 0,
+                    oversized: flags & OVERSIZED != 0,
+                    is_known: flags & IS_KNOWN != 0,
+                    tags,
+                    chunks,
+                });
+            }
+
+            sentences.push(ExportedSentence {
+                char_start,
+                byte_start,
+                tokens,
+            });
+        }
+
+        Ok(sentences)
+    }
+}
+
+impl<R: Read> Iterator for TokenReader<R> {
+    type Item = io::Result<Vec<ExportedSentence>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut magic = [0; 4];
+        let mut n_read = 0;
+
+        // the end of the input is only valid between documents
+        while n_read < magic.len() {
+            match self.reader.read(&mut magic[n_read..]) {
+                Ok(0) if n_read == 0 => return None,
+                Ok(0) => return Some(Err(io::ErrorKind::UnexpectedEof.into())),
+                Ok(n) => n_read += n,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Some(Err(error)),
+            }
+        }
+
+        Some(self.read_document(magic))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tokenizer::tag::Tagger,
+        types::{PosId, PosIdInt, Span, Word, WordData},
+    };
+
+    // the default tagger has no tags, so tokens with tags have to be created by hand
+    fn tokens<'t>(sentence: &'t str, tagger: &'t Tagger) -> Vec<Token<'t>> {
+        let mut start = 0;
+
+        sentence
+            .split_whitespace()
+            .map(|word| {
+                let byte_start = start + sentence[start..].find(word).unwrap();
+                let char_start = sentence[..byte_start].chars().count();
+                start = byte_start + word.len();
+
+                Token {
+                    word: Word::new_with_tags(
+                        tagger.id_word(word.to_lowercase().into()),
+                        vec![WordData::new(
+                            tagger.id_word(word.into()),
+                            PosId("NN", PosIdInt(0)),
+                        )],
+                    ),
+                    original_text: word,
+                    span: Span::new(
+                        char_start..char_start + word.chars().count(),
+                        byte_start..start,
+                    ),
+                    has_space_before: byte_start > 0,
+                    chunks: vec!["B-NP".to_owned()],
+                    oversized: word.len() > 10,
+                    is_known: word.len() <= 3,
+                    sentence,
+                    tagger,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exports_roundtrip() {
+        let tagger = Tagger::default();
+        let text = "A first Ünïcode text. And a loooooooooong one.";
+        let sentences = vec![tokens(&text[..23], &tagger), tokens(&text[23..], &tagger)];
+
+        let mut buffer = Vec::new();
+        write_document(&mut buffer, text, &sentences).unwrap();
+        write_document(&mut buffer, "", &[]).unwrap();
+
+        let documents = TokenReader::new(buffer.as_slice())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(documents.len(), 2);
+        assert!(documents[1].is_empty());
+
+        let document = &documents[0];
+        assert_eq!(document.len(), 2);
+        assert_eq!((document[1].char_start, document[1].byte_start), (21, 23));
+
+        for (tokens, exported) in sentences.iter().zip(document) {
+            assert_eq!(tokens.len(), exported.tokens.len());
+
+            for (token, exported) in tokens.iter().zip(exported.tokens.iter()) {
+                assert_eq!(token.word.text.as_ref(), exported.text);
+                assert_eq!(token.original_text, exported.original_text);
+                assert_eq!(
+                    token.span.char(),
+                    exported.char_span.0..exported.char_span.1
+                );
+                assert_eq!(
+                    token.span.byte(),
+                    exported.byte_span.0..exported.byte_span.1
+                );
+                assert_eq!(token.has_space_before, exported.has_space_before);
+                assert_eq!(token.oversized, exported.oversized);
+                assert_eq!(token.is_known, exported.is_known);
+                assert_eq!(token.chunks, exported.chunks);
+                assert_eq!(exported.tags[0].lemma, token.original_text);
+                assert_eq!(exported.tags[0].pos, "NN");
+            }
+        }
+    }
+
+    #[test]
+    fn sentences_must_be_part_of_the_text() {
+        let tagger = Tagger::default();
+        let other = "Some other text.".to_owned();
+
+        assert!(write_document(&mut Vec::new(), "Some text.", &[tokens(&other, &tagger)]).is_err());
+    }
+
+    #[test]
+    fn truncated_exports_are_errors() {
+        let tagger = Tagger::default();
+        let text = "Some text.";
+        let mut buffer = Vec::new();
+        write_document(&mut buffer, text, &[tokens(text, &tagger)]).unwrap();
+
+        let mut reader = TokenReader::new(&buffer[..buffer.len() - 1]);
+        assert!(reader.next().unwrap().is_err());
+
+        let mut reader = TokenReader::new(&b"NLP"[..]);
+        assert!(reader.next().unwrap().is_err());
+        assert!(TokenReader::new(&b""[..]).next().is_none());
+    }
+}
@@ -2,12 +2,13 @@
 //! where each word typically has multiple entries with different part-of-speech tags.
 
 use crate::types::*;
+use crate::utils::parallelism::MaybeParallelIterator;
 use bimap::BiMap;
 use fst::{IntoStreamer, Map, Streamer};
 use indexmap::IndexMap;
 use log::error;
 use serde::{Deserialize, Serialize};
-use std::{borrow::Cow, iter::once};
+use std::{borrow::Cow, collections::BTreeMap, convert::TryFrom, iter::once};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct TaggerLangOptions {
@@ -17,6 +18,9 @@ pub(crate) struct TaggerLangOptions {
     pub always_add_lower_tags: bool,
     /// Used part-of-speech tags which are not in the tagger dictionary.
     pub extra_tags: Vec<String>,
+    /// Named classes of words (e. g. stopwords or prepositions) which rules can refer to. See [TokenClasses].
+    #[serde(default)]
+    pub token_classes: BTreeMap<String, Vec<String>>,
 }
 
 impl Default for TaggerLangOptions {
@@ -25,10 +29,92 @@ impl Default for TaggerLangOptions {
             use_compound_split_heuristic: false,
             always_add_lower_tags: false,
             extra_tags: Vec::new(),
+            token_classes: BTreeMap::new(),
         }
     }
 }
 
+/// Named classes of words e. g. stopwords, auxiliaries or prepositions. Membership is case-insensitive and
+/// stored as a bitset per word, so checking whether a token belongs to a class is a single lookup.
+#[derive(Debug, Default, Clone)]
+pub struct TokenClasses {
+    names: Vec<String>,
+    members: DefaultHashMap<String, u64>,
+}
+
+impl TokenClasses {
+    /// The maximum number of classes per language.
+    pub const MAX_CLASSES: usize = 64;
+
+    pub(crate) fn new(classes: &BTreeMap<String, Vec<String>>) -> Self {
+        assert!(
+            classes.len() <= Self::MAX_CLASSES,
+            "at most {} token classes are supported",
+            Self::MAX_CLASSES
+        );
+
+        let mut members = DefaultHashMap::new();
+        for (i, words) in classes.values().enumerate() {
+            for word in words {
+                *members.entry(word.to_lowercase()).or_insert(0) |= 1 << i;
+            }
+        }
+
+        TokenClasses {
+            names: classes.keys().cloned().collect(),
+            members,
+        }
+    }
+
+    /// The names of all classes. The index of a name is the ID of the class.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Gets the ID of the class with the given name.
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|x| x == name)
+    }
+
+    /// Gets the bitset of classes the word belongs to. Bit `i` is set if the word is in the class with ID `i`.
+    pub fn get(&self, word: &str) -> u64 {
+        match self.members.get(word) {
+            Some(bits) => *bits,
+            // members are lowercase so lowercase words are already handled
+            None if word.chars().any(char::is_uppercase) => {
+                self.members.get(&word.to_lowercase()).copied().unwrap_or(0)
+            }
+            None => 0,
+        }
+    }
+
+    /// Whether the word is in the class with the given ID.
+    pub fn contains(&self, word: &str, id: usize) -> bool {
+        self.get(word) & (1 << id) != 0
+    }
+
+    /// Gets the names of the classes the word belongs to.
+    pub fn classes_of(&self, word: &str) -> Vec<&str> {
+        let bits = self.get(word);
+
+        self.names
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| bits & (1 << i) != 0)
+            .map(|(_, name)| name.as_str())
+            .collect()
+    }
+
+    /// Gets the (lowercase) words in the class with the given ID.
+    #[allow(dead_code)] // used by compile module
+    pub(crate) fn words(&self, id: usize) -> impl Iterator<Item = &str> {
+        self.members
+            .iter()
+            .filter(move |(_, bits)| *bits & (1 << id) != 0)
+            .map(|(word, _)| word.as_str())
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct TaggerFields {
     tag_fst: Vec<u8>,
@@ -98,13 +184,17 @@ impl From<Tagger> for TaggerFields {
     }
 }
 
-impl From<TaggerFields> for Tagger {
-    fn from(data: TaggerFields) -> Self {
-        let word_store_fst = Map::new(data.word_store_fst).unwrap();
+impl TryFrom<TaggerFields> for Tagger {
+    type Error = String;
+
+    fn try_from(data: TaggerFields) -> Result<Self, Self::Error> {
+        let invalid = |err: fst::Error| format!("invalid tagger dictionary: {}", err);
+
+        let word_store_fst = Map::new(data.word_store_fst).map_err(invalid)?;
         let word_store: BiMap<String, WordIdInt> = word_store_fst
             .into_stream()
             .into_str_vec()
-            .unwrap()
+            .map_err(invalid)?
             .into_iter()
             .map(|(key, value)| (key, WordIdInt(value as u32)))
             .collect();
@@ -112,12 +202,17 @@ impl From<TaggerFields> for Tagger {
         let mut tags = DefaultHashMap::new();
         let mut groups = DefaultHashMap::new();
 
-        let tag_fst = Map::new(data.tag_fst).unwrap();
+        let tag_fst = Map::new(data.tag_fst).map_err(invalid)?;
         let mut stream = tag_fst.into_stream();
 
         while let Some((key, value)) = stream.next() {
-            let word = std::str::from_utf8(&key[..key.len() - 1]).unwrap();
-            let word_id = *word_store.get_by_left(word).unwrap();
+            let word = key
+                .split_last()
+                .and_then(|(_, word)| std::str::from_utf8(word).ok())
+                .ok_or("invalid tagger dictionary: key is not valid UTF-8")?;
+            let word_id = *word_store
+                .get_by_left(word)
+                .ok_or_else(|| format!("invalid tagger dictionary: unknown word {:?}", word))?;
 
             let value_bytes = value.to_be_bytes();
             let inflection_id = WordIdInt(u32::from_be_bytes([
@@ -140,25 +235,30 @@ impl From<TaggerFields> for Tagger {
                 .push(pos_id);
         }
 
-        Tagger {
+        Ok(Tagger {
             tags,
             tag_store: data.tag_store,
             word_store,
             groups,
+            token_classes: TokenClasses::new(&data.lang_options.token_classes),
             lang_options: data.lang_options,
-        }
+            overrides: DefaultHashSet::new(),
+        })
     }
 }
 
 /// The lexical tagger.
 #[derive(Default, Serialize, Deserialize, Clone)]
-#[serde(from = "TaggerFields", into = "TaggerFields")]
+#[serde(try_from = "TaggerFields", into = "TaggerFields")]
 pub struct Tagger {
     pub(crate) tags: DefaultHashMap<WordIdInt, IndexMap<WordIdInt, Vec<PosIdInt>>>,
     pub(crate) tag_store: BiMap<String, PosIdInt>,
     pub(crate) word_store: BiMap<String, WordIdInt>,
     pub(crate) groups: DefaultHashMap<WordIdInt, Vec<WordIdInt>>,
+    pub(crate) token_classes: TokenClasses,
     pub(crate) lang_options: TaggerLangOptions,
+    // words whose entries were overridden at runtime, they are not subject to the lookup heuristics
+    pub(crate) overrides: DefaultHashSet<WordIdInt>,
 }
 
 impl Tagger {
@@ -195,6 +295,7 @@ impl Tagger {
         let lower = word.to_lowercase();
 
         if (add_lower || (add_lower_if_empty && tags.is_empty()))
+            && !self.is_overridden(word)
             && (word != lower
                 && (crate::utils::is_title_case(word) || crate::utils::is_uppercase(word)))
         {
@@ -214,6 +315,14 @@ impl Tagger {
         &self.word_store
     }
 
+    fn is_overridden(&self, word: &str) -> bool {
+        !self.overrides.is_empty()
+            && self
+                .word_store
+                .get_by_left(word)
+                .is_some_and(|id| self.overrides.contains(id))
+    }
+
     fn str_for_word_id(&self, id: &WordIdInt) -> &str {
         self.word_store
             .get_by_right(id)
@@ -226,6 +335,33 @@ impl Tagger {
             .expect("only valid pos ids are created")
     }
 
+    /// Whether words are split with a heuristic if they are not found in the dictionary, to get the tags of
+    /// compound words (e. g. "Haustür" gets the tags of "Tür"). Set according to the language by default.
+    pub fn use_compound_split_heuristic(&self) -> bool {
+        self.lang_options.use_compound_split_heuristic
+    }
+
+    /// Sets whether to use the compound splitting heuristic, see [use_compound_split_heuristic][Tagger::use_compound_split_heuristic].
+    pub fn set_use_compound_split_heuristic(&mut self, use_compound_split_heuristic: bool) {
+        self.lang_options.use_compound_split_heuristic = use_compound_split_heuristic;
+    }
+
+    /// Whether the tags of the lowercase version of title case and uppercase words are always added, not only if
+    /// the word itself is not found. Set according to the language by default.
+    pub fn always_add_lower_tags(&self) -> bool {
+        self.lang_options.always_add_lower_tags
+    }
+
+    /// Sets whether to always add the tags of the lowercase version, see [always_add_lower_tags][Tagger::always_add_lower_tags].
+    pub fn set_always_add_lower_tags(&mut self, always_add_lower_tags: bool) {
+        self.lang_options.always_add_lower_tags = always_add_lower_tags;
+    }
+
+    /// Gets the named classes of words of this language.
+    pub fn token_classes(&self) -> &TokenClasses {
+        &self.token_classes
+    }
+
     /// Tags the given string representation of a part-of-speech tag.
     /// Part-of-speech tags are treated as a closed set so each valid part-of-speech tag will get a numerical id.
     pub fn id_tag<'a>(&self, tag: &'a str) -> PosId<'a> {
@@ -268,6 +404,10 @@ impl Tagger {
         let use_compound_split_heuristic =
             use_compound_split_heuristic.unwrap_or(self.lang_options.use_compound_split_heuristic);
 
+        if self.is_overridden(word) {
+            return self.get_raw(word);
+        }
+
         let mut tags = self.get_strict_tags(word, add_lower, true);
 
         // compound splitting heuristic, seems to work reasonably well
@@ -322,12 +462,352 @@ impl Tagger {
         self.get_tags_with_options(word, None, None)
     }
 
+    /// Like [get_tags_with_options][Tagger::get_tags_with_options] for many words at once. The words are looked up
+    /// in parallel unless disabled with the `NLPRULE_PARALLELISM` environment variable.
+    /// The data of each word is at the index of the word, unknown words have no data.
+    pub fn get_tags_batch<S: AsRef<str> + Sync>(
+        &self,
+        words: &[S],
+        add_lower: Option<bool>,
+        use_compound_split_heuristic: Option<bool>,
+    ) -> Vec<Vec<WordData<'_>>> {
+        words
+            .into_maybe_par_iter()
+            .map(|word| {
+                self.get_tags_with_options(word.as_ref(), add_lower, use_compound_split_heuristic)
+            })
+            .collect()
+    }
+
+    /// Adds entries of the form `(word, lemma, part-of-speech)` to the dictionary e. g. for product names or
+    /// domain vocabulary. Added words are looked up like the words of the binary, including the lowercase and
+    /// compound splitting heuristics. Entries which already exist are ignored.
+    ///
+    /// # Errors
+    /// - If a part-of-speech tag is not in the tag set of the binary. Part-of-speech tags are a closed set
+    ///   since rules are compiled against it.
+    pub fn add_words<S: AsRef<str>>(&mut self, entries: &[(S, S, S)]) -> Result<(), crate::Error> {
+        let pos_ids = self.pos_ids(entries.iter().map(|(_, _, pos)| pos))?;
+
+        for ((word, lemma, _), pos_id) in entries.iter().zip(pos_ids) {
+            let word_id = self.get_or_insert_word(word.as_ref());
+            self.insert_entry(word_id, lemma.as_ref(), pos_id);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the entries of a word with the given `(lemma, part-of-speech)` tuples e. g. to tag a brand name
+    /// as a proper noun instead of a common noun. The word then gets exactly these tags: they take precedence over
+    /// tags from the lowercase version of the word and the word is never split as a compound.
+    ///
+    /// # Errors
+    /// - If a part-of-speech tag is not in the tag set of the binary.
+    pub fn override_tags<S: AsRef<str>>(
+        &mut self,
+        word: &str,
+        tags: &[(S, S)],
+    ) -> Result<(), crate::Error> {
+        let pos_ids = self.pos_ids(tags.iter().map(|(_, pos)| pos))?;
+
+        let word_id = self.get_or_insert_word(word);
+        self.remove_entries(word_id);
+        for ((lemma, _), pos_id) in tags.iter().zip(pos_ids) {
+            self.insert_entry(word_id, lemma.as_ref(), pos_id);
+        }
+        self.overrides.insert(word_id);
+
+        Ok(())
+    }
+
+    /// Removes all entries of a word. Like an [override][Tagger::override_tags] with no tags, so the word does
+    /// not get tags from the lowercase and compound splitting heuristics either.
+    /// Returns whether the word had any entries.
+    pub fn remove_word(&mut self, word: &str) -> bool {
+        let word_id = self.get_or_insert_word(word);
+        let removed = self.remove_entries(word_id);
+        self.overrides.insert(word_id);
+
+        removed
+    }
+
+    fn pos_ids<'a, S: AsRef<str> + 'a>(
+        &self,
+        tags: impl Iterator<Item = &'a S>,
+    ) -> Result<Vec<PosIdInt>, crate::Error> {
+        tags.map(|pos| {
+            self.tag_store
+                .get_by_left(pos.as_ref())
+                .copied()
+                .ok_or_else(|| crate::Error::UnknownPosTag(pos.as_ref().to_string()))
+        })
+        .collect()
+    }
+
+    fn insert_entry(&mut self, word_id: WordIdInt, lemma: &str, pos_id: PosIdInt) {
+        let lemma_id = self.get_or_insert_word(lemma);
+
+        let group = self.groups.entry(lemma_id).or_default();
+        if !group.contains(&word_id) {
+            group.push(word_id);
+        }
+
+        let pos_ids = self
+            .tags
+            .entry(word_id)
+            .or_default()
+            .entry(lemma_id)
+            .or_default();
+        if !pos_ids.contains(&pos_id) {
+            pos_ids.push(pos_id);
+        }
+    }
+
+    fn remove_entries(&mut self, word_id: WordIdInt) -> bool {
+        let entries = match self.tags.remove(&word_id) {
+            Some(entries) => entries,
+            None => return false,
+        };
+
+        for lemma_id in entries.keys() {
+            if let Some(group) = self.groups.get_mut(lemma_id) {
+                group.retain(|x| *x != word_id);
+            }
+        }
+
+        !entries.is_empty()
+    }
+
+    fn get_or_insert_word(&mut self, word: &str) -> WordIdInt {
+        if let Some(id) = self.word_store.get_by_left(word) {
+            return *id;
+        }
+
+        let id = WordIdInt(
+            self.word_store
+                .right_values()
+                .map(|x| x.0 + 1)
+                .max()
+                .unwrap_or(0),
+        );
+        self.word_store.insert(word.to_string(), id);
+        id
+    }
+
+    /// Iterates over all words in the dictionary together with their data, sorted by word.
+    /// The data of a word is the same as from [get_tags_with_options][Tagger::get_tags_with_options] without
+    /// the lowercase and compound splitting heuristics.
+    pub fn iter_words(&self) -> impl Iterator<Item = (&str, Vec<WordData<'_>>)> + '_ {
+        let mut words: Vec<_> = self
+            .tags
+            .keys()
+            .map(|id| self.str_for_word_id(id))
+            .collect();
+        words.sort_unstable();
+
+        words
+            .into_iter()
+            .map(move |word| (word, self.get_raw(word)))
+    }
+
     /// Get the words with the same lemma as the given lemma.
+    /// The result is empty if no word in the dictionary has the lemma. The lemma itself is only part of the result
+    /// if it is a word with this lemma in the dictionary (which is usually the case e. g. "house" for "house").
     pub fn get_group_members(&self, lemma: &str) -> Vec<&str> {
-        self.word_store
-            .get_by_left(lemma)
-            .and_then(|x| self.groups.get(x))
-            .map(|vec| vec.iter().map(|x| self.str_for_word_id(x)).collect())
+        self.get_group_members_with_pos(lemma, |_| true)
+    }
+
+    /// Like [get_group_members][Tagger::get_group_members] but only gets the words which have the lemma with
+    /// a part-of-speech tag for which `pos_filter` is true e. g. `|pos| pos.starts_with("VB")` for verb forms.
+    pub fn get_group_members_with_pos<F: Fn(&str) -> bool>(
+        &self,
+        lemma: &str,
+        pos_filter: F,
+    ) -> Vec<&str> {
+        let lemma_id = match self.word_store.get_by_left(lemma) {
+            Some(id) => id,
+            None => return Vec::new(),
+        };
+
+        self.groups
+            .get(lemma_id)
+            .map(|vec| {
+                vec.iter()
+                    .filter(|word_id| {
+                        self.tags
+                            .get(word_id)
+                            .and_then(|map| map.get(lemma_id))
+                            .is_some_and(|pos_ids| {
+                                pos_ids
+                                    .iter()
+                                    .any(|pos_id| pos_filter(self.str_for_pos_id(pos_id)))
+                            })
+                    })
+                    .map(|x| self.str_for_word_id(x))
+                    .collect()
+            })
             .unwrap_or_else(Vec::new)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupt_dictionary_is_a_deserialization_error() {
+        let fields = TaggerFields {
+            tag_fst: vec![1, 2, 3],
+            word_store_fst: vec![4, 5, 6],
+            tag_store: BiMap::new(),
+            lang_options: TaggerLangOptions::default(),
+        };
+        let bytes = bincode::serialize(&fields).unwrap();
+
+        let err = bincode::deserialize::<Tagger>(&bytes).err().unwrap();
+        assert!(err.to_string().contains("invalid tagger dictionary"));
+    }
+
+    #[test]
+    fn batch_lookup_keeps_unknown_words() {
+        let tagger = Tagger::default();
+        let words = ["a", "unknown", ""];
+
+        let batch = tagger.get_tags_batch(&words, Some(false), Some(false));
+        assert_eq!(batch.len(), words.len());
+        for (word, data) in words.iter().zip(batch) {
+            assert_eq!(
+                data,
+                tagger.get_tags_with_options(word, Some(false), Some(false))
+            );
+        }
+    }
+
+    #[test]
+    fn added_words_are_tagged() {
+        let mut tagger = Tagger::default();
+        tagger.tag_store.insert("NNP".into(), PosIdInt(0));
+
+        tagger
+            .add_words(&[
+                ("Nlprule", "Nlprule", "NNP"),
+                ("Nlprules", "Nlprule", "NNP"),
+            ])
+            .unwrap();
+
+        let tags = tagger.get_tags_with_options("Nlprules", Some(false), Some(false));
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].lemma.as_ref(), "Nlprule");
+        assert_eq!(tags[0].pos.as_ref(), "NNP");
+        assert_eq!(
+            tagger.get_group_members("Nlprule"),
+            vec!["Nlprule", "Nlprules"]
+        );
+
+        // participates in the compound splitting heuristic
+        let tags = tagger.get_tags_with_options("Supernlprule", Some(false), Some(true));
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].lemma.as_ref(), "Supernlprule");
+
+        assert!(matches!(
+            tagger.add_words(&[("word", "word", "XYZ")]),
+            Err(crate::Error::UnknownPosTag(_))
+        ));
+        assert!(tagger.get_tags("word").is_empty());
+    }
+
+    #[test]
+    fn overrides_take_precedence_over_heuristics() {
+        let mut tagger = Tagger::default();
+        tagger.tag_store.insert("NN".into(), PosIdInt(0));
+        tagger.tag_store.insert("NNP".into(), PosIdInt(1));
+        tagger
+            .add_words(&[("apple", "apple", "NN"), ("Apple", "apple", "NN")])
+            .unwrap();
+
+        tagger.override_tags("Apple", &[("Apple", "NNP")]).unwrap();
+        let tags = tagger.get_tags_with_options("Apple", Some(true), Some(true));
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].lemma.as_ref(), "Apple");
+        assert_eq!(tags[0].pos.as_ref(), "NNP");
+        assert_eq!(tagger.get_group_members("apple"), vec!["apple"]);
+
+        assert!(tagger.remove_word("apple"));
+        assert!(tagger.get_tags("apple").is_empty());
+        // a removed word is not split as a compound
+        tagger.add_words(&[("pie", "pie", "NN")]).unwrap();
+        tagger.remove_word("applepie");
+        assert!(tagger
+            .get_tags_with_options("applepie", Some(true), Some(true))
+            .is_empty());
+        assert!(!tagger.remove_word("applepie"));
+    }
+
+    #[test]
+    fn words_are_iterated_in_sorted_order() {
+        let mut tagger = Tagger::default();
+        tagger.tag_store.insert("NN".into(), PosIdInt(0));
+        tagger.tag_store.insert("NNS".into(), PosIdInt(1));
+        tagger
+            .add_words(&[
+                ("houses", "house", "NNS"),
+                ("house", "house", "NN"),
+                ("apple", "apple", "NN"),
+            ])
+            .unwrap();
+        tagger.remove_word("apple");
+
+        let words: Vec<_> = tagger
+            .iter_words()
+            .map(|(word, data)| {
+                let data: Vec<_> = data
+                    .iter()
+                    .map(|x| (x.lemma.as_ref().to_string(), x.pos.as_ref().to_string()))
+                    .collect();
+                (word, data)
+            })
+            .collect();
+        assert_eq!(
+            words,
+            vec![
+                ("house", vec![("house".to_string(), "NN".to_string())]),
+                ("houses", vec![("house".to_string(), "NNS".to_string())]),
+            ]
+        );
+    }
+
+    #[test]
+    fn group_members_can_be_filtered_by_pos() {
+        let mut tagger = Tagger::default();
+        tagger.tag_store.insert("NN".into(), PosIdInt(0));
+        tagger.tag_store.insert("VB".into(), PosIdInt(1));
+        tagger.tag_store.insert("VBZ".into(), PosIdInt(2));
+        tagger
+            .add_words(&[
+                ("walk", "walk", "NN"),
+                ("walk", "walk", "VB"),
+                ("walks", "walk", "VBZ"),
+                ("walks", "walk", "NN"),
+                ("walked", "walk", "VB"),
+                ("walking", "walking", "NN"),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            tagger.get_group_members("walk"),
+            vec!["walk", "walks", "walked"]
+        );
+        assert_eq!(
+            tagger.get_group_members_with_pos("walk", |pos| pos.starts_with("VB")),
+            vec!["walk", "walks", "walked"]
+        );
+        assert_eq!(
+            tagger.get_group_members_with_pos("walk", |pos| pos == "NN"),
+            vec!["walk", "walks"]
+        );
+
+        // words which are not the lemma of any word have no group, not even themselves
+        assert!(tagger.get_group_members("walked").is_empty());
+        assert!(tagger.get_group_members("unknown").is_empty());
+    }
+}
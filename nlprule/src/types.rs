@@ -5,9 +5,15 @@ use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
     collections::{hash_map, HashMap, HashSet},
+    convert::TryFrom,
+    fmt,
+    io::{self, Read, Write},
+    ops::Range,
 };
+use thiserror::Error;
 
 use crate::tokenizer::tag::Tagger;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub(crate) type DefaultHashMap<K, V> = HashMap<K, V>;
 pub(crate) type DefaultHashSet<T> = HashSet<T>;
@@ -20,6 +26,137 @@ pub(crate) struct WordIdInt(pub u32);
 #[serde(transparent)]
 pub(crate) struct PosIdInt(pub u16);
 
+/// A span of text in char, byte and UTF-16 coordinates. The char and byte ranges are stored,
+/// the UTF-16 range is computed from the text on demand. Spans are relative to a text e. g. the sentence of a token
+/// or the text a suggestion was computed for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    char_start: usize,
+    char_end: usize,
+    byte_start: usize,
+    byte_end: usize,
+}
+
+impl Span {
+    /// Creates a span from a char and byte range which refer to the same slice of a text.
+    ///
+    /// # Panics
+    /// - If one of the ranges ends before it starts.
+    pub fn new(char: Range<usize>, byte: Range<usize>) -> Self {
+        assert!(
+            char.start <= char.end && byte.start <= byte.end,
+            "spans can not end before they start"
+        );
+
+        Span {
+            char_start: char.start,
+            char_end: char.end,
+            byte_start: byte.start,
+            byte_end: byte.end,
+        }
+    }
+
+    /// Creates the span of the chars `char` in the `text`.
+    ///
+    /// # Panics
+    /// - If the range ends before it starts or after the end of the text.
+    pub fn from_chars(text: &str, char: Range<usize>) -> Self {
+        assert!(
+            char.start <= char.end,
+            "spans can not end before they start"
+        );
+
+        let mut byte_indices = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()));
+        let byte_start = byte_indices
+            .nth(char.start)
+            .expect("char range must be within the text");
+        let byte_end = if char.is_empty() {
+            byte_start
+        } else {
+            byte_indices
+                .nth(char.end - char.start - 1)
+                .expect("char range must be within the text")
+        };
+
+        Span::new(char, byte_start..byte_end)
+    }
+
+    /// Creates the span of the bytes `byte` in the `text`.
+    ///
+    /// # Panics
+    /// - If the range is not within the text or not on char boundaries.
+    pub fn from_bytes(text: &str, byte: Range<usize>) -> Self {
+        let char_start = text[..byte.start].chars().count();
+        let char_end = char_start + text[byte.clone()].chars().count();
+
+        Span::new(char_start..char_end, byte)
+    }
+
+    /// The char range of this span.
+    pub fn char(&self) -> Range<usize> {
+        self.char_start..self.char_end
+    }
+
+    /// The byte range of this span.
+    pub fn byte(&self) -> Range<usize> {
+        self.byte_start..self.byte_end
+    }
+
+    /// Computes the UTF-16 range of this span e. g. for JavaScript or Java APIs.
+    /// `text` is the text this span is relative to.
+    pub fn utf16(&self, text: &str) -> Range<usize> {
+        let start = text[..self.byte_start].encode_utf16().count();
+        start..start + self.slice(text).encode_utf16().count()
+    }
+
+    /// Whether this span is empty.
+    pub fn is_empty(&self) -> bool {
+        self.char_start == self.char_end
+    }
+
+    /// Gets the slice of the `text` this span refers to. `text` is the text this span is relative to.
+    pub fn slice<'t>(&self, text: &'t str) -> &'t str {
+        &text[self.byte()]
+    }
+
+    /// Whether the other span is within this span.
+    pub fn contains(&self, other: &Span) -> bool {
+        self.char_start <= other.char_start && other.char_end <= self.char_end
+    }
+
+    /// Whether this span and the other span overlap. An empty span only intersects spans it lies strictly within.
+    pub fn intersects(&self, other: &Span) -> bool {
+        self.char_start < other.char_end && other.char_start < self.char_end
+    }
+
+    /// Shifts this span to the right e. g. to make a span relative to a sentence relative to the text
+    /// if the sentence starts at `char_offset` and `byte_offset` in the text.
+    pub fn shift(&self, char_offset: usize, byte_offset: usize) -> Span {
+        Span {
+            char_start: self.char_start + char_offset,
+            char_end: self.char_end + char_offset,
+            byte_start: self.byte_start + byte_offset,
+            byte_end: self.byte_end + byte_offset,
+        }
+    }
+
+    /// Shifts this span to the left, the inverse of [Span::shift].
+    ///
+    /// # Panics
+    /// - If the span starts before the offset.
+    pub fn unshift(&self, char_offset: usize, byte_offset: usize) -> Span {
+        Span {
+            char_start: self.char_start - char_offset,
+            char_end: self.char_end - char_offset,
+            byte_start: self.byte_start - byte_offset,
+            byte_end: self.byte_end - byte_offset,
+        }
+    }
+}
+
 /// Owned versions of the types for use in longer-living structures not bound to the `'t` lifetime e.g. rule tests.
 pub mod owned {
     use super::*;
@@ -87,10 +224,31 @@ pub mod owned {
     #[allow(missing_docs)]
     pub struct Token {
         pub word: Word,
-        pub char_span: (usize, usize),
-        pub byte_span: (usize, usize),
+        pub original_text: String,
+        pub span: Span,
         pub has_space_before: bool,
+        /// See [super::Token::whitespace_before].
+        #[serde(default)]
+        pub whitespace_before: String,
         pub chunks: Vec<String>,
+        pub oversized: bool,
+        pub is_known: bool,
+        #[serde(default)]
+        pub classes: Vec<String>,
+    }
+
+    impl Token {
+        /// See [super::Token::char_span].
+        #[deprecated(note = "use `span.char()` instead")]
+        pub fn char_span(&self) -> (usize, usize) {
+            (self.span.char().start, self.span.char().end)
+        }
+
+        /// See [super::Token::byte_span].
+        #[deprecated(note = "use `span.byte()` instead")]
+        pub fn byte_span(&self) -> (usize, usize) {
+            (self.span.byte().start, self.span.byte().end)
+        }
     }
 }
 
@@ -192,16 +350,21 @@ impl<'t> Word<'t> {
 pub struct IncompleteToken<'t> {
     /// The word of this token. Contains information about the actual text and part-of-speech tags + lemmas.
     pub word: Word<'t>,
-    /// Byte start (inclusive) and end (exclusive) of this token in the sentence.
-    pub byte_span: (usize, usize),
-    /// Char start (inclusive) and end (exclusive) of this token in the sentence.
-    pub char_span: (usize, usize),
+    /// The exact slice of the sentence this token was created from. Equivalent to `span.slice(sentence)`.
+    pub original_text: &'t str,
+    /// The span of this token in the sentence.
+    pub span: Span,
     /// Whether this token is the last token in the sentence-
     pub is_sentence_end: bool,
     /// Whether this token has one or more whitespace characters before.
     pub has_space_before: bool,
     /// Chunks associated with this token.
     pub chunks: Vec<String>,
+    /// Whether this token is longer than [TokenizerOptions::max_token_length][crate::tokenizer::TokenizerOptions::max_token_length].
+    /// Oversized tokens are not tagged, never matched by regexes and never part of suggestions.
+    pub oversized: bool,
+    /// Whether this token is a known word. See [Tokenizer::is_known][crate::tokenizer::Tokenizer::is_known].
+    pub is_known: bool,
     /// A *multiword* lemma and part-of-speech tag. Set if the token was found in a list of phrases.
     pub multiword_data: Option<WordData<'t>>,
     /// The sentence this token is in.
@@ -223,10 +386,12 @@ pub struct DisambiguatedToken<'t>(pub IncompleteToken<'t>);
 #[allow(missing_docs)]
 pub struct Token<'t> {
     pub word: Word<'t>,
-    pub char_span: (usize, usize),
-    pub byte_span: (usize, usize),
+    pub original_text: &'t str,
+    pub span: Span,
     pub has_space_before: bool,
     pub chunks: Vec<String>,
+    pub oversized: bool,
+    pub is_known: bool,
     pub sentence: &'t str,
     #[derivative(Debug = "ignore")]
     pub tagger: &'t Tagger,
@@ -245,23 +410,59 @@ impl<'t> Token<'t> {
                 .into_iter()
                 .collect(),
             ),
-            char_span: (0, 0),
-            byte_span: (0, 0),
+            original_text: "",
+            span: Span::default(),
             has_space_before: false,
             chunks: Vec::new(),
+            oversized: false,
+            is_known: true,
             sentence,
             tagger,
         }
     }
 
+    /// Char start (inclusive) and end (exclusive) of this token in the sentence.
+    #[deprecated(note = "use `span.char()` instead")]
+    pub fn char_span(&self) -> (usize, usize) {
+        (self.span.char().start, self.span.char().end)
+    }
+
+    /// Byte start (inclusive) and end (exclusive) of this token in the sentence.
+    #[deprecated(note = "use `span.byte()` instead")]
+    pub fn byte_span(&self) -> (usize, usize) {
+        (self.span.byte().start, self.span.byte().end)
+    }
+
+    /// The exact whitespace directly before this token in the sentence (e. g. "  " or "\t\n"), empty if
+    /// [has_space_before][Token::has_space_before] is false. Concatenating the whitespace and original text of all
+    /// tokens of a sentence reproduces the sentence up to trailing whitespace and
+    /// [invisible chars][crate::tokenizer::TokenizerOptions::invisible_chars] between tokens.
+    /// See [detokenize][crate::tokenizer::detokenize] to reproduce the text exactly.
+    pub fn whitespace_before(&self) -> &'t str {
+        let before = &self.sentence[..self.span.byte().start];
+        &before[before.trim_end_matches(char::is_whitespace).len()..]
+    }
+
+    /// Gets the names of the token classes (e. g. "stopword") this token belongs to.
+    /// See [TokenClasses](crate::tokenizer::tag::TokenClasses).
+    pub fn classes(&self) -> Vec<&'t str> {
+        self.tagger
+            .token_classes()
+            .classes_of(self.word.text.as_ref())
+    }
+
     /// Converts this token to an owned equivalent.
     pub fn to_owned_token(&self) -> owned::Token {
         owned::Token {
             word: self.word.to_owned_word(),
-            char_span: self.char_span,
-            byte_span: self.byte_span,
+            original_text: self.original_text.to_string(),
+            span: self.span,
             has_space_before: self.has_space_before,
+            whitespace_before: self.whitespace_before().to_owned(),
             chunks: self.chunks.clone(),
+            oversized: self.oversized,
+            is_known: self.is_known,
+            classes: self.classes().into_iter().map(String::from).collect(),
         }
     }
 }
@@ -294,35 +495,1028 @@ impl<'t> From<IncompleteToken<'t>> for Token<'t> {
 
         Token {
             word,
-            byte_span: data.byte_span,
-            char_span: data.char_span,
+            original_text: data.original_text,
+            span: data.span,
             has_space_before: data.has_space_before,
             chunks: data.chunks,
+            oversized: data.oversized,
+            is_known: data.is_known,
             sentence: data.sentence,
             tagger: data.tagger,
         }
     }
 }
 
+/// The structural type of a segment of text. Some rules assume that they are applied to full sentences
+/// and are skipped for other segment types, see [RulesOptions::segment_ignore_ids][crate::rules::RulesOptions::segment_ignore_ids].
+#[derive(
+    Debug, Copy, Clone, Serialize, Deserialize, Hash, Eq, PartialEq, Ord, PartialOrd, Default,
+)]
+pub enum SegmentType {
+    /// Running text consisting of full sentences.
+    #[default]
+    Sentence,
+    /// A heading or title.
+    Heading,
+    /// An item of a (bulleted or numbered) list.
+    ListItem,
+    /// A caption of e. g. a figure or a table.
+    Caption,
+    /// An isolated word or phrase e. g. a form field or a product title. See [Rules::check_fragment][crate::rules::Rules::check_fragment].
+    Fragment,
+}
+
+impl SegmentType {
+    /// Gets the segment type of one line of Markdown and the text of the segment without the markup
+    /// i. e. without leading `#`s for headings and the bullet / number for list items.
+    pub fn from_markdown_line(line: &str) -> (SegmentType, &str) {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with('#') {
+            let text = trimmed.trim_start_matches('#');
+            if text.is_empty() || text.starts_with(char::is_whitespace) {
+                return (SegmentType::Heading, text.trim());
+            }
+        }
+
+        for bullet in &["- ", "* ", "+ "] {
+            if let Some(text) = trimmed.strip_prefix(bullet) {
+                return (SegmentType::ListItem, text.trim());
+            }
+        }
+
+        let n_digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+        if n_digits > 0 {
+            if let Some(text) = trimmed[n_digits..]
+                .strip_prefix(". ")
+                .or_else(|| trimmed[n_digits..].strip_prefix(") "))
+            {
+                return (SegmentType::ListItem, text.trim());
+            }
+        }
+
+        (SegmentType::Sentence, line)
+    }
+
+    /// Gets a lowercase string representation of this segment type e. g. "list_item".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SegmentType::Sentence => "sentence",
+            SegmentType::Heading => "heading",
+            SegmentType::ListItem => "list_item",
+            SegmentType::Caption => "caption",
+            SegmentType::Fragment => "fragment",
+        }
+    }
+}
+
+impl std::str::FromStr for SegmentType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "sentence" => SegmentType::Sentence,
+            "heading" => SegmentType::Heading,
+            "list_item" => SegmentType::ListItem,
+            "caption" => SegmentType::Caption,
+            "fragment" => SegmentType::Fragment,
+            x => return Err(format!("unknown segment type '{}'", x)),
+        })
+    }
+}
+
+/// How important a suggestion is, ordered from least to most important.
+/// See [ApplyOptions::min_severity][crate::rules::ApplyOptions::min_severity].
+#[derive(
+    Debug, Copy, Clone, Serialize, Deserialize, Hash, Eq, PartialEq, Ord, PartialOrd, Default,
+)]
+pub enum Severity {
+    /// A suggestion which can be ignored e. g. a stylistic preference.
+    Hint,
+    /// A probable but not certain issue.
+    #[default]
+    Warning,
+    /// A definite mistake e. g. a misspelling or a grammar error.
+    Error,
+}
+
+impl Severity {
+    /// Gets the default severity of rules with the given LanguageTool category type
+    /// (the ITS issue type e. g. "misspelling" or "style").
+    pub fn from_category_type(category_type: Option<&str>) -> Self {
+        match category_type {
+            Some(
+                "misspelling" | "grammar" | "duplication" | "addition" | "omission"
+                | "untranslated" | "mistranslation",
+            ) => Severity::Error,
+            Some("style" | "register") => Severity::Hint,
+            _ => Severity::Warning,
+        }
+    }
+
+    /// Gets a lowercase string representation of this severity e. g. "error".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Hint => "hint",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "hint" => Severity::Hint,
+            "warning" => Severity::Warning,
+            "error" => Severity::Error,
+            x => return Err(format!("unknown severity '{}'", x)),
+        })
+    }
+}
+
 /// Suggestion for change in a text.
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(try_from = "SuggestionFields", into = "SuggestionFields")]
 pub struct Suggestion {
     /// The ID of the rule this suggestion is from.
     pub source: String,
     /// A human-readable message.
     pub message: String,
-    /// The start character index (inclusive).
-    pub start: usize,
-    /// The end character index (exclusive).
-    pub end: usize,
-    /// The suggested replacement options for the text.
+    /// A short message e. g. "Possible typo" suitable for compact display, empty if the rule has none.
+    /// See [Rule::short][crate::rule::Rule::short].
+    pub short_message: String,
+    /// A link to an explanation of the rule, if there is one. See [Rule::url][crate::rule::Rule::url].
+    pub url: Option<String>,
+    /// How important the suggestion is. See [Rule::severity][crate::rule::Rule::severity].
+    pub severity: Severity,
+    /// The span of the flagged text.
+    pub span: Span,
+    /// The suggested replacement options for the text. An empty replacement deletes the text,
+    /// see [apply_suggestions][crate::rules::apply_suggestions].
     pub replacements: Vec<String>,
+    /// The type of the segment the suggestion is in.
+    pub segment_type: SegmentType,
+    /// The smallest Levenshtein distance (in chars) between the replaced text and one of the replacements.
+    /// Can be used to sort suggestions by how minimal they are.
+    pub replacement_distance: usize,
+}
+
+/// The serialized form of a [Suggestion]. The char range is kept as `start` and `end` for compatibility,
+/// documents written before byte offsets were added have an empty byte range.
+#[derive(Serialize, Deserialize)]
+struct SuggestionFields {
+    source: String,
+    message: String,
+    #[serde(default)]
+    short_message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(default)]
+    severity: Severity,
+    start: usize,
+    end: usize,
+    #[serde(default)]
+    byte_start: usize,
+    #[serde(default)]
+    byte_end: usize,
+    replacements: Vec<String>,
+    #[serde(default)]
+    segment_type: SegmentType,
+    #[serde(default)]
+    replacement_distance: usize,
+}
+
+impl From<Suggestion> for SuggestionFields {
+    fn from(suggestion: Suggestion) -> Self {
+        SuggestionFields {
+            source: suggestion.source,
+            message: suggestion.message,
+            short_message: suggestion.short_message,
+            url: suggestion.url,
+            severity: suggestion.severity,
+            start: suggestion.span.char().start,
+            end: suggestion.span.char().end,
+            byte_start: suggestion.span.byte().start,
+            byte_end: suggestion.span.byte().end,
+            replacements: suggestion.replacements,
+            segment_type: suggestion.segment_type,
+            replacement_distance: suggestion.replacement_distance,
+        }
+    }
+}
+
+impl TryFrom<SuggestionFields> for Suggestion {
+    type Error = String;
+
+    fn try_from(data: SuggestionFields) -> Result<Self, Self::Error> {
+        if data.start > data.end || data.byte_start > data.byte_end {
+            return Err(format!(
+                "invalid span: chars {}..{}, bytes {}..{} end before they start",
+                data.start, data.end, data.byte_start, data.byte_end
+            ));
+        }
+
+        Ok(Suggestion {
+            source: data.source,
+            message: data.message,
+            short_message: data.short_message,
+            url: data.url,
+            severity: data.severity,
+            span: Span::new(data.start..data.end, data.byte_start..data.byte_end),
+            replacements: data.replacements,
+            segment_type: data.segment_type,
+            replacement_distance: data.replacement_distance,
+        })
+    }
+}
+
+/// The version and provenance of a binary. See [Tokenizer::info][crate::Tokenizer::info] and [Rules::info][crate::Rules::info].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// The language code of the binary e. g. "en".
+    pub language: String,
+    /// The version of nlprule the binary was built with.
+    pub crate_version: String,
+    /// The date the binary was built at in `YYYY-MM-DD` format (UTC).
+    pub build_date: String,
+    /// The version of LanguageTool the binary was built from, if known.
+    pub lt_version: Option<String>,
+    /// A hash of the content of the binary computed when it was built. Identifies the exact binary.
+    pub checksum: String,
+}
+
+/// The part of a version which must match for binaries to be compatible, the major version or the minor version
+/// before 1.0 (e. g. "0.4" for "0.4.7-pre").
+fn compatible_version(version: &str) -> &str {
+    let version = version.split('-').next().unwrap_or_default();
+    let mut parts = version.splitn(3, '.');
+    let major = parts.next().unwrap_or_default();
+
+    match (major, parts.next()) {
+        ("0", Some(minor)) => &version[..major.len() + 1 + minor.len()],
+        _ => major,
+    }
+}
+
+impl ModelInfo {
+    /// Checks that the binary was built with a version of nlprule compatible with this one.
+    /// Binaries without a version (e. g. built in tests) are not checked.
+    ///
+    /// # Errors
+    /// - [Error::IncompatibleBinary][crate::Error::IncompatibleBinary] if the versions are not compatible.
+    pub fn check_compatible(&self) -> Result<(), crate::Error> {
+        let expected = env!("CARGO_PKG_VERSION");
+
+        if self.crate_version.is_empty()
+            || compatible_version(&self.crate_version) == compatible_version(expected)
+        {
+            Ok(())
+        } else {
+            Err(crate::Error::IncompatibleBinary {
+                found: self.crate_version.clone(),
+                expected: expected.to_owned(),
+            })
+        }
+    }
+}
+
+/// The kind of component stored in a binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BinaryKind {
+    /// A [Tokenizer][crate::Tokenizer] binary.
+    Tokenizer,
+    /// A [Rules][crate::Rules] binary.
+    Rules,
+}
+
+impl fmt::Display for BinaryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryKind::Tokenizer => write!(f, "tokenizer"),
+            BinaryKind::Rules => write!(f, "rules"),
+        }
+    }
+}
+
+/// The header at the start of each (decompressed) binary. Checked before the rest of the binary is deserialized,
+/// so binaries of incompatible versions are rejected with a descriptive error instead of failing somewhere
+/// during deserialization.
+///
+/// Starts with [BinaryHeader::MAGIC]. The format version and the crate version always come first, so they can be
+/// read from binaries of any format version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BinaryHeader {
+    /// The version of the binary format, see [BinaryHeader::FORMAT_VERSION].
+    pub format_version: u32,
+    /// The version of nlprule the binary was written with.
+    pub crate_version: String,
+    /// The language code of the binary e. g. "en".
+    pub language: String,
+    /// The kind of component stored in the binary.
+    pub kind: BinaryKind,
+}
+
+impl BinaryHeader {
+    /// The magic bytes each binary starts with.
+    pub const MAGIC: [u8; 8] = *b"NLPRULE\0";
+    /// The current version of the binary format. Incremented whenever the serialized structures change.
+    pub const FORMAT_VERSION: u32 = 2;
+
+    pub(crate) fn new(kind: BinaryKind, language: &str) -> Self {
+        BinaryHeader {
+            format_version: BinaryHeader::FORMAT_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_owned(),
+            language: language.to_owned(),
+            kind,
+        }
+    }
+
+    pub(crate) fn write<W: Write>(&self, mut writer: W) -> Result<(), crate::Error> {
+        writer.write_all(&BinaryHeader::MAGIC)?;
+        bincode::serialize_into(writer, self)?;
+        Ok(())
+    }
+
+    /// Reads the header and checks that the binary is of the `expected` kind and compatible with this version
+    /// of nlprule. Leaves the reader at the start of the serialized component.
+    ///
+    /// # Errors
+    /// - [Error::MissingBinaryHeader][crate::Error::MissingBinaryHeader] if the data does not start with a header.
+    /// - [Error::IncompatibleBinary][crate::Error::IncompatibleBinary] if the binary format or the versions
+    ///   are not compatible.
+    /// - [Error::UnexpectedBinaryKind][crate::Error::UnexpectedBinaryKind] if the binary is of another kind.
+    pub(crate) fn read<R: Read>(mut reader: R, expected: BinaryKind) -> Result<Self, crate::Error> {
+        let mut magic = [0; 8];
+        match reader.read_exact(&mut magic) {
+            Ok(()) if magic == BinaryHeader::MAGIC => {}
+            Ok(()) => return Err(crate::Error::MissingBinaryHeader),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                return Err(crate::Error::MissingBinaryHeader)
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        let format_version: u32 = bincode::deserialize_from(&mut reader)?;
+        let crate_version: String = bincode::deserialize_from(&mut reader)?;
+        let expected_version = env!("CARGO_PKG_VERSION");
+
+        if format_version != BinaryHeader::FORMAT_VERSION {
+            return Err(crate::Error::IncompatibleBinary {
+                found: format!("{} (binary format {})", crate_version, format_version),
+                expected: format!(
+                    "{} (binary format {})",
+                    expected_version,
+                    BinaryHeader::FORMAT_VERSION
+                ),
+            });
+        }
+        if compatible_version(&crate_version) != compatible_version(expected_version) {
+            return Err(crate::Error::IncompatibleBinary {
+                found: crate_version,
+                expected: expected_version.to_owned(),
+            });
+        }
+
+        let header = BinaryHeader {
+            format_version,
+            crate_version,
+            language: bincode::deserialize_from(&mut reader)?,
+            kind: bincode::deserialize_from(&mut reader)?,
+        };
+
+        if header.kind != expected {
+            return Err(crate::Error::UnexpectedBinaryKind {
+                expected,
+                found: header.kind,
+            });
+        }
+
+        Ok(header)
+    }
+}
+
+/// Estimated heap memory used by the components of a [Tokenizer][crate::Tokenizer] or [Rules][crate::Rules] in bytes.
+/// The estimate is based on the serialized size of each component so it does not include allocator overhead
+/// or caches which are populated at runtime e. g. lazily compiled regexes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryFootprint {
+    /// The name and estimated size of each component.
+    pub components: Vec<(String, usize)>,
+}
+
+impl MemoryFootprint {
+    pub(crate) fn add<T: Serialize + ?Sized>(&mut self, name: &str, component: &T) {
+        let size =
+            bincode::serialized_size(component).expect("serialization to memory can not fail");
+        self.components.push((name.to_owned(), size as usize));
+    }
+
+    /// Gets the estimated size of a component. Components which are not loaded are not included.
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.components
+            .iter()
+            .find(|(x, _)| x == name)
+            .map(|(_, size)| *size)
+    }
+
+    /// Gets the estimated size of all components.
+    pub fn total(&self) -> usize {
+        self.components.iter().map(|(_, size)| size).sum()
+    }
+}
+
+/// One word-level edit between the text flagged by a suggestion and one of its replacements.
+/// See [Suggestion::inline_diff].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InlineEdit {
+    /// Text which is in both the flagged text and the replacement.
+    Equal(String),
+    /// Text which is only in the replacement.
+    Insert(String),
+    /// Text which is only in the flagged text.
+    Delete(String),
+}
+
+impl InlineEdit {
+    /// Gets the text of this edit.
+    pub fn text(&self) -> &str {
+        match self {
+            InlineEdit::Equal(text) | InlineEdit::Insert(text) | InlineEdit::Delete(text) => text,
+        }
+    }
+
+    /// Gets a lowercase string representation of the kind of this edit i. e. "equal", "insert" or "delete".
+    pub fn kind(&self) -> &'static str {
+        match self {
+            InlineEdit::Equal(_) => "equal",
+            InlineEdit::Insert(_) => "insert",
+            InlineEdit::Delete(_) => "delete",
+        }
+    }
+
+    fn push(edits: &mut Vec<InlineEdit>, edit: InlineEdit) {
+        match (edits.last_mut(), edit) {
+            (Some(InlineEdit::Equal(last)), InlineEdit::Equal(text))
+            | (Some(InlineEdit::Insert(last)), InlineEdit::Insert(text))
+            | (Some(InlineEdit::Delete(last)), InlineEdit::Delete(text)) => last.push_str(&text),
+            (_, edit) => edits.push(edit),
+        }
+    }
+}
+
+/// Computes a diff between `before` and `after` on Unicode word boundaries (see [UAX #29](https://unicode.org/reports/tr29/)).
+/// Adjacent edits of the same kind are merged, deletions come before insertions.
+fn word_diff(before: &str, after: &str) -> Vec<InlineEdit> {
+    let before: Vec<&str> = before.split_word_bounds().collect();
+    let after: Vec<&str> = after.split_word_bounds().collect();
+
+    // lcs[i][j] is the length of the longest common subsequence of before[i..] and after[j..]
+    let mut lcs = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            lcs[i][j] = if before[i] == after[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            InlineEdit::push(&mut edits, InlineEdit::Equal(before[i].to_owned()));
+            i += 1;
+            j += 1;
+        } else if i < before.len() && (j == after.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            InlineEdit::push(&mut edits, InlineEdit::Delete(before[i].to_owned()));
+            i += 1;
+        } else {
+            InlineEdit::push(&mut edits, InlineEdit::Insert(after[j].to_owned()));
+            j += 1;
+        }
+    }
+
+    edits
 }
 
 impl Suggestion {
-    /// Shift `start` and `end` to the right by the specified amount.
+    /// The start character index (inclusive).
+    #[deprecated(note = "use `span.char().start` instead")]
+    pub fn start(&self) -> usize {
+        self.span.char().start
+    }
+
+    /// The end character index (exclusive).
+    #[deprecated(note = "use `span.char().end` instead")]
+    pub fn end(&self) -> usize {
+        self.span.char().end
+    }
+
+    /// Shifts the span to the right e. g. to make a suggestion for a sentence relative to the text
+    /// if the sentence starts at `char_offset` and `byte_offset` in the text. See [Span::shift].
+    pub fn shift(&mut self, char_offset: usize, byte_offset: usize) {
+        self.span = self.span.shift(char_offset, byte_offset);
+    }
+
+    /// Shifts the char span to the right by the specified amount. The byte span is not shifted.
+    #[deprecated(note = "use `shift` instead, which also shifts the byte span")]
     pub fn rshift(&mut self, offset: usize) {
-        self.start += offset;
-        self.end += offset;
+        self.shift(offset, 0);
+    }
+
+    /// Gets the text flagged by this suggestion. `text` is the text this suggestion was computed for.
+    /// The flagged text is always a substring of `text` in logical order, regardless of the direction of the text.
+    pub fn flagged_text(&self, text: &str) -> String {
+        text.chars()
+            .skip(self.span.char().start)
+            .take(self.span.char().len())
+            .collect()
+    }
+
+    /// Computes a word-level diff between the text flagged by this suggestion and the replacement at `idx`
+    /// e. g. to highlight only the changed word of a multi-word replacement.
+    /// `text` is the text this suggestion was computed for.
+    ///
+    /// # Panics
+    /// - If `idx` is not a valid index into the replacements.
+    pub fn inline_diff(&self, text: &str, idx: usize) -> Vec<InlineEdit> {
+        word_diff(&self.flagged_text(text), &self.replacements[idx])
+    }
+}
+
+/// An edit which was applied to a text: the chars `original` of the original text were replaced with
+/// the chars `corrected` of the corrected text. See [OffsetMap].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedEdit {
+    /// The char span of the replaced text in the original text.
+    pub original: Range<usize>,
+    /// The char span of the replacement in the corrected text.
+    pub corrected: Range<usize>,
+}
+
+/// What happens when a position strictly inside a span which was replaced by an edit is projected. See [OffsetMap].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProjectionPolicy {
+    /// Move the position to the start of the replacement. The end of a range moves to the end of the replacement instead,
+    /// so a projected range covers the whole replacement.
+    #[default]
+    Clamp,
+    /// Fail with [Deleted].
+    Error,
+}
+
+/// The error of projecting a position which is strictly inside a span which was replaced by an edit
+/// if the [ProjectionPolicy] is [ProjectionPolicy::Error].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("position is inside the span {start}..{end} which was replaced by an edit")]
+pub struct Deleted {
+    /// The start of the replaced span in the text the position was projected from.
+    pub start: usize,
+    /// The end of the replaced span in the text the position was projected from.
+    pub end: usize,
+}
+
+/// Which kind of position is projected. See [OffsetMap].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    Point,
+    RangeStart,
+    RangeEnd,
+}
+
+/// Maps char positions in a text to char positions in the corrected text and back, built from the applied edits.
+/// Used to re-anchor annotations (e. g. entity spans) onto the corrected text.
+/// See [Rules::correct_with_mapping][crate::rules::Rules::correct_with_mapping].
+///
+/// Positions are boundaries between chars i. e. range from zero to the length of the text (inclusive).
+/// Positions at the start or end of a replaced span are kept at the start or end of its replacement.
+/// A position at an insertion (a replacement of an empty span) is kept before the inserted text.
+/// Ranges never grow to include text inserted at their start or end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OffsetMap {
+    edits: Vec<AppliedEdit>,
+    original_len: usize,
+    corrected_len: usize,
+    policy: ProjectionPolicy,
+}
+
+impl OffsetMap {
+    /// Creates a map from edits sorted by position which do not overlap.
+    pub(crate) fn new(edits: Vec<AppliedEdit>, original_len: usize, corrected_len: usize) -> Self {
+        OffsetMap {
+            edits,
+            original_len,
+            corrected_len,
+            policy: ProjectionPolicy::default(),
+        }
+    }
+
+    /// Sets the policy for positions inside replaced spans. [ProjectionPolicy::Clamp] by default.
+    pub fn with_policy(mut self, policy: ProjectionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Gets the policy for positions inside replaced spans.
+    pub fn policy(&self) -> ProjectionPolicy {
+        self.policy
+    }
+
+    /// Gets the applied edits, sorted by position.
+    pub fn edits(&self) -> &[AppliedEdit] {
+        &self.edits
+    }
+
+    /// Gets the length of the original text in chars.
+    pub fn original_len(&self) -> usize {
+        self.original_len
+    }
+
+    /// Gets the length of the corrected text in chars.
+    pub fn corrected_len(&self) -> usize {
+        self.corrected_len
+    }
+
+    fn project_with(
+        &self,
+        offset: usize,
+        backward: bool,
+        anchor: Anchor,
+    ) -> Result<usize, Deleted> {
+        let spans = |edit: &AppliedEdit| {
+            if backward {
+                (edit.corrected.clone(), edit.original.clone())
+            } else {
+                (edit.original.clone(), edit.corrected.clone())
+            }
+        };
+        let len = if backward {
+            self.corrected_len
+        } else {
+            self.original_len
+        };
+        assert!(
+            offset <= len,
+            "position {} is out of bounds for a text with {} chars",
+            offset,
+            len
+        );
+
+        let mut delta = 0isize;
+
+        for edit in self.edits.iter() {
+            let (from, to) = spans(edit);
+            let is_before = offset < from.start
+                || (offset == from.start && !(from.is_empty() && anchor == Anchor::RangeStart));
+
+            if is_before {
+                break;
+            } else if offset >= from.end {
+                delta = to.end as isize - from.end as isize;
+            } else {
+                return match self.policy {
+                    ProjectionPolicy::Clamp if anchor == Anchor::RangeEnd => Ok(to.end),
+                    ProjectionPolicy::Clamp => Ok(to.start),
+                    ProjectionPolicy::Error => Err(Deleted {
+                        start: from.start,
+                        end: from.end,
+                    }),
+                };
+            }
+        }
+
+        Ok((offset as isize + delta) as usize)
+    }
+
+    /// Maps a position in the original text to the position in the corrected text.
+    ///
+    /// # Panics
+    /// - If the position is greater than the length of the original text.
+    pub fn project(&self, offset: usize) -> Result<usize, Deleted> {
+        self.project_with(offset, false, Anchor::Point)
+    }
+
+    /// Maps a range in the original text to the range in the corrected text.
+    ///
+    /// # Panics
+    /// - If the range ends after the end of the original text.
+    pub fn project_range(&self, range: Range<usize>) -> Result<Range<usize>, Deleted> {
+        Ok(self.project_with(range.start, false, Anchor::RangeStart)?
+            ..self.project_with(range.end, false, Anchor::RangeEnd)?)
+    }
+
+    /// Maps a position in the corrected text back to the position in the original text.
+    /// Positions inside replacements are handled according to the [ProjectionPolicy].
+    ///
+    /// # Panics
+    /// - If the position is greater than the length of the corrected text.
+    pub fn project_back(&self, offset: usize) -> Result<usize, Deleted> {
+        self.project_with(offset, true, Anchor::Point)
+    }
+
+    /// Maps a range in the corrected text back to the range in the original text.
+    ///
+    /// # Panics
+    /// - If the range ends after the end of the corrected text.
+    pub fn project_range_back(&self, range: Range<usize>) -> Result<Range<usize>, Deleted> {
+        Ok(self.project_with(range.start, true, Anchor::RangeStart)?
+            ..self.project_with(range.end, true, Anchor::RangeEnd)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binaries_of_compatible_versions_are_accepted() {
+        assert_eq!(compatible_version("0.4.7-pre"), "0.4");
+        assert_eq!(compatible_version("1.2.3"), "1");
+
+        let mut info = ModelInfo::default();
+        assert!(info.check_compatible().is_ok());
+
+        info.crate_version = env!("CARGO_PKG_VERSION").to_owned();
+        assert!(info.check_compatible().is_ok());
+
+        info.crate_version = "0.1.0".to_owned();
+        assert!(matches!(
+            info.check_compatible(),
+            Err(crate::Error::IncompatibleBinary { .. })
+        ));
+    }
+
+    #[test]
+    fn binary_headers_are_checked() {
+        let header = |header: BinaryHeader| {
+            let mut data = Vec::new();
+            header.write(&mut data).unwrap();
+            data.extend_from_slice(b"body");
+            data
+        };
+        let current = BinaryHeader::new(BinaryKind::Rules, "en");
+
+        let data = header(current.clone());
+        let mut reader = &data[..];
+        assert_eq!(
+            BinaryHeader::read(&mut reader, BinaryKind::Rules).unwrap(),
+            current
+        );
+        // the reader is left at the start of the body
+        assert_eq!(reader, b"body");
+
+        assert!(matches!(
+            BinaryHeader::read(&data[..], BinaryKind::Tokenizer),
+            Err(crate::Error::UnexpectedBinaryKind {
+                expected: BinaryKind::Tokenizer,
+                found: BinaryKind::Rules
+            })
+        ));
+
+        let data = header(BinaryHeader {
+            crate_version: "0.1.0".into(),
+            ..current.clone()
+        });
+        assert!(matches!(
+            BinaryHeader::read(&data[..], BinaryKind::Rules),
+            Err(crate::Error::IncompatibleBinary { found, .. }) if found == "0.1.0"
+        ));
+
+        let data = header(BinaryHeader {
+            format_version: BinaryHeader::FORMAT_VERSION + 1,
+            ..current
+        });
+        assert!(matches!(
+            BinaryHeader::read(&data[..], BinaryKind::Rules),
+            Err(crate::Error::IncompatibleBinary { .. })
+        ));
+
+        // binaries of old versions start with the serialized component
+        for data in &[&[0u8; 16][..], &[1, 0, 0]] {
+            assert!(matches!(
+                BinaryHeader::read(*data, BinaryKind::Rules),
+                Err(crate::Error::MissingBinaryHeader)
+            ));
+        }
+    }
+
+    #[test]
+    fn suggestions_of_older_versions_can_be_deserialized() {
+        // the shape of a serialized suggestion before spans, segment types and replacement distances were added
+        let suggestion: Suggestion = serde_json::from_str(
+            r#"{"source": "RULE", "message": "Did you mean?", "start": 2, "end": 5, "replacements": ["can"]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(suggestion.source, "RULE");
+        assert_eq!(suggestion.span.char(), 2..5);
+        assert_eq!(suggestion.span.byte(), 0..0);
+        assert_eq!(suggestion.replacements, vec!["can".to_owned()]);
+        assert_eq!(suggestion.segment_type, SegmentType::Sentence);
+        assert_eq!(suggestion.replacement_distance, 0);
+        assert_eq!(suggestion.severity, Severity::default());
+    }
+
+    #[test]
+    fn suggestions_with_invalid_spans_are_rejected() {
+        for json in &[
+            r#"{"source": "RULE", "message": "", "start": 5, "end": 2, "replacements": []}"#,
+            r#"{"source": "RULE", "message": "", "start": 2, "end": 5, "byte_start": 5, "byte_end": 2, "replacements": []}"#,
+        ] {
+            let error = serde_json::from_str::<Suggestion>(json).unwrap_err();
+            assert!(error.to_string().contains("invalid span"), "{}", error);
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn suggestions_can_be_shifted() {
+        let mut suggestion: Suggestion = serde_json::from_str(
+            r#"{"source": "RULE", "message": "", "start": 2, "end": 5, "byte_start": 2, "byte_end": 5, "replacements": []}"#,
+        )
+        .unwrap();
+
+        suggestion.shift(1, 2);
+        assert_eq!(suggestion.span, Span::new(3..6, 4..7));
+
+        suggestion.rshift(10);
+        assert_eq!(suggestion.span, Span::new(13..16, 4..7));
+        assert_eq!((suggestion.start(), suggestion.end()), (13, 16));
+    }
+
+    #[test]
+    fn segment_type_from_markdown_line() {
+        assert_eq!(
+            SegmentType::from_markdown_line("## Introduction"),
+            (SegmentType::Heading, "Introduction")
+        );
+        assert_eq!(
+            SegmentType::from_markdown_line("- fix the bug"),
+            (SegmentType::ListItem, "fix the bug")
+        );
+        assert_eq!(
+            SegmentType::from_markdown_line("  * nested item"),
+            (SegmentType::ListItem, "nested item")
+        );
+        assert_eq!(
+            SegmentType::from_markdown_line("12. numbered item"),
+            (SegmentType::ListItem, "numbered item")
+        );
+        assert_eq!(
+            SegmentType::from_markdown_line("#hashtag at the start."),
+            (SegmentType::Sentence, "#hashtag at the start.")
+        );
+        assert_eq!(
+            SegmentType::from_markdown_line("2021 was a year."),
+            (SegmentType::Sentence, "2021 was a year.")
+        );
+    }
+
+    fn suggestion(text: &str, start: usize, end: usize, replacement: &str) -> Suggestion {
+        Suggestion {
+            source: "TEST".into(),
+            message: String::new(),
+            short_message: String::new(),
+            url: None,
+            severity: Severity::default(),
+            span: Span::from_chars(text, start..end),
+            replacements: vec![replacement.into()],
+            segment_type: SegmentType::Sentence,
+            replacement_distance: 0,
+        }
+    }
+
+    #[test]
+    fn inline_diff_finds_changed_words() {
+        use InlineEdit::*;
+
+        let text = "He has went to the big old house.";
+
+        assert_eq!(
+            suggestion(text, 3, 11, "has gone").inline_diff(text, 0),
+            vec![
+                Equal("has ".into()),
+                Delete("went".into()),
+                Insert("gone".into())
+            ]
+        );
+        // inserted and deleted words
+        assert_eq!(
+            suggestion(text, 15, 32, "the big house").inline_diff(text, 0),
+            vec![
+                Equal("the big ".into()),
+                Delete("old ".into()),
+                Equal("house".into())
+            ]
+        );
+        assert_eq!(
+            suggestion(text, 3, 11, "has not gone").inline_diff(text, 0),
+            vec![
+                Equal("has ".into()),
+                Delete("went".into()),
+                Insert("not gone".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_diff_uses_unicode_word_boundaries() {
+        use InlineEdit::*;
+
+        // a no-break space between the words and an accented word
+        let text = "Das ist über\u{a0}alles schön.";
+        let edits = suggestion(text, 8, 18, "über\u{a0}allem").inline_diff(text, 0);
+
+        assert_eq!(
+            edits,
+            vec![
+                Equal("über\u{a0}".into()),
+                Delete("alles".into()),
+                Insert("allem".into())
+            ]
+        );
+        assert_eq!(
+            edits
+                .iter()
+                .filter(|x| x.kind() != "insert")
+                .map(|x| x.text())
+                .collect::<String>(),
+            "über\u{a0}alles"
+        );
+    }
+
+    #[test]
+    fn segment_type_roundtrips_through_str() {
+        for segment_type in &[
+            SegmentType::Sentence,
+            SegmentType::Heading,
+            SegmentType::ListItem,
+            SegmentType::Caption,
+            SegmentType::Fragment,
+        ] {
+            assert_eq!(segment_type.as_str().parse(), Ok(*segment_type));
+        }
+
+        assert!("paragraph".parse::<SegmentType>().is_err());
+    }
+
+    #[test]
+    fn span_conversions_agree() {
+        for text in &["", "abc", "Straße ist läng", "a😀b 😀😀 c", "שלום 😊 world"] {
+            let n_chars = text.chars().count();
+
+            for start in 0..=n_chars {
+                for end in start..=n_chars {
+                    let span = Span::from_chars(text, start..end);
+                    let expected: String = text.chars().skip(start).take(end - start).collect();
+
+                    assert_eq!(span.char(), start..end);
+                    assert_eq!(span.slice(text), expected);
+                    assert_eq!(span.is_empty(), start == end);
+                    assert_eq!(Span::from_bytes(text, span.byte()), span);
+
+                    let utf16: Vec<_> = text.encode_utf16().collect();
+                    let range = span.utf16(text);
+                    assert_eq!(
+                        String::from_utf16(&utf16[range]).unwrap(),
+                        expected,
+                        "{}",
+                        text
+                    );
+                }
+            }
+        }
+
+        let span = Span::from_chars("a😀b", 1..3);
+        assert_eq!(span.byte(), 1..6);
+        assert_eq!(span.utf16("a😀b"), 1..4);
+    }
+
+    #[test]
+    fn span_relations() {
+        let text = "ä b c";
+        let outer = Span::from_chars(text, 0..3);
+        let inner = Span::from_chars(text, 1..2);
+        let empty = Span::from_chars(text, 1..1);
+
+        assert!(outer.contains(&inner));
+        assert!(!inner.contains(&outer));
+        assert!(outer.contains(&empty));
+        assert!(outer.intersects(&inner));
+        assert!(!outer.intersects(&Span::from_chars(text, 3..5)));
+        assert!(outer.intersects(&empty));
+        assert!(!inner.intersects(&empty));
+        assert!(!empty.intersects(&empty));
+
+        let shifted = inner.shift(10, 20);
+        assert_eq!(shifted.char(), 11..12);
+        assert_eq!(shifted.byte(), 22..23);
+        assert_eq!(shifted.unshift(10, 20), inner);
     }
 }
@@ -27,6 +27,24 @@ pub fn is_uppercase(string: &str) -> bool {
     !string.chars().any(|x| x.is_lowercase())
 }
 
+/// Whether the text is written in all caps e. g. "THERE IS". Needs at least two uppercase letters,
+/// so "I" or "A" at the start of a sentence are not all caps.
+pub fn is_all_caps(string: &str) -> bool {
+    string.chars().filter(|x| x.is_uppercase()).count() >= 2 && is_uppercase(string)
+}
+
+/// Uppercases the first letter of the text. Leading apostrophes are skipped e. g. "'tis" becomes "'Tis".
+pub fn uppercase_first_letter(string: &str) -> String {
+    let n_apostrophes = string
+        .chars()
+        .take_while(|x| matches!(x, '\'' | '’'))
+        .map(char::len_utf8)
+        .sum();
+    let (apostrophes, rest) = string.split_at(n_apostrophes);
+
+    apostrophes.to_owned() + &apply_to_first(rest, |x| x.to_uppercase().collect())
+}
+
 // remove duplicate whitespaces
 pub fn normalize_whitespace(string: &str) -> String {
     lazy_static! {
@@ -36,6 +54,25 @@ pub fn normalize_whitespace(string: &str) -> String {
     REGEX.replace_all(string, "$1")
 }
 
+/// The Levenshtein distance between two strings, counted in chars.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut distances: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous = distances[0];
+        distances[0] = i + 1;
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous + if a_char == *b_char { 0 } else { 1 };
+            previous = distances[j + 1];
+            distances[j + 1] = substitution.min(distances[j] + 1).min(previous + 1);
+        }
+    }
+
+    distances[b.len()]
+}
+
 #[inline]
 pub fn splitting_chars() -> &'static str {
     r##"¡¿«»'’`´‘],.:;!?/\()<=>„“”"+#…*"##
@@ -60,3 +97,145 @@ pub fn fix_nospace_chars(text: &str) -> String {
         .map(|x| x.1)
         .collect()
 }
+
+/// A strong text direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// E. g. Latin or Cyrillic script.
+    LeftToRight,
+    /// E. g. Hebrew or Arabic script.
+    RightToLeft,
+}
+
+/// Gets the strong direction of a char. Letters of right-to-left scripts (Hebrew, Arabic, Syriac, Thaana, N'Ko etc.)
+/// are right-to-left, other letters are left-to-right. Neutral chars like digits, punctuation and whitespace have no direction.
+pub fn strong_direction(c: char) -> Option<Direction> {
+    match c {
+        '\u{0590}'..='\u{08FF}'
+        | '\u{FB1D}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFF}'
+        | '\u{10800}'..='\u{10FFF}'
+        | '\u{1E800}'..='\u{1EFFF}'
+            if c.is_alphabetic() =>
+        {
+            Some(Direction::RightToLeft)
+        }
+        c if c.is_alphabetic() => Some(Direction::LeftToRight),
+        _ => None,
+    }
+}
+
+/// A pair of straight quotes (`"` or `'`) in a text, see [pair_straight_quotes].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotePair {
+    /// The char index of the opening quote.
+    pub open: usize,
+    /// The char index of the closing quote.
+    pub close: usize,
+    /// The number of pairs this pair is nested in e. g. 1 for the inner pair of `"He said 'hi'."`.
+    pub depth: usize,
+}
+
+/// Pairs the straight quotes in a text, in the order of the closing quotes. A quote opens if it is followed by
+/// a non-whitespace char and not preceded by a letter, digit or closing punctuation. A quote closes if it is
+/// preceded by a non-whitespace char and not followed by a letter or digit. Apostrophes within words (e. g. "it's"),
+/// quotes which could both open and close and quotes without a partner are not paired.
+pub fn pair_straight_quotes(text: &str) -> Vec<QuotePair> {
+    let chars: Vec<_> = text.chars().collect();
+    let mut open: Vec<(char, usize)> = Vec::new();
+    let mut pairs = Vec::new();
+
+    for (i, c) in chars.iter().copied().enumerate() {
+        if c != '"' && c != '\'' {
+            continue;
+        }
+
+        let prev = if i > 0 { Some(chars[i - 1]) } else { None };
+        let next = chars.get(i + 1).copied();
+
+        let can_open = matches!(next, Some(x) if !x.is_whitespace())
+            && !matches!(prev, Some(x) if !x.is_whitespace() && !"([{<-–—/\"'".contains(x));
+        let can_close = matches!(prev, Some(x) if !x.is_whitespace())
+            && !matches!(next, Some(x) if x.is_alphanumeric());
+
+        match (can_open, can_close) {
+            (true, false) => open.push((c, i)),
+            (false, true) => {
+                // an unmatched closing `'` is an apostrophe e. g. "the dogs' bones"
+                if let Some(index) = open.iter().rposition(|(x, _)| *x == c) {
+                    pairs.push(QuotePair {
+                        open: open[index].1,
+                        close: i,
+                        depth: index,
+                    });
+                    // quotes opened inside this pair which were not closed are unpaired
+                    open.truncate(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levenshtein_counts_chars() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("house", ""), 5);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("was not been", "has not been"), 1);
+        assert_eq!(levenshtein("Straße", "Strasse"), 2);
+    }
+
+    #[test]
+    fn case_helpers() {
+        assert!(is_all_caps("THERE"));
+        assert!(is_all_caps("DON'T DO IT"));
+        assert!(!is_all_caps("I"));
+        assert!(!is_all_caps("There"));
+        assert!(!is_all_caps("ÉTÉ été"));
+
+        assert_eq!(uppercase_first_letter("their house"), "Their house");
+        assert_eq!(uppercase_first_letter("'tis"), "'Tis");
+        assert_eq!(uppercase_first_letter("’em"), "’Em");
+        assert_eq!(uppercase_first_letter("London"), "London");
+        assert_eq!(uppercase_first_letter("'"), "'");
+    }
+
+    #[test]
+    fn strong_directions() {
+        assert_eq!(strong_direction('a'), Some(Direction::LeftToRight));
+        assert_eq!(strong_direction('ß'), Some(Direction::LeftToRight));
+        assert_eq!(strong_direction('ש'), Some(Direction::RightToLeft));
+        assert_eq!(strong_direction('ع'), Some(Direction::RightToLeft));
+        assert_eq!(strong_direction('1'), None);
+        assert_eq!(strong_direction('.'), None);
+    }
+
+    #[test]
+    fn straight_quotes_are_paired() {
+        let pairs = |text: &str| -> Vec<_> {
+            pair_straight_quotes(text)
+                .into_iter()
+                .map(|x| (x.open, x.close, x.depth))
+                .collect()
+        };
+
+        assert_eq!(pairs(r#"He said "hi"."#), vec![(8, 11, 0)]);
+        assert_eq!(
+            pairs(r#""She said 'it's fine', didn't she?""#),
+            vec![(10, 20, 1), (0, 34, 0)]
+        );
+        // apostrophes are not quotes
+        assert_eq!(pairs("It's the dogs' bone, isn't it?"), vec![]);
+        // unpaired quotes are skipped
+        assert_eq!(pairs(r#"He said "hi."#), vec![]);
+        assert_eq!(pairs(r#"A " B "c""#), vec![(6, 8, 0)]);
+        assert_eq!(pairs(r#""a 'b" c'"#), vec![(0, 5, 0)]);
+    }
+}
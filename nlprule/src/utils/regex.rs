@@ -61,12 +61,28 @@ impl Regex {
         }
     }
 
+    /// The pattern string of this regex.
+    #[cfg(feature = "compile")]
+    pub fn as_str(&self) -> &str {
+        &self.regex_str
+    }
+
     /// Check whether the pattern compiles as a valid regex.
-    #[allow(dead_code)] // used only in compile module
     pub fn try_compile(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
         regex_impl::Regex::new(&self.regex_str).map(|_| ())
     }
 
+    /// Compiles the regex now instead of on first use. Panics if the regex is invalid, like the first use would.
+    pub fn compile(&self) {
+        self.regex();
+    }
+
+    /// Whether the regex has already been compiled.
+    #[allow(dead_code)] // used only in tests
+    pub(crate) fn is_compiled(&self) -> bool {
+        self.regex.borrow().is_some()
+    }
+
     fn regex(&self) -> &regex_impl::Regex {
         if let Some(regex) = self.regex.borrow() {
             regex
@@ -1,7 +1,19 @@
-use std::convert::TryInto;
+use std::{
+    convert::TryInto,
+    time::{Duration, Instant},
+};
 
 use lazy_static::lazy_static;
-use nlprule::{rule::id::Category, Rules, Tokenizer};
+use nlprule::{
+    assert_clean, assert_suggests,
+    rule::id::{Category, Selector},
+    rules::{
+        ApplyOptions, ApplyOutput, CorrectOptions, RulesConfig, SuppressionReason, TextPattern,
+    },
+    tokenizer::{detokenize, export::TokenReader, TokenizerOptions},
+    types::{SegmentType, Suggestion},
+    Error, Rules, Tokenizer,
+};
 use quickcheck_macros::quickcheck;
 
 const TOKENIZER_PATH: &str = "../storage/en_tokenizer.bin";
@@ -23,6 +35,210 @@ fn can_tokenize_anything(text: String) -> bool {
     true
 }
 
+#[test]
+fn batch_pipe_matches_serial_pipe() {
+    let sentences: Vec<String> = (0..3000)
+        .map(|i| match i % 3 {
+            0 => format!("I can due his homework {} times.", i),
+            1 => format!("She was not been here since Monday number {}.", i),
+            _ => format!("The {} houses were build in the bluehouse district.", i),
+        })
+        .collect();
+    let sentences: Vec<&str> = sentences.iter().map(|x| x.as_str()).collect();
+
+    let batch = TOKENIZER.pipe_sentence_batch(&sentences, None);
+    assert_eq!(batch.len(), sentences.len());
+    for (sentence, tokens) in sentences.iter().zip(batch) {
+        assert_eq!(
+            format!("{:?}", tokens),
+            format!("{:?}", TOKENIZER.pipe_sentence(sentence))
+        );
+    }
+}
+
+#[test]
+fn warm_up_does_not_change_suggestions() {
+    let rules = Rules::new(RULES_PATH).unwrap();
+    rules.warm_up();
+
+    for rule in RULES.rules().iter().step_by(10) {
+        for example in rule.examples() {
+            let tokens = TOKENIZER.pipe_sentence(example.text());
+
+            assert_eq!(
+                format!("{:?}", RULES.apply(&tokens, &TOKENIZER)),
+                format!("{:?}", rules.apply(&tokens, &TOKENIZER)),
+                "{}",
+                example.text()
+            );
+        }
+    }
+}
+
+#[test]
+fn prefilter_does_not_change_suggestions() {
+    let unfiltered = ApplyOptions {
+        disable_prefilter: true,
+        ..ApplyOptions::default()
+    };
+
+    for rule in RULES.rules() {
+        for example in rule.examples() {
+            let tokens = TOKENIZER.pipe_sentence(example.text());
+
+            assert_eq!(
+                format!("{:?}", RULES.apply(&tokens, &TOKENIZER)),
+                format!(
+                    "{:?}",
+                    RULES
+                        .apply_with_options(&tokens, &TOKENIZER, &unfiltered)
+                        .active
+                ),
+                "{}",
+                example.text()
+            );
+        }
+    }
+}
+
+/// Checks that detokenizing the tokens reproduces each sentence exactly, up to trailing whitespace
+/// and invisible chars. Returns the number of tokens whose text was normalized.
+fn roundtrips(tokenizer: &Tokenizer, text: &str) -> Option<usize> {
+    let mut n_normalized = 0;
+
+    for tokens in tokenizer.pipe(text) {
+        let (sentence, end) = match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => (first.sentence, last.span.byte().end),
+            _ => continue,
+        };
+
+        let rest_is_invisible = sentence[end..]
+            .chars()
+            .all(|c| c.is_whitespace() || tokenizer.options().invisible_chars.contains(&c));
+        if detokenize(&tokens) != sentence[..end] || !rest_is_invisible {
+            return None;
+        }
+
+        n_normalized += tokens
+            .iter()
+            .filter(|x| x.word.text.as_ref() != x.original_text)
+            .count();
+    }
+
+    Some(n_normalized)
+}
+
+#[test]
+fn original_text_roundtrips() {
+    let mut rejoining = Tokenizer::new(TOKENIZER_PATH).unwrap();
+    rejoining.options_mut().rejoin_hyphenation = true;
+
+    for (tokenizer, text) in &[
+        (
+            &*TOKENIZER,
+            "She said: “I can’t go.”  Then\tleft.\n\nNew paragraph.",
+        ),
+        (
+            &*TOKENIZER,
+            "Non\u{a0}breaking space and e\u{301}\u{301} combining marks.",
+        ),
+        (&*TOKENIZER, "   leading and trailing whitespace   "),
+        (&*TOKENIZER, "ÄÖÜ ß straße. U.K. e.g. Mr. Smith..."),
+    ] {
+        assert_eq!(roundtrips(tokenizer, text), Some(0), "{}", text);
+    }
+
+    // the text of these tokens differs from the original text
+    for (tokenizer, text) in &[
+        (
+            &*TOKENIZER,
+            "Emoji 👩‍👩‍👧 and soft\u{ad}hyphen and zero\u{200b}width \u{200b} here.",
+        ),
+        (&rejoining, "The infor-\nmation is well-\nknown."),
+    ] {
+        assert!(roundtrips(tokenizer, text).unwrap() > 0, "{}", text);
+    }
+}
+
+#[quickcheck]
+fn original_text_roundtrips_anything(text: String) -> bool {
+    roundtrips(&TOKENIZER, &text).is_some()
+}
+
+#[test]
+fn has_issues_agrees_with_suggest() {
+    for text in &[
+        "",
+        "She was not been here since Monday.",
+        "She was not here since Monday.",
+        "I can due his homework.",
+        "He wants that you send him an email.",
+        "This is a perfectly fine sentence. So is this one.",
+    ] {
+        let has_suggestions = !RULES.suggest(text, &TOKENIZER).is_empty();
+        assert_eq!(RULES.has_issues(text, &TOKENIZER), has_suggestions);
+    }
+}
+
+#[quickcheck]
+fn has_issues_agrees_with_suggest_anything(text: String) -> bool {
+    let has_suggestions = !RULES.suggest(&text, &TOKENIZER).is_empty();
+    RULES.has_issues(&text, &TOKENIZER) == has_suggestions
+}
+
+#[test]
+fn suppressed_suggestions_can_be_collected() {
+    let options = ApplyOptions {
+        collect_suppressed: true,
+        ..ApplyOptions::default()
+    };
+
+    // disabled by default
+    let output = RULES.suggest_with_options("I can not go", &TOKENIZER, &options);
+    assert!(output.active.is_empty());
+    assert!(output
+        .suppressed
+        .iter()
+        .any(|(_, reason)| *reason == SuppressionReason::Disabled));
+
+    // not collected by default
+    let output = RULES.suggest_with_options("I can not go", &TOKENIZER, &ApplyOptions::default());
+    assert!(output.suppressed.is_empty());
+}
+
+#[test]
+fn segment_types_skip_sentence_rules() {
+    let document = "# introduction\n\n- fix teh bug , then ship\n- I can due his homework\n\nThis is a sentence.";
+    let ignore_ids = &RULES.options().segment_ignore_ids;
+
+    for line in document.lines() {
+        let (segment_type, text) = SegmentType::from_markdown_line(line);
+        let options = ApplyOptions {
+            segment_type,
+            ..ApplyOptions::default()
+        };
+
+        let suggestions = RULES
+            .suggest_with_options(text, &TOKENIZER, &options)
+            .active;
+
+        for suggestion in &suggestions {
+            assert_eq!(suggestion.segment_type, segment_type);
+
+            let id: Selector = suggestion.source.as_str().try_into().unwrap();
+            if let (Selector::Index(id), Some(ids)) = (id, ignore_ids.get(&segment_type)) {
+                assert!(!ids.iter().any(|x| x.is_match(&id)));
+            }
+        }
+
+        // real errors in list items are still caught
+        if text == "I can due his homework" {
+            assert_eq!(segment_type, SegmentType::ListItem);
+            assert!(!suggestions.is_empty());
+        }
+    }
+}
+
 #[test]
 fn rules_can_be_disabled_enabled() {
     let mut rules = Rules::new(RULES_PATH).unwrap();
@@ -55,3 +271,778 @@ fn rules_can_be_disabled_enabled() {
     // enabled now
     assert!(!rules.suggest("I can not go", &*TOKENIZER).is_empty());
 }
+
+#[test]
+fn suggestions_can_be_asserted() {
+    assert_suggests!(RULES, TOKENIZER, "I can due his homework", ["do"]);
+    assert_suggests!(RULES, TOKENIZER, "I can due his homework", []);
+    assert_clean!(RULES, TOKENIZER, "I can do his homework");
+}
+
+#[test]
+#[should_panic]
+fn clean_assertion_fails_for_suggestions() {
+    assert_clean!(RULES, TOKENIZER, "I can due his homework");
+}
+
+#[test]
+fn categories_can_be_disabled() {
+    let mut rules = Rules::new(RULES_PATH).unwrap();
+    let text = "She was not been here since Monday instead off working. I can due his homework.";
+    let category = |source: &str| source.split('/').next().unwrap().to_lowercase();
+
+    let suggestions = rules.suggest(text, &TOKENIZER);
+    assert!(suggestions.len() > 1);
+    let disabled = category(&suggestions[0].source);
+
+    rules.disable_category(&disabled).unwrap();
+    let suggestions = rules.suggest(text, &TOKENIZER);
+    assert!(suggestions.iter().all(|x| category(&x.source) != disabled));
+
+    rules.enable_category(&disabled).unwrap();
+    assert!(rules
+        .suggest(text, &TOKENIZER)
+        .iter()
+        .any(|x| category(&x.source) == disabled));
+}
+
+#[test]
+fn straight_quotes_can_be_made_typographic() {
+    let mut rules = Rules::new(RULES_PATH).unwrap();
+    let quote_fixes = |rules: &Rules, text: &str| -> Vec<_> {
+        rules
+            .suggest(text, &TOKENIZER)
+            .into_iter()
+            .filter(|x| x.source.starts_with("MISC/TYPOGRAPHIC_QUOTES"))
+            .map(|x| (x.span.char().start, x.replacements[0].clone()))
+            .collect()
+    };
+
+    // disabled by default
+    assert!(quote_fixes(&rules, r#"He said "hi"."#).is_empty());
+
+    rules
+        .select_mut(&"misc/typographic_quotes".try_into().unwrap())
+        .for_each(|x| x.enable());
+
+    assert_eq!(
+        quote_fixes(&rules, r#"He said "hi"."#),
+        vec![(8, "“".to_owned()), (11, "”".to_owned())]
+    );
+    assert_eq!(
+        rules.correct(r#""She said 'it's fine', didn't she?""#, &TOKENIZER),
+        "“She said ‘it's fine’, didn't she?”"
+    );
+    // unpaired quotes and apostrophes are left alone
+    assert!(quote_fixes(&rules, r#"He said "hi."#).is_empty());
+    assert!(quote_fixes(&rules, "It's the dogs' bone.").is_empty());
+}
+
+#[test]
+fn config_roundtrips() {
+    let mut rules = Rules::new(RULES_PATH).unwrap();
+
+    rules
+        .select_mut(
+            &Category::new("confused_words")
+                .join("confusion_due_do")
+                .into(),
+        )
+        .for_each(|x| x.disable());
+    rules
+        .select_mut(&"typos/can_not".try_into().unwrap())
+        .for_each(|x| x.enable());
+    rules.disable_category("typos").unwrap();
+    rules.enable(&["typos/can_not"]).unwrap();
+    rules
+        .set_message(
+            &rules.suggest("It is a exciting day.", &TOKENIZER)[0].source,
+            "Use \"an\" here.",
+        )
+        .unwrap();
+    let options = ApplyOptions {
+        case_exceptions: vec!["iPhone".into()],
+        ..ApplyOptions::default()
+    };
+
+    let json = serde_json::to_string(&rules.export_config_with_options(&options)).unwrap();
+    let config: RulesConfig = serde_json::from_str(&json).unwrap();
+
+    let mut fresh = Rules::new(RULES_PATH).unwrap();
+    fresh.apply_config(&config).unwrap();
+    let fresh_options = config.apply_options();
+
+    assert_eq!(config.case_exceptions, vec!["iPhone".to_owned()]);
+    assert_eq!(
+        fresh.export_config_with_options(&fresh_options),
+        rules.export_config_with_options(&options)
+    );
+
+    for text in &[
+        "I can due his homework",
+        "I can not go",
+        "Iphone is a phone.",
+        "She was not been here since Monday.",
+        "It is a exciting day.",
+    ] {
+        // `Suggestion`'s `PartialEq` only compares spans and replacements loosely
+        assert_eq!(
+            format!(
+                "{:?}",
+                fresh
+                    .suggest_with_options(text, &TOKENIZER, &fresh_options)
+                    .active
+            ),
+            format!(
+                "{:?}",
+                rules
+                    .suggest_with_options(text, &TOKENIZER, &options)
+                    .active
+            )
+        );
+    }
+
+    // the category toggle is kept as such, so enabling the category restores its rules
+    rules.enable_category("typos").unwrap();
+    fresh.enable_category("typos").unwrap();
+    assert_eq!(fresh.export_config(), rules.export_config());
+}
+
+#[test]
+fn messages_can_be_overridden() {
+    let mut rules = Rules::new(RULES_PATH).unwrap();
+    let text = "I can due his homework";
+    let id = rules.suggest(text, &TOKENIZER)[0].source.clone();
+
+    rules
+        .set_message(&id, "Consider using \"do\" here.")
+        .unwrap();
+    assert_eq!(
+        rules.suggest(text, &TOKENIZER)[0].message,
+        "Consider using \"do\" here."
+    );
+    assert_eq!(rules.messages()[&id], "Consider using \"do\" here.");
+
+    assert!(matches!(
+        rules.set_message("TYPOS/DOES_NOT_EXIST/0", "Never shown."),
+        Err(Error::UnknownRuleIds(_))
+    ));
+}
+
+#[test]
+fn repeated_words_are_detected() {
+    let repeat_suggestions = |text: &str| -> Vec<_> {
+        RULES
+            .suggest(text, &TOKENIZER)
+            .into_iter()
+            .filter(|x| x.source.starts_with("MISC/ENGLISH_WORD_REPEAT_RULE"))
+            .collect()
+    };
+
+    for text in &[
+        "I went to the the store.",
+        "I went to the\nthe store.",
+        "I went to the, the store.",
+        "The the store is closed.",
+    ] {
+        let suggestions = repeat_suggestions(text);
+
+        assert_eq!(suggestions.len(), 1, "{}", text);
+        assert_eq!(suggestions[0].replacements.len(), 1);
+        assert!(suggestions[0].replacements[0].eq_ignore_ascii_case("the"));
+    }
+
+    for text in &[
+        "She had had enough.",
+        "He said that that was fine.",
+        "It is very, very good.",
+        "The store is closed.",
+    ] {
+        assert!(repeat_suggestions(text).is_empty(), "{}", text);
+    }
+}
+
+#[test]
+fn context_suggestions_stay_in_target() {
+    let sentences = TOKENIZER.pipe("He was here yesterday. I can due his homework. She went home.");
+    assert_eq!(sentences.len(), 3);
+
+    let target = &sentences[1];
+    let suggestions = RULES.apply_in_context(target, &sentences[0], &sentences[2], &TOKENIZER);
+
+    assert!(!suggestions.is_empty());
+    for suggestion in suggestions {
+        assert!(suggestion.span.char().end <= target[0].sentence.chars().count());
+    }
+}
+
+#[test]
+fn dates_are_validated() {
+    // May 4, 2021 was a Tuesday
+    let suggestions = RULES.suggest("We met on Monday, May 4, 2021 at noon.", &TOKENIZER);
+    assert!(suggestions
+        .iter()
+        .any(|x| x.message.contains("Tuesday") && !x.message.contains("{realDay}")));
+
+    assert!(RULES
+        .suggest("We met on Tuesday, May 4, 2021 at noon.", &TOKENIZER)
+        .is_empty());
+
+    // 2020 is a leap year, 2021 is not
+    assert!(!RULES
+        .suggest("The deadline is February 29, 2021.", &TOKENIZER)
+        .is_empty());
+    assert!(RULES
+        .suggest("The deadline is February 29, 2020.", &TOKENIZER)
+        .is_empty());
+}
+
+#[test]
+fn oversized_tokens_are_checked_quickly() {
+    let token = "a".repeat(100_000);
+    let text = format!("I can due his homework {} today.", token);
+
+    let start = Instant::now();
+    let tokens = TOKENIZER.pipe(&text).remove(0);
+    let suggestions = RULES.suggest(&text, &TOKENIZER);
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let oversized = tokens.iter().find(|x| x.oversized).unwrap();
+    assert_eq!(oversized.word.text.as_ref(), token);
+    assert_eq!(oversized.span.char(), 23..100_023);
+    // suggestions before the oversized token are still found
+    assert!(suggestions.iter().any(|x| x.span.char().end <= 23));
+    assert!(suggestions
+        .iter()
+        .all(|x| x.span.char().end <= 23 || x.span.char().start >= 100_023));
+}
+
+#[test]
+fn tokens_know_whether_they_are_known() {
+    let tokens = TOKENIZER.pipe("The hosue is blue.").remove(0);
+    let known: Vec<_> = tokens.iter().skip(1).map(|x| x.is_known).collect();
+    assert_eq!(known, vec![true, false, true, true, true]);
+
+    let mut tokenizer = Tokenizer::new(TOKENIZER_PATH).unwrap();
+    tokenizer.options_mut().known_words = vec!["hosue".to_owned()].into_iter().collect();
+    assert!(tokenizer.is_known("hosue"));
+    assert!(tokenizer.pipe("The hosue is blue.")[0]
+        .iter()
+        .all(|x| x.is_known));
+}
+
+#[test]
+fn fragments_skip_sentence_rules() {
+    // as a sentence, the lowercase start and the missing period could be flagged
+    let suggestions = RULES.check_fragment("blue bicycle", &TOKENIZER);
+    assert!(suggestions.is_empty());
+
+    let tokens = TOKENIZER.pipe_fragment("Blue bicycle. Red car");
+    assert_eq!(tokens.last().unwrap().span.char().end, 21);
+
+    // real errors are still caught
+    let suggestions = RULES.check_fragment("a apple pie", &TOKENIZER);
+    assert!(!suggestions.is_empty());
+    assert!(suggestions
+        .iter()
+        .all(|x| x.segment_type == SegmentType::Fragment && x.span.char().start == 0));
+}
+
+#[test]
+fn sentence_spans_agree_with_pipe() {
+    let text = "A first sentence. Ein zweiter Satz?\nThe end";
+    let spans = TOKENIZER.sentence_spans(text);
+    let sentences = TOKENIZER.pipe(text);
+
+    assert_eq!(spans.len(), sentences.len());
+    assert_eq!(spans.last().unwrap().1, text.chars().count());
+
+    for ((start, end), tokens) in spans.into_iter().zip(sentences) {
+        let sentence: String = text.chars().skip(start).take(end - start).collect();
+        assert_eq!(sentence, tokens[0].sentence);
+
+        let piped = TOKENIZER.pipe_sentence(tokens[0].sentence);
+        assert_eq!(
+            piped.iter().map(|x| x.span).collect::<Vec<_>>(),
+            tokens.iter().map(|x| x.span).collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn read_sentences_agree_with_sentences() {
+    let text = "She was not been here since Monday. I can due his homework.\nA first sentence. Ein zweiter Satz?\n\nThe end".repeat(3);
+    let expected: Vec<_> = TOKENIZER
+        .sentences(&text)
+        .into_iter()
+        .map(|(span, sentence)| (span, sentence.to_owned()))
+        .collect();
+    let suggestions = format!("{:?}", RULES.suggest(&text, &TOKENIZER));
+
+    for chunk_size in [1, 64, 1 << 16] {
+        let sentences: Vec<_> = TOKENIZER
+            .read_sentences(text.as_bytes())
+            .with_chunk_size(chunk_size)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(sentences, expected);
+    }
+
+    let mut streamed = Vec::new();
+    RULES
+        .apply_reader(&TOKENIZER, text.as_bytes(), |x| streamed.push(x))
+        .unwrap();
+    assert_eq!(format!("{:?}", streamed), suggestions);
+}
+
+#[test]
+fn suggestions_are_ordered_deterministically() {
+    let text = "She was not been here since Monday. I can due his homework. He wants see it.";
+    let suggestions = RULES.suggest(text, &TOKENIZER);
+    assert!(suggestions.len() >= 3);
+
+    let key = |x: &Suggestion| (x.span.char().start, x.span.char().end, x.source.clone());
+    assert!(suggestions.windows(2).all(|x| key(&x[0]) <= key(&x[1])));
+
+    // the order is the same across sentences and for the streaming and option variants
+    let streamed: Vec<_> = RULES
+        .apply_stream(&TOKENIZER, TOKENIZER.sentences(text))
+        .collect();
+    let output = RULES.suggest_with_options(text, &TOKENIZER, &ApplyOptions::default());
+    assert_eq!(format!("{:?}", streamed), format!("{:?}", suggestions));
+    assert_eq!(format!("{:?}", output.active), format!("{:?}", suggestions));
+}
+
+#[test]
+fn suggestions_can_be_ignored_by_flagged_text() {
+    let text = "I can due his homework.";
+    assert!(!RULES.suggest(text, &TOKENIZER).is_empty());
+
+    let options = ApplyOptions {
+        ignore_text_patterns: TextPattern::compile_all([r"[A-Z]+-\d+", "due"]).unwrap(),
+        collect_suppressed: true,
+        report: true,
+        ..ApplyOptions::default()
+    };
+    let output = RULES.suggest_with_options(text, &TOKENIZER, &options);
+
+    assert!(output.active.is_empty());
+    assert!(output
+        .suppressed
+        .iter()
+        .any(|(_, reason)| *reason == SuppressionReason::IgnoredText));
+    assert!(output.report.unwrap().ignored_text > 0);
+
+    assert!(matches!(
+        TextPattern::compile_all(["due", "[unclosed"]),
+        Err(Error::InvalidPattern { index: 1, .. })
+    ));
+}
+
+#[test]
+fn exported_tokens_roundtrip() {
+    let texts = [
+        "She was not been here since Monday. The chunks are   here.",
+        "",
+        "co\u{ad}operate with ÜNÏCODE",
+    ];
+    let mut buffer = Vec::new();
+    for text in texts.iter() {
+        TOKENIZER.tokenize_to_writer(text, &mut buffer).unwrap();
+    }
+
+    let documents: Vec<_> = TokenReader::new(buffer.as_slice())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(documents.len(), texts.len());
+
+    for (text, document) in texts.iter().zip(documents) {
+        let sentences = TOKENIZER.pipe(text);
+        assert_eq!(sentences.len(), document.len());
+
+        for (tokens, exported) in sentences.iter().zip(document) {
+            assert!(text[exported.byte_start..].starts_with(tokens[0].sentence));
+            assert_eq!(tokens.len(), exported.tokens.len());
+
+            for (token, exported) in tokens.iter().zip(exported.tokens) {
+                assert_eq!(token.word.text.as_ref(), exported.text);
+                assert_eq!(token.original_text, exported.original_text);
+                assert_eq!(
+                    token.span.char(),
+                    exported.char_span.0..exported.char_span.1
+                );
+                assert_eq!(
+                    token.span.byte(),
+                    exported.byte_span.0..exported.byte_span.1
+                );
+                assert_eq!(token.is_known, exported.is_known);
+                assert_eq!(token.chunks, exported.chunks);
+
+                let tags: Vec<_> = token
+                    .word
+                    .tags
+                    .iter()
+                    .map(|x| (x.lemma.as_ref(), x.pos.as_ref()))
+                    .collect();
+                let exported_tags: Vec<_> = exported
+                    .tags
+                    .iter()
+                    .map(|x| (x.lemma.as_str(), x.pos.as_str()))
+                    .collect();
+                assert_eq!(tags, exported_tags);
+            }
+        }
+    }
+}
+
+#[test]
+fn check_reports_tell_what_was_checked() {
+    let options = ApplyOptions {
+        report: true,
+        ..ApplyOptions::default()
+    };
+    let text = "I can due his homework. This is fine.";
+
+    let report = RULES
+        .suggest_with_options(text, &TOKENIZER, &options)
+        .report
+        .unwrap();
+    assert!(report.is_complete());
+    assert_eq!(report.sentences.len(), 2);
+    assert_eq!(report.sentences[1].char_span.1, text.chars().count());
+
+    let mut tokenizer = Tokenizer::new(TOKENIZER_PATH).unwrap();
+    let footprint = tokenizer.memory_footprint();
+    tokenizer
+        .apply_memory_budget(footprint.total() - footprint.get("chunker").unwrap())
+        .unwrap();
+
+    let report = RULES
+        .suggest_with_options(text, &tokenizer, &options)
+        .report
+        .unwrap();
+    assert!(!report.rules_missing_chunker.is_empty());
+    assert!(!report.is_complete());
+}
+
+#[test]
+fn timings_cover_all_stages() {
+    let text = "I can due his homework. This is fine.";
+    let options = ApplyOptions {
+        timing: true,
+        ..ApplyOptions::default()
+    };
+
+    let output = RULES.suggest_with_options(text, &TOKENIZER, &options);
+    let timing = output.timing.unwrap();
+    assert!(timing.tokenize > 0 && timing.rules > 0);
+    assert!(timing.total >= timing.tokenize + timing.disambiguate + timing.rules);
+    assert_eq!(output.active, RULES.suggest(text, &TOKENIZER));
+
+    let (sentences, timing) = TOKENIZER.pipe_timed(text);
+    assert_eq!(
+        format!("{:?}", sentences),
+        format!("{:?}", TOKENIZER.pipe(text))
+    );
+    assert_eq!(timing.rules, 0);
+
+    let mut streamed = ApplyOutput::default();
+    for output in
+        RULES.try_apply_stream_with_options(&TOKENIZER, TOKENIZER.sentences(text), &options)
+    {
+        streamed.append(output.unwrap(), 0, 0);
+    }
+    let timing = streamed.timing.unwrap();
+    assert_eq!(
+        timing.total,
+        timing.tokenize + timing.disambiguate + timing.rules
+    );
+
+    let output = RULES.suggest_with_options(text, &TOKENIZER, &ApplyOptions::default());
+    assert!(output.timing.is_none());
+}
+
+#[test]
+fn long_documents_are_truncated_between_sentences() {
+    let text = "I can due his homework. This is fine. He wants see it. I can due this.";
+    let n_sentences = TOKENIZER.sentences(text).len();
+
+    for max_chars in 0..=text.chars().count() {
+        let options = ApplyOptions {
+            max_chars: Some(max_chars),
+            report: true,
+            ..ApplyOptions::default()
+        };
+        let output = RULES.suggest_with_options(text, &TOKENIZER, &options);
+        let report = output.report.unwrap();
+
+        let checked: usize = report
+            .sentences
+            .iter()
+            .map(|x| x.char_span.1 - x.char_span.0)
+            .sum();
+        assert!(checked <= max_chars);
+
+        match report.truncated_at {
+            Some(truncated_at) => {
+                assert!(!report.is_complete());
+                assert_eq!(truncated_at.sentence_index, report.sentences.len());
+                assert_eq!(truncated_at.char_offset, checked);
+
+                // the checked sentences and the unchecked remainder reconstruct the input
+                let checked_text: String = report
+                    .sentences
+                    .iter()
+                    .map(|x| {
+                        text.chars()
+                            .skip(x.char_span.0)
+                            .take(x.char_span.1 - x.char_span.0)
+                            .collect::<String>()
+                    })
+                    .collect();
+                assert_eq!(checked_text + &text[truncated_at.byte_offset..], text);
+                assert!(output
+                    .active
+                    .iter()
+                    .all(|x| x.span.byte().end <= truncated_at.byte_offset));
+            }
+            None => assert_eq!(report.sentences.len(), n_sentences),
+        }
+    }
+
+    let options = ApplyOptions {
+        max_sentences: Some(1),
+        report: true,
+        ..ApplyOptions::default()
+    };
+    let output = RULES.suggest_with_options(text, &TOKENIZER, &options);
+    let truncated_at = output.report.unwrap().truncated_at.unwrap();
+    assert_eq!(truncated_at.sentence_index, 1);
+    assert_eq!(
+        &text[truncated_at.byte_offset..],
+        "This is fine. He wants see it. I can due this."
+    );
+    assert_eq!(output.active.len(), 1);
+
+    let mut streamed = ApplyOutput::default();
+    for output in
+        RULES.try_apply_stream_with_options(&TOKENIZER, TOKENIZER.sentences(text), &options)
+    {
+        streamed.append(output.unwrap(), 0, 0);
+    }
+    assert_eq!(streamed.report.unwrap().truncated_at, Some(truncated_at));
+}
+
+#[test]
+fn memory_budget_is_respected() {
+    let mut tokenizer = Tokenizer::new(TOKENIZER_PATH).unwrap();
+    let footprint = tokenizer.memory_footprint();
+    let chunker_size = footprint.get("chunker").unwrap();
+
+    let budget = footprint.total() - chunker_size;
+    let footprint = tokenizer.apply_memory_budget(budget).unwrap();
+    assert!(footprint.total() <= budget);
+    assert!(footprint.get("chunker").is_none());
+
+    let mut rules = Rules::new(RULES_PATH).unwrap();
+    rules
+        .select_mut(&Category::new("STYLE").into())
+        .for_each(|x| x.disable());
+    let budget = rules.memory_footprint().get("rules").unwrap();
+    let footprint = rules.apply_memory_budget(budget).unwrap();
+    assert!(footprint.total() <= budget);
+
+    // still produces suggestions, just without the chunker and style rules
+    assert!(!rules
+        .suggest("She was not been here since Monday.", &tokenizer)
+        .is_empty());
+    assert!(tokenizer.apply_memory_budget(0).is_err());
+}
+
+#[test]
+fn soft_hyphens_are_transparent() {
+    let text = "I can d\u{ad}ue his home\u{ad}work.";
+
+    let suggestions = RULES.suggest(text, &TOKENIZER);
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].span.char(), 6..10);
+
+    assert_eq!(
+        RULES.correct(text, &TOKENIZER),
+        "I can do his home\u{ad}work."
+    );
+}
+
+#[test]
+fn binaries_have_info() {
+    for info in &[TOKENIZER.info(), RULES.info()] {
+        assert_eq!(info.language, "en");
+        assert_eq!(info.build_date.len(), "2021-05-04".len());
+        assert!(!info.checksum.is_empty());
+    }
+}
+
+#[test]
+fn mixed_direction_text_is_corrected_in_logical_order() {
+    let text = "He said שלום עולם to to me. She quoted مرحبا بالعالم in in her talk.";
+    let suggestions = RULES.suggest(text, &TOKENIZER);
+    assert!(suggestions.len() >= 2);
+
+    for suggestion in &suggestions {
+        let start = text
+            .char_indices()
+            .nth(suggestion.span.char().start)
+            .unwrap()
+            .0;
+        let end = text
+            .char_indices()
+            .nth(suggestion.span.char().end)
+            .map_or(text.len(), |x| x.0);
+        assert_eq!(suggestion.flagged_text(text), &text[start..end]);
+        assert_eq!(suggestion.span.byte(), start..end);
+    }
+
+    let corrected = RULES.correct(text, &TOKENIZER);
+    assert!(corrected.contains("שלום עולם to me."));
+    assert!(corrected.contains("مرحبا بالعالم in her talk."));
+
+    let isolated = RULES.correct_with_options(
+        text,
+        &TOKENIZER,
+        &CorrectOptions {
+            isolate_bidi: true,
+            ..CorrectOptions::default()
+        },
+    );
+    assert!(isolated.contains("שלום עולם"));
+    assert!(isolated.contains("مرحبا بالعالم"));
+}
+
+#[test]
+fn streamed_suggestions_agree_with_suggest() {
+    let texts = [
+        "I can due his homework. He wants see it.",
+        "Die Straße ist lang.   Ünïcödé 😊 He wants see it!\nI can due this. 😊😊",
+        "😊 I can due his homework.😊He wants see it. ",
+    ];
+
+    for text in &texts {
+        let expected = RULES.suggest(text, &TOKENIZER);
+        let streamed: Vec<_> = RULES
+            .apply_stream(&TOKENIZER, TOKENIZER.sentences(text))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(
+            format!("{:?}", streamed),
+            format!("{:?}", expected),
+            "{}",
+            text
+        );
+
+        let options = ApplyOptions {
+            collect_suppressed: true,
+            ..ApplyOptions::default()
+        };
+        let expected = RULES.suggest_with_options(text, &TOKENIZER, &options);
+        let mut streamed = ApplyOutput::default();
+        for output in
+            RULES.try_apply_stream_with_options(&TOKENIZER, TOKENIZER.sentences(text), &options)
+        {
+            streamed.append(output.unwrap(), 0, 0);
+        }
+        assert_eq!(format!("{:?}", streamed), format!("{:?}", expected));
+    }
+}
+
+#[test]
+fn hyphenation_at_line_breaks_can_be_rejoined() {
+    let options = TokenizerOptions::builder()
+        .rejoin_hyphenation(true)
+        .build()
+        .unwrap();
+    let tokenizer = Tokenizer::new_with_options(TOKENIZER_PATH, options).unwrap();
+    let text = "The infor-\nmation is well-\nknown.";
+
+    let texts = |tokenizer: &Tokenizer| -> Vec<String> {
+        tokenizer
+            .pipe(text)
+            .iter()
+            .flatten()
+            .map(|x| x.word.text.as_ref().to_owned())
+            .collect()
+    };
+    assert!(!texts(&TOKENIZER).contains(&"information".to_owned()));
+    assert!(texts(&tokenizer).contains(&"information".to_owned()));
+    // "well" and "known" are words, so the compound is not rejoined
+    assert!(!texts(&tokenizer).contains(&"wellknown".to_owned()));
+
+    assert_eq!(RULES.correct(text, &tokenizer), text);
+    let options = CorrectOptions {
+        repair_hyphenation: true,
+        ..CorrectOptions::default()
+    };
+    assert_eq!(
+        RULES.correct_with_options(text, &tokenizer, &options),
+        "The information is well-\nknown."
+    );
+}
+
+const DETERMINISM_CORPUS: &str = include_str!("determinism/corpus.txt");
+const DETERMINISM_EXPECTED_PATH: &str = "tests/determinism/expected.txt";
+
+/// FNV-1a. Unlike the hashers in `std`, the result is guaranteed to be the same on every platform and Rust version.
+fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Hashes the readings after disambiguation, the chunks and the suggestions for a line of the corpus.
+fn determinism_hash(text: &str) -> u64 {
+    let mut output = String::new();
+
+    for tokens in TOKENIZER.pipe(text) {
+        for token in tokens {
+            output.push_str(token.word.text.as_ref());
+            for data in token.word.tags.iter() {
+                output.push_str(&format!(" {}/{}", data.lemma.as_ref(), data.pos.as_ref()));
+            }
+            output.push_str(&format!(" {:?}\n", token.chunks));
+        }
+    }
+    output.push_str(&serde_json::to_string(&RULES.suggest(text, &TOKENIZER)).unwrap());
+
+    stable_hash(output.as_bytes())
+}
+
+/// The readings and suggestions must be the same on every platform (including wasm32) and do not depend on hash seeds.
+/// Run with `NLPRULE_BLESS=1` to record the expected hashes after an intended change.
+#[test]
+fn results_are_deterministic() {
+    let hashes: Vec<_> = DETERMINISM_CORPUS
+        .lines()
+        .map(|line| format!("{:016x}", determinism_hash(line)))
+        .collect();
+
+    if std::env::var_os("NLPRULE_BLESS").is_some() {
+        std::fs::write(DETERMINISM_EXPECTED_PATH, hashes.join("\n") + "\n").unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(DETERMINISM_EXPECTED_PATH).unwrap_or_else(|_| {
+        panic!(
+            "{} is missing, run with NLPRULE_BLESS=1 to record it",
+            DETERMINISM_EXPECTED_PATH
+        )
+    });
+
+    for ((line, hash), expected) in DETERMINISM_CORPUS
+        .lines()
+        .zip(&hashes)
+        .zip(expected.lines())
+    {
+        assert_eq!(hash, expected, "results changed for {:?}", line);
+    }
+    assert_eq!(hashes.len(), expected.lines().count());
+}
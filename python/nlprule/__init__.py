@@ -0,0 +1,2 @@
+from .nlprule import *  # noqa: F401,F403
+from .nlprule import __doc__, __version__, SCHEMA_VERSION  # noqa: F401
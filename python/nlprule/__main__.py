@@ -0,0 +1,80 @@
+"""Command line interface of nlprule.
+
+Prefetch the binaries of some languages, e. g. to bake them into a Docker image:
+
+    python -m nlprule download en de --cache-dir /opt/nlprule
+
+Set the `NLPRULE_CACHE_DIR` environment variable to the same directory to load them offline.
+"""
+import argparse
+import sys
+
+from . import download
+
+
+def main(argv=None):
+    parser = argparse.ArgumentParser(prog="python -m nlprule")
+    subparsers = parser.add_subparsers(dest="command")
+    subparsers.required = True
+
+    download_parser = subparsers.add_parser(
+        "download", help="Download the tokenizer and rules binaries into the cache."
+    )
+    download_parser.add_argument(
+        "lang_codes", nargs="+", metavar="LANG_CODE", help='Language codes e. g. "en".'
+    )
+    download_parser.add_argument(
+        "--components",
+        nargs="+",
+        choices=["tokenizer", "rules"],
+        default=None,
+        help="Which binaries to download. Defaults to both.",
+    )
+    download_parser.add_argument(
+        "--cache-dir",
+        default=None,
+        help="The cache directory. Defaults to NLPRULE_CACHE_DIR or the user cache.",
+    )
+    download_parser.add_argument(
+        "--base-url",
+        default=None,
+        help="The URL template to download from. Defaults to NLPRULE_BINARY_URL.",
+    )
+    download_parser.add_argument(
+        "--timeout",
+        type=float,
+        default=None,
+        help="The connect and read timeout in seconds. Defaults to 30.",
+    )
+    download_parser.add_argument(
+        "--proxy",
+        default=None,
+        help="The URL of the proxy to download through e. g. http://user:pw@host:8080.",
+    )
+
+    args = parser.parse_args(argv)
+
+    failed = False
+    for lang_code in args.lang_codes:
+        try:
+            paths = download(
+                lang_code,
+                components=args.components,
+                cache_dir=args.cache_dir,
+                base_url=args.base_url,
+                timeout=args.timeout,
+                proxy=args.proxy,
+            )
+        except (ValueError, OSError) as error:
+            print(f"{lang_code}: {error}", file=sys.stderr)
+            failed = True
+            continue
+
+        for path in paths:
+            print(path)
+
+    return 1 if failed else 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
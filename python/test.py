@@ -1,6 +1,14 @@
 import pytest
+import gzip
+import hashlib
+import pathlib
 import pickle
-from nlprule import Tokenizer, Rules
+import struct
+import time
+import json
+from concurrent.futures import ThreadPoolExecutor
+import nlprule
+from nlprule import Tokenizer, Rules, SplitOn, TokenReader, set_strictness
 
 
 @pytest.fixture()
@@ -10,6 +18,18 @@ def tokenizer_and_rules():
     return tokenizer, rules
 
 
+def test_binaries_can_be_loaded_from_paths_and_files(tmp_path):
+    tokenizer = Tokenizer(pathlib.Path("storage") / "en_tokenizer.bin")
+
+    with open("storage/en_rules.bin", "rb") as f:
+        rules = Rules(f, tokenizer)
+
+    assert len(rules.suggest("She was not been here since Monday.")) > 0
+
+    with pytest.raises(FileNotFoundError):
+        Tokenizer(tmp_path / "missing.bin")
+
+
 def test_correct(tokenizer_and_rules):
     (_, rules) = tokenizer_and_rules
 
@@ -41,6 +61,52 @@ def test_sentencization_sane(tokenizer_and_rules):
     assert (sentences[1][1].text, sentences[1][2].text) == ("SRX", "is")
 
 
+def test_has_issues(tokenizer_and_rules):
+    (_, rules) = tokenizer_and_rules
+
+    assert rules.has_issues("She was not been here since Monday.")
+    assert not rules.has_issues("She was not here since Monday.")
+    assert rules.has_issues(["I can due his homework.", "I can do his homework."]) == [
+        True,
+        False,
+    ]
+
+
+def test_has_issues_agrees_with_suggest(tokenizer_and_rules):
+    (_, rules) = tokenizer_and_rules
+
+    corpus = [
+        "",
+        "She was not been here since Monday.",
+        "She was not here since Monday.",
+        "I can due his homework.",
+        "He wants that you send him an email.",
+        "It is a exciting day. It is a sunny day.",
+        "This is a perfectly fine sentence. So is this one.",
+    ]
+    assert rules.has_issues(corpus) == [len(x) > 0 for x in rules.suggest(corpus)]
+
+
+def test_has_issues_is_faster_than_suggest(tokenizer_and_rules):
+    (_, rules) = tokenizer_and_rules
+
+    # the issues are at the start, so `has_issues` can stop early while `suggest` checks everything
+    text = "She was not been here since Monday. " + " ".join(
+        ["This is a perfectly fine sentence."] * 50
+    )
+
+    def best_of(f, n=3):
+        times = []
+        for _ in range(n):
+            start = time.perf_counter()
+            f(text)
+            times.append(time.perf_counter() - start)
+        return min(times)
+
+    assert rules.has_issues(text)
+    assert best_of(rules.has_issues) * 5 < best_of(rules.suggest)
+
+
 def test_suggest(tokenizer_and_rules):
     (_, rules) = tokenizer_and_rules
 
@@ -61,6 +127,41 @@ def test_suggest(tokenizer_and_rules):
     )
 
 
+def test_suggest_collect_suppressed(tokenizer_and_rules):
+    (_, rules) = tokenizer_and_rules
+
+    # there is a rule for this but it is turned off
+    result = rules.suggest("I can not go.", collect_suppressed=True)
+    assert len(result.suggestions) == 0
+    assert any(
+        reason == "disabled" and suggestion.source.startswith("TYPOS/CAN_NOT")
+        for (suggestion, reason) in result.suppressed
+    )
+    # only what was requested is set
+    assert result.report is None and result.timings is None
+
+
+def test_suggest_segment_type(tokenizer_and_rules):
+    (_, rules) = tokenizer_and_rules
+
+    suggestions = rules.suggest("I can due his homework", segment_type="list_item")
+    assert len(suggestions) > 0
+    assert all(s.segment_type == "list_item" for s in suggestions)
+
+    with pytest.raises(ValueError):
+        rules.suggest("I can due his homework", segment_type="paragraph")
+
+
+def test_suggest_fragment(tokenizer_and_rules):
+    (_, rules) = tokenizer_and_rules
+
+    assert len(rules.suggest("blue bicycle", fragment=True)) == 0
+
+    suggestions = rules.suggest("a apple pie", fragment=True)
+    assert len(suggestions) > 0
+    assert all(s.segment_type == "fragment" for s in suggestions)
+
+
 def test_rules_inspectable(tokenizer_and_rules):
     (_, rules) = tokenizer_and_rules
 
@@ -71,6 +172,8 @@ def test_rules_inspectable(tokenizer_and_rules):
 
     # metadata of the rule itself
     assert rule.short == "Commonly confused word"
+    assert suggestion.short_message == rule.short
+    assert suggestion.url == rule.url
     assert rule.url == "https://www.merriam-webster.com/dictionary/take%20aback"
     assert rule.id == "CONFUSED_WORDS/BACK_ABACK/0"
     assert rule.name == "taken back (aback) by"
@@ -105,6 +208,79 @@ def test_pickle_roundtrip_works(tokenizer_and_rules):
     (tokenizer, rules) = pickle.loads(dump)
 
     assert len(rules.rules) > 0
+    assert rules.tokenizer is tokenizer
+
+
+def test_pickled_rules_correct_identically(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+    text = "He wants that you send him an email. I can due his homework."
+
+    loaded = pickle.loads(pickle.dumps(rules))
+
+    assert loaded.correct(text) == rules.correct(text)
+
+
+def test_pickle_keeps_picklable_sentence_splitter():
+    tokenizer = Tokenizer(
+        "storage/en_tokenizer.bin", sentence_splitter=SplitOn([".", "!"])
+    )
+    text = "This is one. This is two! And three"
+
+    loaded = pickle.loads(pickle.dumps(tokenizer))
+    assert [len(x) for x in loaded.pipe(text)] == [len(x) for x in tokenizer.pipe(text)]
+
+    # lambdas can not be pickled so the splitter is dropped
+    tokenizer = Tokenizer(
+        "storage/en_tokenizer.bin", sentence_splitter=lambda texts: [[(0, 3)]]
+    )
+    loaded = pickle.loads(pickle.dumps(tokenizer))
+    assert len(loaded.pipe(text)) == 3
+
+
+def test_rules_keep_their_tokenizer(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    assert rules.tokenizer is tokenizer
+
+
+def test_threaded_use_of_shared_tokenizer(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+    text = "She was not been here since Monday."
+    state = tokenizer.__getstate__()
+
+    def work(i):
+        if i % 3 == 0:
+            # mutably borrows the tokenizer
+            tokenizer.__setstate__(state)
+            return None
+        elif i % 3 == 1:
+            return len(tokenizer.pipe(text))
+        else:
+            return len(rules.suggest(text))
+
+    with ThreadPoolExecutor(max_workers=8) as executor:
+        results = list(executor.map(work, range(300)))
+
+    assert all(x == 1 for x in results[1::3])
+    assert all(x == results[2] and x > 0 for x in results[2::3])
+
+
+def test_threaded_checks_match_sequential_checks(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+    text = "She was not been here since Monday. I can due his homework. " * 20
+
+    expected = rules.correct(text)
+    seen = []
+
+    def work(_):
+        # the GIL is released while checking and reacquired for the callback
+        return rules.correct(text, on_suggestion=seen.append)
+
+    with ThreadPoolExecutor(max_workers=4) as executor:
+        results = list(executor.map(work, range(8)))
+
+    assert all(x == expected for x in results)
+    assert len(seen) == 8 * len(rules.suggest(text))
 
 
 def test_invalid_selector_fails(tokenizer_and_rules):
@@ -138,3 +314,1132 @@ def test_rules_can_be_disabled(tokenizer_and_rules):
         rule.disable()
 
     assert len(rules.suggest("I can due his homework")) == 0
+
+
+def test_rules_can_be_toggled_by_id(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+    text = "I can due his homework"
+
+    rules.disable(["CONFUSION_DUE_DO"])
+    assert len(rules.suggest(text)) == 0
+
+    # the state survives pickling
+    assert len(pickle.loads(pickle.dumps(rules)).suggest(text)) == 0
+
+    rules.enable(["confused_words/confusion_due_do"])
+    assert len(rules.suggest(text)) > 0
+
+    with pytest.raises(ValueError, match="(?i)confused_words/confusion_due_do"):
+        rules.disable(["CONFUSION_DUE_DOO"])
+
+
+def test_categories_can_be_disabled(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+    text = "She was not been here since Monday instead off working."
+
+    def categories():
+        return {s.source.split("/")[0].lower() for s in rules.suggest(text)}
+
+    category = sorted(categories())[0]
+    rules.disable_category(category)
+    assert category not in categories()
+
+    rules.enable_category(category)
+    assert category in categories()
+
+    with pytest.raises(ValueError):
+        rules.disable_category("NOT_A_CATEGORY")
+
+
+def test_config_roundtrip(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    for rule in rules.select("confused_words/confusion_due_do"):
+        rule.disable()
+    rules.disable_category("typos")
+    source = rules.suggest("It is a exciting day.")[0].source
+    rules.set_message(source, "Use an article which fits.")
+    rules.set_severity(source, "error")
+    rules.set_case_exceptions(["iPhone"])
+
+    fresh = Rules("storage/en_rules.bin", tokenizer)
+    fresh.apply_config(rules.export_config())
+
+    corpus = ["I can due his homework", "Iphone is a phone.", "It is a exciting day."]
+    assert [[(s.source, s.message) for s in fresh.suggest(x)] for x in corpus] == [
+        [(s.source, s.message) for s in rules.suggest(x)] for x in corpus
+    ]
+
+    # the category toggle is kept as such, so enabling the category restores its rules
+    rules.enable_category("typos")
+    fresh.enable_category("typos")
+    assert fresh.export_config() == rules.export_config()
+
+    fresh = Rules("storage/en_rules.bin", tokenizer)
+    fresh.config_from_json(rules.config_to_json())
+    assert fresh.export_config() == rules.export_config()
+
+    config = rules.export_config()
+    config["disabled"].append("NOT_A_CATEGORY/not_a_group/0")
+    with pytest.raises(ValueError):
+        fresh.apply_config(config)
+
+
+def test_max_replacement_distance(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "She was not been here since Monday."
+    suggestions = rules.suggest(text)
+    assert len(suggestions) > 0
+    assert all(s.replacement_distance > 0 for s in suggestions)
+
+    result = rules.suggest(text, collect_suppressed=True, max_replacement_distance=0)
+    assert len(result.suggestions) == 0
+    assert "replacement_distance" in [reason for (_, reason) in result.suppressed]
+
+    sources = [s.source for s in suggestions]
+    assert [
+        s.source
+        for s in rules.suggest(
+            text, max_replacement_distance=0, replacement_distance_exceptions=sources
+        )
+    ] == sources
+
+
+def test_min_severity(tokenizer_and_rules):
+    (tokenizer, _) = tokenizer_and_rules
+    rules = Rules("storage/en_rules.bin", tokenizer)
+
+    text = "He was taken back by my response."
+    (suggestion,) = rules.suggest(text)
+    rule = rules.select(suggestion.source)[0]
+    assert rule.category_type == "misspelling"
+    assert suggestion.severity == rule.severity == "error"
+    assert len(rules.suggest(text, min_severity="error")) == 1
+
+    rules.set_severity(suggestion.source, "hint")
+    assert rules.suggest(text)[0].severity == "hint"
+    result = rules.suggest(text, collect_suppressed=True, min_severity="warning")
+    assert len(result.suggestions) == 0
+    assert "severity" in [reason for (_, reason) in result.suppressed]
+
+    with pytest.raises(ValueError):
+        rules.set_severity(suggestion.source, "fatal")
+    with pytest.raises(ValueError):
+        rules.suggest(text, min_severity="fatal")
+
+
+def test_oversized_tokens(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    token = "a" * 1000
+    sentence = tokenizer.pipe(f"This is {token}.")[0]
+
+    assert [t.oversized for t in sentence] == [False, False, False, True, False]
+    assert sentence[3].span == (8, 1008)
+
+
+def test_token_is_known(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    sentence = tokenizer.pipe("The hosue is blue.")[0]
+
+    # the first token is the sentence start
+    assert [t.is_known for t in sentence] == [True, True, False, True, True, True]
+
+
+def test_token_classes(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    sentence = tokenizer.pipe("The house is in Berlin.")[0]
+
+    assert "stopword" in sentence[1].classes
+    assert "auxiliary" in sentence[3].classes
+    assert "preposition" in sentence[4].classes
+    assert sentence[2].classes == []
+
+
+def test_tokenize_to_file(tokenizer_and_rules, tmp_path):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    texts = ["A first sentence. A second one.", "Another text."]
+    path = str(tmp_path / "tokens.bin")
+    tokenizer.tokenize_to_file(texts, path)
+
+    documents = list(TokenReader(path))
+    assert len(documents) == 2
+
+    for (text, document) in zip(texts, documents):
+        sentences = tokenizer.pipe(text)
+        assert len(sentences) == len(document)
+
+        for (tokens, (start, exported)) in zip(sentences, document):
+            assert [t.text for t in tokens] == [t.text for t in exported]
+            # token spans are relative to the text, exported spans to the sentence
+            assert [t.span for t in tokens] == [
+                (t.span[0] + start, t.span[1] + start) for t in exported
+            ]
+            assert [t.data for t in tokens] == [t.data for t in exported]
+            assert [t.chunks for t in tokens] == [t.chunks for t in exported]
+
+    assert documents[0][1][0] == len("A first sentence. ")
+
+
+def test_strictness(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "This is {}.".format("a" * 1000)
+
+    try:
+        for strictness in ["lenient", "warn"]:
+            set_strictness(strictness)
+            rules.suggest(text)
+
+        set_strictness("strict")
+        with pytest.raises(ValueError, match="degraded"):
+            rules.suggest(text)
+        # clean text is not affected
+        rules.suggest("This is fine.")
+    finally:
+        set_strictness("lenient")
+
+    with pytest.raises(ValueError):
+        set_strictness("pedantic")
+
+
+def test_sentence_splitter_spans():
+    text = "A first sentence.  A second sentence!"
+
+    tokenizer = Tokenizer(
+        "storage/en_tokenizer.bin", sentence_splitter=SplitOn([".", "!"])
+    )
+    assert SplitOn([".", "!"])([text]) == [[(0, 19), (19, 37)]]
+
+    sentences = tokenizer.pipe(text)
+    assert len(sentences) == 2
+    assert sentences[1][1].text == "A"
+    assert sentences[1][1].span == (19, 20)
+
+    for spans in [[(5, 2)], [(0, 100)], [(10, 20), (0, 5)], [(0, 10), (5, 15)]]:
+        tokenizer = Tokenizer(
+            "storage/en_tokenizer.bin",
+            sentence_splitter=lambda texts: [spans for _ in texts],
+        )
+        with pytest.raises(ValueError):
+            tokenizer.pipe(text)
+
+
+def test_split_on_abbreviations():
+    text = "He works at Dr. Smith's office. It is nice."
+
+    # splits after every separator by default
+    assert len(SplitOn(["."])([text])[0]) == 3
+
+    assert SplitOn(["."], abbreviations="en")([text]) == [[(0, 32), (32, 43)]]
+    assert SplitOn(["."], abbreviations=["Dr."])([text]) == [[(0, 32), (32, 43)]]
+    assert len(SplitOn(["."], abbreviations="de")(["Das ist z.B. gut."])[0]) == 1
+
+    text = "It costs 3. 5 are left. the end."
+    assert len(SplitOn(["."], continue_on_lowercase=True)([text])[0]) == 1
+
+    splitter = SplitOn(["."], abbreviations="en", continue_on_lowercase=True)
+    assert pickle.loads(pickle.dumps(splitter))([text]) == splitter([text])
+
+    with pytest.raises(ValueError):
+        SplitOn(["."], abbreviations="xx")
+
+
+def test_sentence_splitter_strings():
+    text = "I can due his homework.   She was not been here since Monday."
+
+    def stripping_splitter(texts):
+        return [
+            [x.strip() + "." for x in text.split(".") if x.strip()] for text in texts
+        ]
+
+    tokenizer = Tokenizer(
+        "storage/en_tokenizer.bin", sentence_splitter=stripping_splitter
+    )
+    rules = Rules("storage/en_rules.bin", tokenizer)
+
+    sentences = tokenizer.pipe(text)
+    assert len(sentences) == 2
+    assert sentences[1][1].span == (26, 29)
+
+    default_rules = Rules(
+        "storage/en_rules.bin", Tokenizer("storage/en_tokenizer.bin")
+    )
+    assert [(s.start, s.end) for s in rules.suggest(text)] == [
+        (s.start, s.end) for s in default_rules.suggest(text)
+    ]
+    assert rules.correct(text) == default_rules.correct(text)
+
+    def lowercasing_splitter(texts):
+        return [[x.lower() for x in stripping_splitter([text])[0]] for text in texts]
+
+    tokenizer = Tokenizer(
+        "storage/en_tokenizer.bin", sentence_splitter=lowercasing_splitter
+    )
+    with pytest.raises(ValueError, match="can not be aligned"):
+        tokenizer.pipe(text)
+
+
+def test_correct_keeps_whitespace_between_sentences():
+    text = "Hello there.  How are you?\n\nI am fine.\tThanks!\n"
+
+    def stripping_splitter(texts):
+        return [
+            [x.strip() for x in text.replace("\t", "\n").splitlines() if x.strip()]
+            for text in texts
+        ]
+
+    for splitter in [None, stripping_splitter]:
+        tokenizer = Tokenizer("storage/en_tokenizer.bin", sentence_splitter=splitter)
+        rules = Rules("storage/en_rules.bin", tokenizer)
+
+        assert rules.suggest(text) == []
+        assert rules.correct(text) == text
+
+
+def test_inline_diff(tokenizer_and_rules):
+    (_, rules) = tokenizer_and_rules
+
+    text = "She was not been here since Monday."
+    suggestion = rules.suggest(text)[0]
+    edits = suggestion.inline_diff(text)
+
+    assert all(kind in ("equal", "insert", "delete") for (kind, _) in edits)
+    assert "".join(x for (kind, x) in edits if kind != "insert") == text[
+        suggestion.start : suggestion.end
+    ]
+    assert (
+        "".join(x for (kind, x) in edits if kind != "delete")
+        == suggestion.replacements[0]
+    )
+
+    with pytest.raises(IndexError):
+        suggestion.inline_diff(text, len(suggestion.replacements))
+
+
+def test_model_info(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    for info in [tokenizer.info, rules.info]:
+        assert info["language"] == "en"
+        assert len(info["checksum"]) > 0
+        assert set(info.keys()) == {
+            "language",
+            "crate_version",
+            "build_date",
+            "lt_version",
+            "checksum",
+        }
+
+
+def test_message_overrides(tokenizer_and_rules, tmp_path):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "I can due his homework"
+    source = rules.suggest(text)[0].source
+
+    rules.set_message(source, 'Consider using "do" here.')
+    assert rules.suggest(text)[0].message == 'Consider using "do" here.'
+    assert rules.export_messages()[source] == 'Consider using "do" here.'
+
+    path = tmp_path / "messages.json"
+    path.write_text(json.dumps({source: "Did you mean another word?"}))
+    rules.load_message_overrides(str(path))
+    assert rules.suggest(text)[0].message == "Did you mean another word?"
+
+    path.write_text(json.dumps({"NOT_A_CATEGORY/not_a_group/0": "Never shown."}))
+    with pytest.raises(ValueError):
+        rules.load_message_overrides(str(path))
+
+
+def test_ignore_patterns(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "I can due his homework."
+    assert len(rules.suggest(text)) > 0
+
+    result = rules.suggest(
+        text,
+        collect_suppressed=True,
+        report=True,
+        ignore_patterns=[r"[A-Z]+-\d+", "due"],
+    )
+    assert result.suggestions == []
+    assert "ignored_text" in [reason for (_, reason) in result.suppressed]
+    assert result.report["ignored_text"] > 0
+
+    with pytest.raises(ValueError, match="index 1"):
+        rules.suggest(text, ignore_patterns=["due", "[unclosed"])
+
+
+def test_suggest_report(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "I can due his homework. This is fine."
+    result = rules.suggest(text, report=True)
+    assert len(result.suggestions) == 1
+    assert result.suppressed is None
+    report = result.report
+    assert [x["disposition"] for x in report["sentences"]] == ["checked", "checked"]
+    assert report["rules_missing_chunker"] == []
+    assert report["malformed_suggestions"] == 0
+
+    result = rules.suggest(text, collect_suppressed=True, report=True)
+    assert len(result.report["sentences"]) == 2
+    assert result.suppressed is not None
+
+    results = rules.suggest([text, "This is fine."], report=True)
+    assert [len(x.suggestions) for x in results] == [1, 0]
+    assert [len(x.report["sentences"]) for x in results] == [2, 1]
+
+
+def test_on_suggestion(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    texts = ["I can due his homework. He wants see it.", "This is fine."]
+    observed = []
+    suggestions = rules.suggest(texts, on_suggestion=observed.append)
+
+    assert [(s.source, s.start, s.end) for s in observed] == [
+        (s.source, s.start, s.end) for batch in suggestions for s in batch
+    ]
+
+    observed = []
+    corrected = rules.correct(texts[0], on_suggestion=observed.append)
+    assert len(observed) > 0
+    assert corrected == rules.correct(texts[0])
+
+    def fail(suggestion):
+        raise RuntimeError("observer failed")
+
+    with pytest.raises(RuntimeError, match="observer failed"):
+        rules.suggest(texts, on_suggestion=fail)
+
+    # the rules are still usable
+    assert [len(batch) for batch in rules.suggest(texts)] == [
+        len(batch) for batch in suggestions
+    ]
+
+
+def test_mixed_direction_text(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "He said שלום עולם to to me."
+    suggestions = rules.suggest(text)
+    assert len(suggestions) > 0
+
+    corrected = rules.correct(text)
+    assert corrected == "He said שלום עולם to me."
+    assert rules.apply_suggestions(text, suggestions[::-1]) == corrected
+
+    isolated = rules.correct(text, isolate_bidi=True)
+    assert "שלום עולם" in isolated
+
+
+def test_conflict_policy(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "She was not been here since Monday."
+    corrected = rules.correct(text)
+    for policy in ["earliest", "longest", "priority"]:
+        assert rules.correct(text, conflict_policy=policy) == corrected
+
+    # overlapping suggestions are applied once
+    suggestion = rules.suggest(text)[0]
+    assert rules.apply_suggestions(text, [suggestion, suggestion]) == corrected
+
+    with pytest.raises(ValueError):
+        rules.correct(text, conflict_policy="latest")
+
+
+def test_correct_with_trace(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "She was not been here since Monday."
+    (corrected, trace) = rules.correct(text, return_trace=True)
+    assert corrected == rules.correct(text)
+    assert len(trace["applied"]) > 0
+
+    for applied in trace["applied"]:
+        assert text[applied["start"] : applied["end"]] == applied["text"]
+        assert applied["replacement"] in corrected
+
+    texts = [text, "I can due his homework."]
+    traced = rules.correct(texts, return_trace=True)
+    assert [x[0] for x in traced] == rules.correct(texts)
+
+
+def test_correct_with_selector(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "She was not been here since Monday. I can due his homework."
+    suggestions = rules.suggest(text)
+    assert len(suggestions) >= 2
+    (first, last) = (suggestions[0], suggestions[-1])
+    index = len(first.replacements) - 1
+
+    assert rules.correct(text, selector=lambda s: 0) == rules.correct(text)
+    assert rules.correct(text, selector=lambda s: None) == text
+
+    # choose the last replacement of the first suggestion and skip all others
+    corrected = rules.correct(
+        text, selector=lambda s: index if s.start == first.start else None
+    )
+    assert corrected == (
+        text[: first.start] + first.replacements[index] + text[first.end :]
+    )
+
+    # later suggestions are applied after a replacement of another length
+    corrected = rules.correct(
+        text, selector=lambda s: index if s.start == first.start else 0
+    )
+    assert corrected.startswith(text[: first.start] + first.replacements[index])
+    assert corrected.endswith(last.replacements[0] + text[last.end :])
+
+    with pytest.raises(IndexError):
+        rules.correct(text, selector=lambda s: len(s.replacements))
+
+
+def test_suggestions_are_relative_to_text(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "😊 Ünïcödé is fine.😊 I can due his homework. 😊😊 He wants see it."
+    suggestions = rules.suggest(text)
+    assert len(suggestions) >= 2
+
+    for suggestion in suggestions:
+        flagged = text[suggestion.start : suggestion.end]
+        assert flagged.strip() == flagged and len(flagged) > 0
+
+    assert "due" in [text[s.start : s.end] for s in suggestions]
+
+
+def test_span_info(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "😊 I can due his homework."
+    suggestion = [s for s in rules.suggest(text) if text[s.start : s.end] == "due"][0]
+    span = suggestion.span_info
+    assert span.char == (suggestion.start, suggestion.end)
+    assert span.slice(text) == "due"
+    assert text.encode("utf-8")[span.byte[0] : span.byte[1]] == b"due"
+    assert span.utf16(text) == (span.char[0] + 1, span.char[1] + 1)
+
+    token = tokenizer.pipe(text)[0][1]
+    assert token.span_info.slice(text) == "😊"
+    assert token.span_info.byte == (0, 4)
+    assert token.span_info.utf16(text) == (0, 2)
+
+    with pytest.raises(ValueError):
+        span.slice("")
+
+
+def test_token_sentence_index_and_offsets(tokenizer_and_rules):
+    (tokenizer, _) = tokenizer_and_rules
+
+    text = "Grüße aus Köln. The dogs bark! Ünïcödé 😊 is fine."
+    encoded = text.encode("utf-8")
+    sentences = tokenizer.pipe(text)
+    assert len(sentences) == 3
+
+    for (i, tokens) in enumerate(sentences):
+        assert all(t.sentence_index == i for t in tokens)
+        for token in tokens:
+            (start, end) = token.span
+            (byte_start, byte_end) = token.byte_span
+            assert text[start:end] == token.original_text
+            assert encoded[byte_start:byte_end].decode("utf-8") == token.original_text
+
+    assert sentences[2][1].span == (31, 38)
+
+
+def test_whitespace_before_reproduces_the_sentence(tokenizer_and_rules):
+    (tokenizer, _) = tokenizer_and_rules
+
+    text = "This  is\ta \n test ,  with\u00a0odd   spacing."
+    tokens = tokenizer.pipe(text)[0]
+    assert "".join(t.whitespace_before + t.original_text for t in tokens) == text
+    assert [t.has_space_before for t in tokens] == [
+        t.whitespace_before != "" for t in tokens
+    ]
+    assert [t.whitespace_before for t in tokens if t.text == "test"] == [" \n "]
+
+
+def test_suggestion_byte_span(tokenizer_and_rules):
+    (_, rules) = tokenizer_and_rules
+
+    # the flagged span is in the second sentence, after multi-byte characters
+    text = "Grüße 😊. She was not been here since Monday."
+    encoded = text.encode("utf-8")
+    suggestions = rules.suggest(text)
+    assert len(suggestions) > 0
+    for suggestion in suggestions:
+        flagged = text[suggestion.start : suggestion.end]
+        assert encoded[suggestion.byte_start : suggestion.byte_end].decode() == flagged
+        assert (suggestion.byte_start, suggestion.byte_end) == suggestion.span_info.byte
+
+
+def test_tagger_batch_lookup_matches_single_lookups(tokenizer_and_rules):
+    (tokenizer, _) = tokenizer_and_rules
+    tagger = tokenizer.tagger
+
+    words = ["dogs", "xyzzyqux", "The", "", "walked", "dogs"] * 100
+    batch = tagger.get_data_batch(words, add_lower=True)
+    assert batch == [tagger.get_data(word, add_lower=True) for word in words]
+    assert batch[1] == []
+    assert ("dog", "NNS") in batch[0]
+
+
+def test_token_byte_span(tokenizer_and_rules):
+    (tokenizer, _) = tokenizer_and_rules
+
+    # multi-byte characters before, inside and after tokens
+    text = "Über 😊 Grüße für Ünïcödé 🎉 hier"
+    encoded = text.encode("utf-8")
+    tokens = tokenizer.pipe(text)[0][1:]
+    for token in tokens:
+        (start, end) = token.span
+        (byte_start, byte_end) = token.byte_span
+        assert encoded[byte_start:byte_end].decode("utf-8") == text[start:end]
+        assert text[start:end] == token.original_text
+        assert token.byte_span == token.span_info.byte
+
+    assert [t.byte_span for t in tokens if t.text == "Grüße"] == [(11, 18)]
+
+
+def test_timings(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    texts = ["I can due his homework.", "This is fine. He wants see it."]
+    results = rules.suggest(texts, timings=True)
+    assert [[s.replacements for s in x.suggestions] for x in results] == [
+        [s.replacements for s in x] for x in rules.suggest(texts)
+    ]
+    assert len(results) == len(texts)
+
+    for result in results:
+        timing = result.timings
+        assert set(timing) == {"tokenize", "disambiguate", "rules", "total"}
+        stages = timing["tokenize"] + timing["disambiguate"] + timing["rules"]
+        assert timing["total"] >= stages
+
+    result = rules.suggest(texts[0], collect_suppressed=True, timings=True)
+    assert result.timings["rules"] > 0
+    assert result.suppressed is not None
+
+    sentences, timings = tokenizer.pipe(texts, timings=True)
+    assert len(sentences) == len(timings) == len(texts)
+    assert all(timing["rules"] == 0 for timing in timings)
+
+
+def test_max_chars(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "I can due his homework. This is fine. He wants see it."
+    result = rules.suggest(text, max_chars=30, report=True)
+    (suggestions, report) = (result.suggestions, result.report)
+    truncated_at = report["truncated_at"]
+
+    assert truncated_at["sentence_index"] == len(report["sentences"]) == 1
+    offset = truncated_at["char_offset"]
+    assert text[offset:] == "This is fine. He wants see it."
+    assert all(s.end <= offset for s in suggestions)
+
+    assert rules.suggest(text, report=True).report["truncated_at"] is None
+
+
+def test_token_exceptions(tokenizer_and_rules):
+    (tokenizer, _) = tokenizer_and_rules
+
+    text = "I use C++, e.g. for games."
+    words = [t.text for t in tokenizer.pipe(text)[0]]
+    assert "e.g." in words and "C++" not in words
+
+    # the tokenizer is shared with the rules
+    with pytest.raises(ValueError):
+        tokenizer.add_token_exceptions(["C++"])
+
+    tokenizer = Tokenizer("storage/en_tokenizer.bin")
+    tokenizer.add_token_exceptions(["C++"])
+    tokens = tokenizer.pipe(text)[0]
+    assert "C++" in [t.text for t in tokens]
+    assert all(text[t.span[0] : t.span[1]] == t.original_text for t in tokens)
+
+
+def test_added_words_are_tagged(tokenizer_and_rules):
+    (tokenizer, _) = tokenizer_and_rules
+
+    # the tokenizer is shared with the rules
+    with pytest.raises(ValueError):
+        tokenizer.add_words([("Nlprule", "Nlprule", "NNP")])
+
+    tokenizer = Tokenizer("storage/en_tokenizer.bin")
+    assert tokenizer.tagger.get_data("Nlprule") == []
+
+    # a `Tagger` object shares the dictionary, it is not copied behind its back
+    tagger = tokenizer.tagger
+    with pytest.raises(ValueError):
+        tokenizer.add_words([("Nlprule", "Nlprule", "NNP")])
+    del tagger
+
+    tokenizer.add_words([("Nlprule", "Nlprule", "NNP")])
+    assert tokenizer.tagger.get_data("Nlprule") == [("Nlprule", "NNP")]
+
+    tokens = tokenizer.pipe("I like Nlprule.")[0]
+    token = [t for t in tokens if t.text == "Nlprule"][0]
+    assert ("Nlprule", "NNP") in token.data
+
+    with pytest.raises(ValueError):
+        tokenizer.add_words([("word", "word", "NOT_A_TAG")])
+
+
+def test_tagger_overrides_take_precedence(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    def fires(rules):
+        text = "I can due his homework."
+        sources = [s.source.split("/")[-1] for s in rules.suggest(text)]
+        return "CONFUSION_DUE_DO" in sources
+
+    assert fires(rules)
+
+    # the tokenizer is shared with the rules
+    with pytest.raises(ValueError):
+        tokenizer.override_tags("can", [("can", "NN")])
+
+    tokenizer = Tokenizer("storage/en_tokenizer.bin")
+    tokenizer.override_tags("can", [("can", "NN")])
+    assert tokenizer.tagger.get_data("can") == [("can", "NN")]
+
+    # the rule matches "can" as a modal verb so it no longer fires
+    assert not fires(Rules("storage/en_rules.bin", tokenizer))
+
+    tokenizer = Tokenizer("storage/en_tokenizer.bin")
+    assert tokenizer.remove_word("homework")
+    assert tokenizer.tagger.get_data("homework") == []
+    assert not tokenizer.remove_word("homework")
+
+    with pytest.raises(ValueError):
+        tokenizer.override_tags("can", [("can", "NOT_A_TAG")])
+
+
+def test_tagger_words_are_sorted(tokenizer_and_rules):
+    (tokenizer, _) = tokenizer_and_rules
+
+    words = tokenizer.tagger.words()
+    first = [next(words) for _ in range(1000)]
+    assert [word for (word, _) in first] == sorted(word for (word, _) in first)
+
+    data = dict(tokenizer.tagger.words())
+    assert ("house", "NN") in data["house"]
+    assert ("house", "NNS") in data["houses"]
+    assert ("be", "VBZ") in data["is"]
+    assert data["is"] == tokenizer.tagger.get_data("is", add_lower=False)
+
+
+def test_group_members_can_be_filtered_by_pos(tokenizer_and_rules):
+    (tokenizer, _) = tokenizer_and_rules
+    tagger = tokenizer.tagger
+
+    members = tagger.get_group_members("walk")
+    assert {"walk", "walks", "walked", "walking"} <= set(members)
+
+    verbs = tagger.get_group_members("walk", pos="VB.*")
+    assert {"walks", "walked", "walking"} <= set(verbs) <= set(members)
+    assert tagger.get_group_members("walk", pos="VBD") == ["walked"]
+
+    # words which are not a lemma have no group
+    assert tagger.get_group_members("walked") == []
+    assert tagger.get_group_members("notaword") == []
+
+
+def test_tokenizer_options_can_be_overridden():
+    tokenizer = Tokenizer("storage/en_tokenizer.bin")
+    options = tokenizer.options
+    assert not options["rejoin_hyphenation"]
+    assert not options["use_compound_split_heuristic"]
+
+    text = "The infor-\nmation is in the bluehouse."
+    words = [t.text for t in tokenizer.pipe(text)[0]]
+    assert "information" not in words
+    assert tokenizer.tagger.get_data("bluehouse") == []
+
+    tokenizer = Tokenizer(
+        "storage/en_tokenizer.bin",
+        options={"rejoin_hyphenation": True, "use_compound_split_heuristic": True},
+    )
+    assert tokenizer.options == {
+        **options,
+        "rejoin_hyphenation": True,
+        "use_compound_split_heuristic": True,
+    }
+    words = [t.text for t in tokenizer.pipe(text)[0]]
+    assert "information" in words
+    assert ("house", "NN") in tokenizer.tagger.get_data("bluehouse")
+
+    with pytest.raises(ValueError):
+        Tokenizer("storage/en_tokenizer.bin", options={"not_an_option": True})
+
+    with pytest.raises(ValueError):
+        Tokenizer("storage/en_tokenizer.bin", options={"max_token_length": 0})
+
+
+def test_compound_split_heuristic_can_be_set_per_call():
+    tokenizer = Tokenizer(
+        "storage/en_tokenizer.bin", options={"use_compound_split_heuristic": True}
+    )
+
+    def data(**kwargs):
+        tokens = tokenizer.pipe("I like the bluehouse.", **kwargs)[0]
+        return [t for t in tokens if t.text == "bluehouse"][0].data
+
+    assert ("bluehouse", "NN") in data()
+    assert ("bluehouse", "NN") in data(use_compound_split_heuristic=True)
+    assert data(use_compound_split_heuristic=False) == []
+
+    tagger = tokenizer.tagger
+    assert tagger.get_data("bluehouse", use_compound_split_heuristic=False) == []
+    assert ("bluehouse", "NN") in tagger.get_data("bluehouse")
+
+
+def test_batch_pipe_matches_serial_pipe(tokenizer_and_rules):
+    (tokenizer, _) = tokenizer_and_rules
+
+    texts = [
+        f"I can due his homework {i} times. She was not been here since Monday."
+        for i in range(2000)
+    ] + ["", "   "]
+
+    def key(sentences):
+        return [
+            [(t.text, t.span, t.data, t.chunks, t.sentence_index) for t in tokens]
+            for tokens in sentences
+        ]
+
+    serial = [key(tokenizer.pipe(text)) for text in texts]
+    assert [key(x) for x in tokenizer.pipe(texts)] == serial
+    assert [key(x) for x in tokenizer.pipe(texts, n_threads=2)] == serial
+
+    with pytest.raises(ValueError):
+        tokenizer.pipe(texts, n_threads=0)
+
+
+def test_schema_version(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    assert nlprule.SCHEMA_VERSION >= 1
+
+    report = rules.suggest("I can due his homework.", report=True).report
+    assert report["schema_version"] == nlprule.SCHEMA_VERSION
+
+    config = rules.export_config()
+    assert config["schema_version"] == nlprule.SCHEMA_VERSION
+    rules.apply_config(config)
+
+
+def test_correct_with_mapping(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    text = "I can due his homework."
+    corrected, mapping = rules.correct_with_mapping(text)
+    assert corrected == rules.correct(text) == "I can do his homework."
+
+    # text boundaries and positions before and after the edit
+    assert mapping.project(0) == 0
+    assert mapping.project(len(text)) == len(corrected)
+    assert mapping.project_range(10, 13) == (9, 12)
+    assert mapping.project_range_back(9, 12) == (10, 13)
+    # positions inside replaced spans are clamped by default
+    assert mapping.project(7) == 6
+    assert mapping.project_range(7, 8) == (6, 8)
+
+    with pytest.raises(IndexError):
+        mapping.project(len(text) + 1)
+
+    _, mapping = rules.correct_with_mapping(text, policy="error")
+    with pytest.raises(ValueError):
+        mapping.project(7)
+    assert mapping.project(9) == 8
+
+    with pytest.raises(ValueError):
+        rules.correct_with_mapping(text, policy="ignore")
+
+
+def test_download_prefetches_components_of_languages(tmp_path):
+    mirror = tmp_path / "mirror"
+    mirror.mkdir()
+    for code in ["en", "de"]:
+        binary = pathlib.Path("storage/en_tokenizer.bin").read_bytes()
+        (mirror / f"{code}_tokenizer.bin.gz").write_bytes(gzip.compress(binary))
+        (mirror / f"{code}_tokenizer.bin.sha256").write_text(
+            hashlib.sha256(binary).hexdigest()
+        )
+
+    cache_dir = str(tmp_path / "cache")
+    paths = nlprule.download(
+        ["en", "de"], ("tokenizer",), cache_dir=cache_dir, base_url=f"file://{mirror}"
+    )
+    assert len(paths) == 2
+    assert all(pathlib.Path(path).name == "tokenizer.bin" for path in paths)
+    assert pathlib.Path(paths[1]).parent.name == "de"
+
+    # already cached, so it works without the mirror
+    for path in mirror.iterdir():
+        path.unlink()
+    base_url = f"file://{mirror}"
+    assert nlprule.download("en", "tokenizer", cache_dir, base_url) == paths[:1]
+
+    with pytest.raises(ValueError, match="unknown component"):
+        nlprule.download("en", ["tagger"], cache_dir=cache_dir)
+
+
+def test_download_cli_fails_for_unknown_language(tmp_path, capsys):
+    from nlprule.__main__ import main
+
+    assert main(["download", "xx", "--cache-dir", str(tmp_path)]) == 1
+    assert "xx" in capsys.readouterr().err
+
+
+def test_token_and_suggestion_repr(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+
+    token = tokenizer.pipe("The dogs bark.")[0][2]
+    assert repr(token).startswith('Token(text="dogs", span=(4, 8), tags=["NNS"]')
+    assert 'lemmas=["dog"]' in repr(token)
+
+    suggestion = rules.suggest("She was not been here since Monday.")[0]
+    replacements = ", ".join(f'"{x}"' for x in suggestion.replacements)
+    assert set(suggestion.replacements) == {"was not", "has not been"}
+    assert str(suggestion) == f"4..16 -> [{replacements}]"
+    assert repr(suggestion) == (
+        f"Suggestion(start=4, end=16, replacements=[{replacements}], "
+        f'source="{suggestion.source}")'
+    )
+
+
+def test_invalid_binary_paths_raise_python_exceptions(tmp_path):
+    with pytest.raises(FileNotFoundError):
+        Tokenizer(str(tmp_path / "missing.bin"))
+    with pytest.raises(IsADirectoryError):
+        Rules(tmp_path)
+
+    garbage = tmp_path / "garbage.bin"
+    garbage.write_bytes(b"not a binary" * 100)
+    with pytest.raises(ValueError, match="does not start with an nlprule binary"):
+        Tokenizer(garbage)
+    # a rules binary passed as tokenizer binary
+    with pytest.raises(ValueError, match="expected a tokenizer binary, found a rules"):
+        Tokenizer("storage/en_rules.bin")
+
+    # the header of a binary built with another version: magic bytes, format version
+    # and the length-prefixed crate version
+    header = b"NLPRULE\0" + struct.pack("<IQ", 1, 5) + b"0.1.0"
+    with pytest.raises(ValueError, match=r"nlprule 0\.1\.0 .*Tokenizer\.load"):
+        Tokenizer.from_bytes(header)
+
+
+def test_core_errors_map_to_python_exceptions(tokenizer_and_rules, tmp_path):
+    (_, rules) = tokenizer_and_rules
+
+    with pytest.raises(FileNotFoundError):
+        rules.load_message_overrides(str(tmp_path / "missing.json"))
+    with pytest.raises(ValueError, match="no rule matches"):
+        rules.disable(["NOT_A_RULE_ID_AT_ALL"])
+
+
+def test_binaries_can_be_loaded_from_bytes():
+    tokenizer_bytes = pathlib.Path("storage/en_tokenizer.bin").read_bytes()
+    rules_bytes = pathlib.Path("storage/en_rules.bin").read_bytes()
+
+    tokenizer = Tokenizer.from_bytes(tokenizer_bytes)
+    rules = Rules.from_bytes(gzip.compress(rules_bytes), tokenizer)
+    assert len(rules.suggest("She was not been here since Monday.")) > 0
+    assert Tokenizer.from_bytes(gzip.compress(tokenizer_bytes)).info == tokenizer.info
+
+    with pytest.raises(ValueError, match="error creating Tokenizer"):
+        Tokenizer.from_bytes(tokenizer_bytes[:100])
+    with pytest.raises(ValueError, match="gzip"):
+        Rules.from_bytes(gzip.compress(rules_bytes)[:100], tokenizer)
+
+
+def test_binaries_are_loaded_from_the_cache_dir(tmp_path, monkeypatch):
+    cached = tmp_path / nlprule.__version__ / "en"
+    cached.mkdir(parents=True)
+    for name in ["tokenizer", "rules"]:
+        (cached / f"{name}.bin").write_bytes(
+            pathlib.Path(f"storage/en_{name}.bin").read_bytes()
+        )
+
+    # found without downloading
+    calls = []
+    tokenizer = Tokenizer.load(
+        "en", cache_dir=str(tmp_path), progress=lambda *args: calls.append(args)
+    )
+    assert calls == []
+    rules = Rules.load("en", tokenizer, cache_dir=str(tmp_path))
+    assert len(rules.suggest("She was not been here since Monday.")) > 0
+
+    monkeypatch.setenv("NLPRULE_CACHE_DIR", str(tmp_path))
+    assert Tokenizer.load("en").info == tokenizer.info
+
+
+def test_offline_load_only_uses_the_cache(tmp_path, monkeypatch):
+    with pytest.raises(FileNotFoundError, match="releases/download") as error:
+        Tokenizer.load("en", cache_dir=str(tmp_path), offline=True)
+    assert str(tmp_path) in str(error.value)
+
+    monkeypatch.setenv("NLPRULE_OFFLINE", "1")
+    with pytest.raises(FileNotFoundError):
+        Tokenizer.load("en", cache_dir=str(tmp_path))
+
+
+def test_corrupt_cached_binaries_are_discarded(tmp_path):
+    cached = tmp_path / nlprule.__version__ / "en"
+    cached.mkdir(parents=True)
+    binary = pathlib.Path("storage/en_tokenizer.bin").read_bytes()
+    (cached / "tokenizer.bin").write_bytes(binary[: len(binary) // 2])
+    (cached / "tokenizer.bin.sha256").write_text(hashlib.sha256(binary).hexdigest())
+
+    with pytest.raises(FileNotFoundError):
+        Tokenizer.load("en", cache_dir=str(tmp_path), offline=True)
+    assert not (cached / "tokenizer.bin").exists()
+
+    (cached / "tokenizer.bin").write_bytes(binary)
+    Tokenizer.load("en", cache_dir=str(tmp_path), offline=True)
+
+
+def test_binaries_can_be_downloaded_from_a_mirror(tmp_path, monkeypatch):
+    mirror = tmp_path / "mirror"
+    mirror.mkdir()
+    binary = pathlib.Path("storage/en_tokenizer.bin").read_bytes()
+    (mirror / "en_tokenizer.bin.gz").write_bytes(gzip.compress(binary))
+    (mirror / "en_tokenizer.bin.sha256").write_text(
+        hashlib.sha256(binary).hexdigest() + "  tokenizer.bin\n"
+    )
+
+    cache_dir = tmp_path / "cache"
+    monkeypatch.setenv("NLPRULE_BINARY_URL", f"file://{mirror}/{{code}}_{{name}}")
+    tokenizer = Tokenizer.load("en", cache_dir=str(cache_dir))
+    assert tokenizer.info == Tokenizer("storage/en_tokenizer.bin").info
+
+    # cached separately from binaries of the default source
+    cached = list(cache_dir.glob("**/tokenizer.bin"))
+    assert len(cached) == 1
+    assert "mirrors" in cached[0].parts
+    with pytest.raises(FileNotFoundError):
+        Tokenizer.load("en", cache_dir=str(cache_dir), offline=True, base_url="")
+
+    # `base_url` takes precedence and may be the directory of the binaries
+    monkeypatch.delenv("NLPRULE_BINARY_URL")
+    Tokenizer.load(
+        "en", cache_dir=str(tmp_path / "other"), base_url=f"file://{mirror}/"
+    )
+
+
+def test_unreachable_downloads_fail_fast(tmp_path):
+    start = time.monotonic()
+    # a non-routable address, connecting to it hangs until the timeout
+    with pytest.raises(OSError, match="10.255.255.1"):
+        Tokenizer.load(
+            "en",
+            cache_dir=str(tmp_path),
+            base_url="http://10.255.255.1/nlprule",
+            timeout=1,
+        )
+    assert time.monotonic() - start < 15
+
+    with pytest.raises(ValueError, match="timeout"):
+        Tokenizer.load("en", cache_dir=str(tmp_path), timeout=0)
+
+
+def test_corrupt_downloads_are_rejected(tmp_path):
+    mirror = tmp_path / "mirror"
+    mirror.mkdir()
+    binary = pathlib.Path("storage/en_tokenizer.bin").read_bytes()
+    (mirror / "en_tokenizer.bin.gz").write_bytes(gzip.compress(binary))
+    base_url = f"file://{mirror}/{{code}}_{{name}}"
+    cache_dir = tmp_path / "cache"
+
+    # without a published checksum, the binary is not downloaded at all
+    downloads = []
+    with pytest.raises(OSError, match="checksum"):
+        Tokenizer.load(
+            "en",
+            cache_dir=str(cache_dir),
+            base_url=base_url,
+            progress=lambda *args: downloads.append(args),
+        )
+    assert downloads == []
+
+    # a mismatch is retried once
+    (mirror / "en_tokenizer.bin.sha256").write_text(hashlib.sha256(b"").hexdigest())
+    with pytest.raises(nlprule.ChecksumMismatchError, match="^checksum mismatch") as error:
+        Tokenizer.load(
+            "en",
+            cache_dir=str(cache_dir),
+            base_url=base_url,
+            progress=lambda *args: downloads.append(args),
+        )
+    assert isinstance(error.value, OSError)
+    assert str(cache_dir) in str(error.value)
+    assert len(downloads) == 2
+    assert list(cache_dir.glob("**/tokenizer.bin")) == []
+
+
+def test_concurrent_loads_download_once(tmp_path):
+    mirror = tmp_path / "mirror"
+    mirror.mkdir()
+    binary = pathlib.Path("storage/en_tokenizer.bin").read_bytes()
+    (mirror / "en_tokenizer.bin.gz").write_bytes(gzip.compress(binary))
+    (mirror / "en_tokenizer.bin.sha256").write_text(hashlib.sha256(binary).hexdigest())
+
+    # `progress` is called once per download of a `file://` URL
+    downloads = []
+
+    def load(_):
+        return Tokenizer.load(
+            "en",
+            cache_dir=str(tmp_path / "cache"),
+            base_url=f"file://{mirror}",
+            progress=lambda *args: downloads.append(args),
+        )
+
+    with ThreadPoolExecutor(8) as executor:
+        tokenizers = list(executor.map(load, range(16)))
+
+    assert len(downloads) == 1
+    assert all(tokenizer.info == tokenizers[0].info for tokenizer in tokenizers)
+    # no temporary files are left behind
+    assert not list((tmp_path / "cache").glob("**/*.tmp*"))
+
+
+def test_warm_up_does_not_change_suggestions(tokenizer_and_rules):
+    (tokenizer, rules) = tokenizer_and_rules
+    text = "She was not been here since Monday. I can due his homework."
+    before = [(s.source, s.replacements) for s in rules.suggest(text)]
+
+    rules = Rules("storage/en_rules.bin", tokenizer)
+    rules.warm_up()
+
+    assert [(s.source, s.replacements) for s in rules.suggest(text)] == before
+
+
+def test_compressed_binaries_can_be_loaded(tmp_path):
+    binary = pathlib.Path("storage/en_tokenizer.bin").read_bytes()
+    (tmp_path / "tokenizer.bin.gz").write_bytes(gzip.compress(binary))
+    text = "She was not been here since Monday."
+
+    def tokens(tokenizer):
+        return [
+            [(token.text, token.lemmas, token.tags) for token in sentence]
+            for sentence in tokenizer.pipe(text)
+        ]
+
+    expected = tokens(Tokenizer(pathlib.Path("storage") / "en_tokenizer.bin"))
+
+    assert tokens(Tokenizer(tmp_path / "tokenizer.bin.gz")) == expected
+    assert tokens(Tokenizer.from_bytes(gzip.compress(binary))) == expected